@@ -0,0 +1,612 @@
+//! Bonding curves, where the price of token 0 is a function of the amount sold
+//!
+//! Token 0 is the launched mint and token 1 the quote, whose reserve (including the
+//! virtual offset) is exactly the integral of the price over the amount sold. The amount
+//! sold is recovered from the quote reserve, so the mint reserve only bounds how much can be
+//! bought.
+
+use crate::{
+    curve::calculator::{Curve, TradeDirection},
+    error::ErrorCode,
+    states::Q32,
+    utils::{exp_x64, ln_x64, to_u128, LN2_X64, ONE_X64, U256},
+};
+use anchor_lang::prelude::*;
+
+/// The fixed point ln may overestimate by `k * ln(2)` rounding, a few ulps at most
+const LN_ERROR: i128 = 256;
+/// The fixed point exp is rounded by 2^-40 relative, well above its error
+const EXP_ERROR_SHIFT: usize = 40;
+
+/// Price of token 0 as a function of the amount sold
+trait BondingCurve {
+    /// Amount sold for the given quote reserve, rounded down
+    fn amount_sold(&self, quote_amount: u128) -> Option<u128>;
+
+    /// Quote reserve backing the given amount sold, rounded up or down
+    fn reserve(&self, amount_sold: u128, round_up: bool) -> Option<u128>;
+
+    /// Marginal price after the given amount sold, as a Q32 fixed point number
+    fn price_x32(&self, amount_sold: u128) -> Option<u128>;
+
+    /// Buying pays quote into the curve, and gets the amount sold in between
+    fn buy_base_input(
+        &self,
+        quote_amount: u128,
+        swap_quote_amount: u128,
+        swap_mint_amount: u128,
+    ) -> Option<u128> {
+        let amount_sold = self.amount_sold(swap_quote_amount)?;
+        let mint_amount = self
+            .amount_sold(swap_quote_amount.checked_add(quote_amount)?)?
+            .checked_sub(amount_sold)?;
+        (mint_amount < swap_mint_amount).then_some(mint_amount)
+    }
+
+    fn buy_base_output(
+        &self,
+        mint_amount: u128,
+        swap_quote_amount: u128,
+        swap_mint_amount: u128,
+    ) -> Option<u128> {
+        if mint_amount >= swap_mint_amount {
+            return None;
+        }
+        let amount_sold = self.amount_sold(swap_quote_amount)?;
+        self.reserve(amount_sold.checked_add(mint_amount)?, true)?
+            .checked_sub(swap_quote_amount)
+    }
+
+    /// Selling only releases the quote backing the amount sold, never the rounding dust
+    fn sell_base_input(&self, mint_amount: u128, swap_quote_amount: u128) -> Option<u128> {
+        let amount_sold = self.amount_sold(swap_quote_amount)?;
+        // Rounding both reserves against the seller may leave nothing for a tiny sale
+        Some(
+            self.reserve(amount_sold, false)?
+                .saturating_sub(self.reserve(amount_sold.checked_sub(mint_amount)?, true)?),
+        )
+    }
+
+    fn sell_base_output(&self, quote_amount: u128, swap_quote_amount: u128) -> Option<u128> {
+        let amount_sold = self.amount_sold(swap_quote_amount)?;
+        let quote_amount_left = self
+            .reserve(amount_sold, false)?
+            .checked_sub(quote_amount)?;
+        amount_sold.checked_sub(self.amount_sold(quote_amount_left)?)
+    }
+
+    fn swap_base_input(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.sell_base_input(source_amount, swap_destination_amount)
+            }
+            TradeDirection::OneForZero => {
+                self.buy_base_input(source_amount, swap_source_amount, swap_destination_amount)
+            }
+        }
+    }
+
+    fn swap_base_output(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        match trade_direction {
+            TradeDirection::ZeroForOne => {
+                self.sell_base_output(destination_amount, swap_destination_amount)
+            }
+            TradeDirection::OneForZero => self.buy_base_output(
+                destination_amount,
+                swap_source_amount,
+                swap_destination_amount,
+            ),
+        }
+    }
+
+    fn spot_price(&self, swap_token_1_amount: u128) -> Option<(u128, u128)> {
+        let token_0_price_x32 = self.price_x32(self.amount_sold(swap_token_1_amount)?)?;
+        Some((
+            token_0_price_x32,
+            (Q32 * Q32).checked_div(token_0_price_x32)?,
+        ))
+    }
+
+    /// The mint reserve plus the amount sold, which only grows with rounding
+    fn mint_invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<U256> {
+        U256::from(swap_token_0_amount)
+            .checked_add(U256::from(self.amount_sold(swap_token_1_amount)?))
+    }
+}
+
+/// Price rising linearly with the amount sold
+/// price = initial_price * (1 + sold / scale_amount)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LinearBondingCurve {
+    /// Price of token 0 before any sale, as a Q32 fixed point number
+    pub initial_price_x32: u64,
+    /// Amount sold for the price to rise by the initial price
+    pub scale_amount: u64,
+}
+
+impl BondingCurve for LinearBondingCurve {
+    /// reserve = initial_price * (sold + sold^2 / (2 * scale_amount))
+    /// sold = sqrt(scale_amount^2 + 2 * scale_amount * reserve / initial_price) - scale_amount
+    fn amount_sold(&self, quote_amount: u128) -> Option<u128> {
+        let scale_amount = U256::from(self.scale_amount);
+        let discriminant = scale_amount.checked_mul(scale_amount)?.checked_add(
+            scale_amount
+                .checked_mul(U256::from(quote_amount))?
+                .checked_mul(U256::from(2 * Q32))?
+                .checked_div(U256::from(self.initial_price_x32))?,
+        )?;
+        to_u128(discriminant.integer_sqrt().checked_sub(scale_amount)?)
+    }
+
+    fn reserve(&self, amount_sold: u128, round_up: bool) -> Option<u128> {
+        let amount_sold = U256::from(amount_sold);
+        let scale_amount = U256::from(self.scale_amount);
+        let numerator = U256::from(self.initial_price_x32).checked_mul(
+            scale_amount
+                .checked_mul(U256::from(2))?
+                .checked_mul(amount_sold)?
+                .checked_add(amount_sold.checked_mul(amount_sold)?)?,
+        )?;
+        let denominator = scale_amount.checked_mul(U256::from(2 * Q32))?;
+        to_u128(div(numerator, denominator, round_up)?)
+    }
+
+    fn price_x32(&self, amount_sold: u128) -> Option<u128> {
+        let initial_price_x32 = u128::from(self.initial_price_x32);
+        initial_price_x32.checked_add(to_u128(
+            U256::from(initial_price_x32)
+                .checked_mul(U256::from(amount_sold))?
+                .checked_div(U256::from(self.scale_amount))?,
+        )?)
+    }
+}
+
+/// Price doubling every `scale_amount` sold
+/// price = initial_price * 2 ^ (sold / scale_amount)
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ExponentialBondingCurve {
+    /// Price of token 0 before any sale, as a Q32 fixed point number
+    pub initial_price_x32: u64,
+    /// Amount sold for the price to double
+    pub scale_amount: u64,
+}
+
+impl ExponentialBondingCurve {
+    /// 2 ^ (sold / scale_amount) as a Q64.64 fixed point number, rounded up or down
+    fn growth_x64(&self, amount_sold: u128, round_up: bool) -> Option<U256> {
+        let exponent = div(
+            U256::from(amount_sold).checked_mul(U256::from(LN2_X64 as u128))?,
+            U256::from(self.scale_amount),
+            round_up,
+        )?;
+        if exponent > U256::from(i128::MAX) {
+            return None;
+        }
+        let growth = exp_x64(exponent.as_u128() as i128)?;
+        if round_up {
+            growth
+                .checked_add(growth >> EXP_ERROR_SHIFT)?
+                .checked_add(U256::one())
+        } else {
+            Some(
+                growth
+                    .saturating_sub(growth >> EXP_ERROR_SHIFT)
+                    .saturating_sub(U256::one())
+                    .max(U256::from(ONE_X64)),
+            )
+        }
+    }
+}
+
+impl BondingCurve for ExponentialBondingCurve {
+    /// sold = scale_amount * log2(1 + reserve * ln(2) / (initial_price * scale_amount))
+    fn amount_sold(&self, quote_amount: u128) -> Option<u128> {
+        let ratio = U256::from(quote_amount)
+            .checked_mul(U256::from(LN2_X64 as u128))?
+            .checked_mul(U256::from(Q32))?
+            .checked_div(
+                U256::from(self.initial_price_x32).checked_mul(U256::from(self.scale_amount))?,
+            )?;
+        let ln = ln_x64(to_u128(ratio)?.checked_add(ONE_X64)?)?
+            .saturating_sub(LN_ERROR)
+            .max(0);
+        to_u128(
+            U256::from(self.scale_amount)
+                .checked_mul(U256::from(ln as u128))?
+                .checked_div(U256::from(LN2_X64 as u128))?,
+        )
+    }
+
+    /// reserve = initial_price * scale_amount * (2 ^ (sold / scale_amount) - 1) / ln(2)
+    fn reserve(&self, amount_sold: u128, round_up: bool) -> Option<u128> {
+        let numerator = U256::from(self.initial_price_x32)
+            .checked_mul(U256::from(self.scale_amount))?
+            .checked_mul(
+                self.growth_x64(amount_sold, round_up)?
+                    .checked_sub(U256::from(ONE_X64))?,
+            )?;
+        let denominator = U256::from(LN2_X64 as u128).checked_mul(U256::from(Q32))?;
+        to_u128(div(numerator, denominator, round_up)?)
+    }
+
+    fn price_x32(&self, amount_sold: u128) -> Option<u128> {
+        to_u128(
+            U256::from(self.initial_price_x32)
+                .checked_mul(self.growth_x64(amount_sold, false)?)?
+                .checked_div(U256::from(ONE_X64))?,
+        )
+    }
+}
+
+macro_rules! impl_curve_for_bonding_curve {
+    ($curve:ty) => {
+        impl Curve for $curve {
+            fn swap_base_input_without_fees(
+                &self,
+                source_amount: u128,
+                swap_source_amount: u128,
+                swap_destination_amount: u128,
+                trade_direction: TradeDirection,
+            ) -> Option<u128> {
+                self.swap_base_input(
+                    source_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+            }
+
+            fn swap_base_output_without_fees(
+                &self,
+                destination_amount: u128,
+                swap_source_amount: u128,
+                swap_destination_amount: u128,
+                trade_direction: TradeDirection,
+            ) -> Option<u128> {
+                self.swap_base_output(
+                    destination_amount,
+                    swap_source_amount,
+                    swap_destination_amount,
+                    trade_direction,
+                )
+            }
+
+            fn spot_price_x32(
+                &self,
+                _swap_token_0_amount: u128,
+                swap_token_1_amount: u128,
+            ) -> Option<(u128, u128)> {
+                self.spot_price(swap_token_1_amount)
+            }
+
+            fn invariant(
+                &self,
+                swap_token_0_amount: u128,
+                swap_token_1_amount: u128,
+            ) -> Option<U256> {
+                self.mint_invariant(swap_token_0_amount, swap_token_1_amount)
+            }
+
+            fn validate(&self) -> Result<()> {
+                if self.initial_price_x32 == 0 || self.scale_amount == 0 {
+                    return err!(ErrorCode::InvalidCurve);
+                }
+                Ok(())
+            }
+
+            /// The curve prices from the quote reserve alone, a virtual offset would shift it
+            /// away from its initial price
+            fn validate_supply(&self, token_0_amount: u64, token_1_amount: u64) -> Result<()> {
+                if token_0_amount == 0 {
+                    return err!(ErrorCode::EmptySupply);
+                }
+                if token_1_amount != 0 {
+                    return err!(ErrorCode::InvalidCurve);
+                }
+                Ok(())
+            }
+        }
+    };
+}
+
+impl_curve_for_bonding_curve!(LinearBondingCurve);
+impl_curve_for_bonding_curve!(ExponentialBondingCurve);
+
+fn div(numerator: U256, denominator: U256, round_up: bool) -> Option<U256> {
+    if round_up {
+        numerator
+            .checked_add(denominator.checked_sub(U256::one())?)?
+            .checked_div(denominator)
+    } else {
+        numerator.checked_div(denominator)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {super::*, proptest::prelude::*};
+
+    const LINEAR: LinearBondingCurve = LinearBondingCurve {
+        initial_price_x32: Q32 as u64,
+        scale_amount: 1_000_000_000,
+    };
+    const EXPONENTIAL: ExponentialBondingCurve = ExponentialBondingCurve {
+        initial_price_x32: Q32 as u64,
+        scale_amount: 1_000_000_000,
+    };
+
+    /// Checks a swap never reduces the mint reserve plus the amount sold
+    fn check_bonding_curve_value_from_swap(
+        curve: &dyn Curve,
+        source_amount_swapped: u128,
+        destination_amount_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(swap_source_amount, swap_destination_amount);
+        let previous_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        let (swap_token_0_amount, swap_token_1_amount) = trade_direction.to_token_amounts(
+            swap_source_amount + source_amount_swapped,
+            swap_destination_amount - destination_amount_swapped,
+        );
+        let new_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        assert!(new_value >= previous_value);
+    }
+
+    fn curves(initial_price_x32: u64, scale_amount: u64) -> [Box<dyn Curve>; 2] {
+        [
+            Box::new(LinearBondingCurve {
+                initial_price_x32,
+                scale_amount,
+            }),
+            Box::new(ExponentialBondingCurve {
+                initial_price_x32,
+                scale_amount,
+            }),
+        ]
+    }
+
+    #[test]
+    fn linear_swap() {
+        // sold + sold^2 / 2e9 = 1e9
+        assert_eq!(LINEAR.amount_sold(1_000_000_000), Some(732_050_807));
+        assert_eq!(LINEAR.reserve(1_000_000_000, true), Some(1_500_000_000));
+        assert_eq!(
+            LINEAR.swap_base_input(
+                1_000_000_000,
+                1_000_000_000,
+                1_000_000_000_000,
+                TradeDirection::OneForZero
+            ),
+            Some(504_017_170)
+        );
+        assert_eq!(
+            LINEAR.swap_base_input(
+                100_000_000,
+                1_000_000_000_000,
+                1_000_000_000,
+                TradeDirection::ZeroForOne
+            ),
+            Some(168_205_080)
+        );
+        assert_eq!(LINEAR.price_x32(1_000_000_000), Some(2 * Q32));
+    }
+
+    #[test]
+    fn exponential_swap() {
+        // sold = 1e9 * log2(1 + ln(2))
+        assert_eq!(EXPONENTIAL.amount_sold(1_000_000_000), Some(759_707_388));
+        assert_eq!(
+            EXPONENTIAL.reserve(1_000_000_000, true),
+            Some(1_442_695_041)
+        );
+        assert_eq!(
+            EXPONENTIAL.reserve(1_000_000_000, false),
+            Some(1_442_695_040)
+        );
+        assert_eq!(
+            EXPONENTIAL.swap_base_input(
+                1_000_000_000,
+                1_000_000_000,
+                1_000_000_000_000,
+                TradeDirection::OneForZero
+            ),
+            Some(495_064_629)
+        );
+        assert_eq!(
+            EXPONENTIAL.swap_base_input(
+                100_000_000,
+                1_000_000_000_000,
+                1_000_000_000,
+                TradeDirection::ZeroForOne
+            ),
+            Some(163_579_978)
+        );
+    }
+
+    #[test]
+    fn buy_never_drains_mint_reserve() {
+        assert_eq!(
+            LINEAR.swap_base_input(u64::MAX as u128, 1, 1_000, TradeDirection::OneForZero),
+            None
+        );
+        assert_eq!(
+            EXPONENTIAL.swap_base_output(1_000, 1, 1_000, TradeDirection::OneForZero),
+            None
+        );
+    }
+
+    #[test]
+    fn validate_parameters() {
+        assert!(LINEAR.validate().is_ok());
+        assert!(LinearBondingCurve {
+            initial_price_x32: 0,
+            scale_amount: 1
+        }
+        .validate()
+        .is_err());
+        assert!(ExponentialBondingCurve {
+            initial_price_x32: 1,
+            scale_amount: 0
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn first_unit_priced_at_initial_price() {
+        for curve in curves(Q32 as u64 / 3, 1_000_000_000) {
+            assert_eq!(
+                curve.spot_price_x32(1_000_000_000, 0).unwrap().0,
+                u128::from(Q32 as u64 / 3)
+            );
+        }
+        assert!(LINEAR.validate_supply(1_000_000_000, 0).is_ok());
+        assert!(LINEAR.validate_supply(1_000_000_000, 1).is_err());
+        assert!(EXPONENTIAL.validate_supply(0, 0).is_err());
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_input(
+            initial_price_x32 in 1..u64::MAX,
+            scale_amount in 1..u64::MAX,
+            source_token_amount in 1..u64::MAX,
+            swap_mint_amount in 1..u64::MAX,
+            swap_quote_amount in 1..u64::MAX,
+        ) {
+            for curve in curves(initial_price_x32, scale_amount) {
+                // Buy
+                if let Some(destination_amount_swapped) = curve.swap_base_input_without_fees(
+                    source_token_amount as u128,
+                    swap_quote_amount as u128,
+                    swap_mint_amount as u128,
+                    TradeDirection::OneForZero,
+                ) {
+                    check_bonding_curve_value_from_swap(
+                        curve.as_ref(),
+                        source_token_amount as u128,
+                        destination_amount_swapped,
+                        swap_quote_amount as u128,
+                        swap_mint_amount as u128,
+                        TradeDirection::OneForZero,
+                    );
+                }
+                // Sell
+                if let Some(destination_amount_swapped) = curve.swap_base_input_without_fees(
+                    source_token_amount as u128,
+                    swap_mint_amount as u128,
+                    swap_quote_amount as u128,
+                    TradeDirection::ZeroForOne,
+                ) {
+                    check_bonding_curve_value_from_swap(
+                        curve.as_ref(),
+                        source_token_amount as u128,
+                        destination_amount_swapped,
+                        swap_mint_amount as u128,
+                        swap_quote_amount as u128,
+                        TradeDirection::ZeroForOne,
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_output(
+            initial_price_x32 in 1..u64::MAX,
+            scale_amount in 1..u64::MAX,
+            destination_token_amount in 1..u64::MAX,
+            swap_mint_amount in 1..u64::MAX,
+            swap_quote_amount in 1..u64::MAX,
+        ) {
+            for curve in curves(initial_price_x32, scale_amount) {
+                // Buy
+                if let Some(source_amount_swapped) = curve.swap_base_output_without_fees(
+                    destination_token_amount as u128,
+                    swap_quote_amount as u128,
+                    swap_mint_amount as u128,
+                    TradeDirection::OneForZero,
+                ) {
+                    check_bonding_curve_value_from_swap(
+                        curve.as_ref(),
+                        source_amount_swapped,
+                        destination_token_amount as u128,
+                        swap_quote_amount as u128,
+                        swap_mint_amount as u128,
+                        TradeDirection::OneForZero,
+                    );
+                }
+                // Sell
+                if let Some(source_amount_swapped) = curve.swap_base_output_without_fees(
+                    destination_token_amount as u128,
+                    swap_mint_amount as u128,
+                    swap_quote_amount as u128,
+                    TradeDirection::ZeroForOne,
+                ) {
+                    check_bonding_curve_value_from_swap(
+                        curve.as_ref(),
+                        source_amount_swapped,
+                        destination_token_amount as u128,
+                        swap_mint_amount as u128,
+                        swap_quote_amount as u128,
+                        TradeDirection::ZeroForOne,
+                    );
+                }
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn round_trip_profits_less_than_one_token(
+            initial_price_x32 in 1..u32::MAX as u64,
+            scale_amount in 1..u64::MAX,
+            quote_amount in 1..u32::MAX as u64,
+            swap_quote_amount in 1..u64::MAX / 2,
+        ) {
+            for curve in curves(initial_price_x32, scale_amount) {
+                let swap_mint_amount = u64::MAX as u128;
+                let swap_quote_amount = swap_quote_amount as u128;
+                let quote_amount = quote_amount as u128;
+                if let Some(mint_amount) = curve.swap_base_input_without_fees(
+                    quote_amount,
+                    swap_quote_amount,
+                    swap_mint_amount,
+                    TradeDirection::OneForZero,
+                ) {
+                    let quote_amount_back = curve.swap_base_input_without_fees(
+                        mint_amount,
+                        swap_mint_amount - mint_amount,
+                        swap_quote_amount + quote_amount,
+                        TradeDirection::ZeroForOne,
+                    ).unwrap();
+                    // The rounding dust left by earlier trades is worth less than one token
+                    let (price_x32, _) = curve
+                        .spot_price_x32(swap_mint_amount - mint_amount, swap_quote_amount + quote_amount)
+                        .unwrap();
+                    prop_assert!(quote_amount_back <= quote_amount + price_x32 / Q32 + 1);
+                }
+            }
+        }
+    }
+}
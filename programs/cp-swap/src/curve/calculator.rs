@@ -1,8 +1,18 @@
 //! Swap calculations
 
-use crate::curve::{constant_product::ConstantProductCurve, fees::Fees};
+use crate::curve::{
+    bonding::{ExponentialBondingCurve, LinearBondingCurve},
+    constant_product::ConstantProductCurve,
+    fees::{Fees, LaunchFee},
+    stable_swap::StableSwapCurve,
+    weighted::WeightedCurve,
+};
+use crate::utils::U256;
 use anchor_lang::prelude::*;
-use {crate::error::ErrorCode, std::fmt::Debug};
+use {
+    crate::error::ErrorCode,
+    std::{fmt::Debug, sync::Arc},
+};
 
 /// Helper function for mapping to ErrorCode::CalculationFailure
 pub fn map_zero_to_none(x: u128) -> Option<u128> {
@@ -43,6 +53,95 @@ impl TradeDirection {
             TradeDirection::OneForZero => TradeDirection::ZeroForOne,
         }
     }
+
+    /// Orders source and destination amounts as token 0 and token 1 amounts
+    pub fn to_token_amounts(&self, source_amount: u128, destination_amount: u128) -> (u128, u128) {
+        match self {
+            TradeDirection::ZeroForOne => (source_amount, destination_amount),
+            TradeDirection::OneForZero => (destination_amount, source_amount),
+        }
+    }
+}
+
+/// The invariant a pool trades on
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CurveType {
+    /// x * y = k
+    #[default]
+    ConstantProduct,
+    /// Curve-style amplified invariant for pegged pairs
+    StableSwap { amp: u64 },
+    /// x^w0 * y^w1 = k, weights out of `WEIGHT_DENOMINATOR`
+    Weighted { weight_0: u64, weight_1: u64 },
+    /// Launch price rising linearly with the amount of token 0 sold
+    LinearBonding {
+        initial_price_x32: u64,
+        scale_amount: u64,
+    },
+    /// Launch price doubling every `scale_amount` of token 0 sold
+    ExponentialBonding {
+        initial_price_x32: u64,
+        scale_amount: u64,
+    },
+}
+
+impl CurveType {
+    /// Discriminator of the curve type stored in the pool state
+    pub fn discriminator(&self) -> u8 {
+        match self {
+            CurveType::ConstantProduct => 0,
+            CurveType::StableSwap { .. } => 1,
+            CurveType::Weighted { .. } => 2,
+            CurveType::LinearBonding { .. } => 3,
+            CurveType::ExponentialBonding { .. } => 4,
+        }
+    }
+
+    /// Parameters of the curve type stored in the pool state
+    pub fn parameters(&self) -> [u64; 2] {
+        match *self {
+            CurveType::ConstantProduct => [0, 0],
+            CurveType::StableSwap { amp } => [amp, 0],
+            CurveType::Weighted { weight_0, weight_1 } => [weight_0, weight_1],
+            CurveType::LinearBonding {
+                initial_price_x32,
+                scale_amount,
+            }
+            | CurveType::ExponentialBonding {
+                initial_price_x32,
+                scale_amount,
+            } => [initial_price_x32, scale_amount],
+        }
+    }
+
+    /// Rebuilds the curve type from its stored discriminator and parameters
+    pub fn from_discriminator(discriminator: u8, parameters: [u64; 2]) -> Option<CurveType> {
+        match discriminator {
+            0 => Some(CurveType::ConstantProduct),
+            1 => Some(CurveType::StableSwap { amp: parameters[0] }),
+            2 => Some(CurveType::Weighted {
+                weight_0: parameters[0],
+                weight_1: parameters[1],
+            }),
+            3 => Some(CurveType::LinearBonding {
+                initial_price_x32: parameters[0],
+                scale_amount: parameters[1],
+            }),
+            4 => Some(CurveType::ExponentialBonding {
+                initial_price_x32: parameters[0],
+                scale_amount: parameters[1],
+            }),
+            _ => None,
+        }
+    }
+
+    /// Bonding curves price token 0 by the amount sold, they only make sense for launches
+    pub fn is_bonding_curve(&self) -> bool {
+        matches!(
+            self,
+            CurveType::LinearBonding { .. } | CurveType::ExponentialBonding { .. }
+        )
+    }
 }
 
 /// Encodes results of depositing both sides at once
@@ -71,44 +170,245 @@ pub struct SwapResult {
     pub protocol_fee: u128,
     /// Amount of source tokens going to creator
     pub creator_fee: u128,
+    /// Amount of source tokens going to the referrer
+    pub referral_fee: u128,
 }
 
-/// Concrete struct to wrap around the trait object which performs calculation.
-#[derive(Clone, Debug, Default, PartialEq)]
-pub struct CurveCalculator {}
+/// The reserves a swap trades against and the fees it is charged
+#[derive(Clone, Copy, Debug)]
+pub struct SwapParams {
+    /// Amount of source token in the pool
+    pub swap_source_amount: u128,
+    /// Amount of destination token in the pool
+    pub swap_destination_amount: u128,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub referral_fee_rate: u64,
+    pub launch_fee: Option<LaunchFee>,
+    pub trade_direction: TradeDirection,
+}
 
-impl CurveCalculator {
-    pub fn validate_supply(mint_amount: u64, offset_amount: u64) -> Result<()> {
-        if mint_amount == 0 {
+/// Bisection steps of a partial fill, leaving it at most 2^-20 of the amount below the
+/// largest fill within the price limit
+pub const PRICE_LIMIT_ITERATIONS: u32 = 20;
+
+/// Trait implemented by every invariant a pool can trade on. Amounts are the pool
+/// reserves the curve sees, fees are handled by `CurveCalculator`.
+pub trait Curve: Debug {
+    /// Calculate how much destination token will be provided given an amount of source token,
+    /// rounded in favor of the pool.
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Calculate how much source token is needed to get an amount of destination token,
+    /// rounded in favor of the pool.
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128>;
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    ///
+    /// Pool tokens are a pro rata share of both reserves unless a curve says otherwise
+    fn lp_tokens_to_trading_tokens(
+        &self,
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+
+    /// Marginal price of token 0 in token 1 and of token 1 in token 0, as Q32 fixed point numbers
+    fn spot_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<(u128, u128)>;
+
+    /// Reserves with the same invariant at which the marginal token 0 price is `price_x32`,
+    /// for curves that have a closed form
+    fn reserves_at_price_x32(
+        &self,
+        _swap_token_0_amount: u128,
+        _swap_token_1_amount: u128,
+        _price_x32: u128,
+    ) -> Option<(u128, u128)> {
+        None
+    }
+
+    /// Value of the reserves that a swap must never decrease
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<U256>;
+
+    /// How much `invariant` may drop from rounding alone
+    fn invariant_tolerance(&self, _invariant: U256) -> U256 {
+        U256::zero()
+    }
+
+    /// Validates the curve parameters
+    fn validate(&self) -> Result<()> {
+        Ok(())
+    }
+
+    /// Validates the initial reserves of a pool
+    fn validate_supply(&self, token_0_amount: u64, token_1_amount: u64) -> Result<()> {
+        if token_0_amount == 0 {
             return Err(ErrorCode::EmptySupply.into());
         }
-        if offset_amount == 0 {
+        if token_1_amount == 0 {
             return Err(ErrorCode::EmptySupply.into());
         }
         Ok(())
     }
+}
 
-    /// Subtract fees and calculate how much destination token will be provided
-    /// given an amount of source token.
-    pub fn swap_base_input(
+/// Concrete struct to wrap around the trait object which performs calculation.
+#[derive(Clone, Debug)]
+pub struct CurveCalculator {
+    /// The type of curve contained in the calculator
+    pub curve_type: CurveType,
+    /// The actual calculator, represented as a trait object to allow for many
+    /// different types of curves
+    pub calculator: Arc<dyn Curve + Sync + Send>,
+}
+
+impl CurveCalculator {
+    pub fn new(curve_type: CurveType) -> Self {
+        let calculator: Arc<dyn Curve + Sync + Send> = match curve_type {
+            CurveType::ConstantProduct => Arc::new(ConstantProductCurve),
+            CurveType::StableSwap { amp } => Arc::new(StableSwapCurve { amp }),
+            CurveType::Weighted { weight_0, weight_1 } => {
+                Arc::new(WeightedCurve { weight_0, weight_1 })
+            }
+            CurveType::LinearBonding {
+                initial_price_x32,
+                scale_amount,
+            } => Arc::new(LinearBondingCurve {
+                initial_price_x32,
+                scale_amount,
+            }),
+            CurveType::ExponentialBonding {
+                initial_price_x32,
+                scale_amount,
+            } => Arc::new(ExponentialBondingCurve {
+                initial_price_x32,
+                scale_amount,
+            }),
+        };
+        Self {
+            curve_type,
+            calculator,
+        }
+    }
+
+    /// Validates the curve parameters
+    pub fn validate(&self) -> Result<()> {
+        self.calculator.validate()
+    }
+
+    pub fn validate_supply(&self, mint_amount: u64, offset_amount: u64) -> Result<()> {
+        self.calculator.validate_supply(mint_amount, offset_amount)
+    }
+
+    /// The fee rate charged on a swap, the launch fee replaces the trade fee while it is higher
+    fn effective_trade_fee_rate(trade_fee_rate: u64, launch_fee: Option<LaunchFee>) -> u64 {
+        match launch_fee {
+            Some(launch_fee) => trade_fee_rate.max(launch_fee.fee_rate),
+            None => trade_fee_rate,
+        }
+    }
+
+    /// Calculate the trade fee charged on `source_amount` and split it into the protocol fee
+    /// and the creator fee. The part of a launch fee above the trade fee goes entirely to the
+    /// launch fee recipient.
+    fn split_trade_fee(
         source_amount: u128,
-        swap_source_amount: u128,
-        swap_destination_amount: u128,
         trade_fee_rate: u64,
         protocol_fee_rate: u64,
-    ) -> Option<SwapResult> {
+        launch_fee: Option<LaunchFee>,
+    ) -> Option<(u128, u128, u128)> {
+        let trade_fee = Fees::trading_fee(
+            source_amount,
+            Self::effective_trade_fee_rate(trade_fee_rate, launch_fee),
+        )?;
+        let base_fee = Fees::trading_fee(source_amount, trade_fee_rate)?.min(trade_fee);
+        let protocol_fee = Fees::protocol_fee(base_fee, protocol_fee_rate)?;
+        let creator_fee = base_fee.saturating_sub(protocol_fee);
+
+        let launch_fee_excess = trade_fee.checked_sub(base_fee)?;
+        match launch_fee {
+            Some(launch_fee) if launch_fee.to_protocol => Some((
+                trade_fee,
+                protocol_fee.checked_add(launch_fee_excess)?,
+                creator_fee,
+            )),
+            _ => Some((
+                trade_fee,
+                protocol_fee,
+                creator_fee.checked_add(launch_fee_excess)?,
+            )),
+        }
+    }
+
+    /// Carve the referral fee out of the protocol fee and the creator fee, each giving up the
+    /// same share
+    fn split_referral_fee(
+        protocol_fee: u128,
+        creator_fee: u128,
+        referral_fee_rate: u64,
+    ) -> Option<(u128, u128, u128)> {
+        let protocol_referral_fee = Fees::referral_fee(protocol_fee, referral_fee_rate)?;
+        let creator_referral_fee = Fees::referral_fee(creator_fee, referral_fee_rate)?;
+        Some((
+            protocol_fee.checked_sub(protocol_referral_fee)?,
+            creator_fee.checked_sub(creator_referral_fee)?,
+            protocol_referral_fee.checked_add(creator_referral_fee)?,
+        ))
+    }
+
+    /// Subtract fees and calculate how much destination token will be provided
+    /// given an amount of source token.
+    pub fn swap_base_input(&self, source_amount: u128, params: &SwapParams) -> Option<SwapResult> {
+        let SwapParams {
+            swap_source_amount,
+            swap_destination_amount,
+            trade_fee_rate,
+            protocol_fee_rate,
+            referral_fee_rate,
+            launch_fee,
+            trade_direction,
+        } = *params;
         // debit the fee to calculate the amount swapped
-        let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        let creator_fee = trade_fee.saturating_sub(protocol_fee);
+        let (trade_fee, protocol_fee, creator_fee) =
+            Self::split_trade_fee(source_amount, trade_fee_rate, protocol_fee_rate, launch_fee)?;
+        let (protocol_fee, creator_fee, referral_fee) =
+            Self::split_referral_fee(protocol_fee, creator_fee, referral_fee_rate)?;
 
         let source_amount_less_fees = source_amount.checked_sub(trade_fee)?;
 
-        let destination_amount_swapped = ConstantProductCurve::swap_base_input_without_fees(
+        let destination_amount_swapped = self.calculator.swap_base_input_without_fees(
             source_amount_less_fees,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -119,27 +419,40 @@ impl CurveCalculator {
             trade_fee,
             protocol_fee,
             creator_fee,
+            referral_fee,
         })
     }
 
     pub fn swap_base_output(
+        &self,
         destinsation_amount: u128,
-        swap_source_amount: u128,
-        swap_destination_amount: u128,
-        trade_fee_rate: u64,
-        protocol_fee_rate: u64,
+        params: &SwapParams,
     ) -> Option<SwapResult> {
-        let source_amount_swapped = ConstantProductCurve::swap_base_output_without_fees(
+        let SwapParams {
+            swap_source_amount,
+            swap_destination_amount,
+            trade_fee_rate,
+            protocol_fee_rate,
+            referral_fee_rate,
+            launch_fee,
+            trade_direction,
+        } = *params;
+        let source_amount_swapped = self.calculator.swap_base_output_without_fees(
             destinsation_amount,
             swap_source_amount,
             swap_destination_amount,
-        );
+            trade_direction,
+        )?;
 
-        let source_amount =
-            Fees::calculate_pre_fee_amount(source_amount_swapped, trade_fee_rate).unwrap();
-        let trade_fee = Fees::trading_fee(source_amount, trade_fee_rate)?;
-        let protocol_fee = Fees::protocol_fee(trade_fee, protocol_fee_rate)?;
-        let creator_fee = trade_fee.saturating_sub(protocol_fee);
+        let source_amount = Fees::calculate_pre_fee_amount(
+            source_amount_swapped,
+            Self::effective_trade_fee_rate(trade_fee_rate, launch_fee),
+        )
+        .unwrap();
+        let (trade_fee, protocol_fee, creator_fee) =
+            Self::split_trade_fee(source_amount, trade_fee_rate, protocol_fee_rate, launch_fee)?;
+        let (protocol_fee, creator_fee, referral_fee) =
+            Self::split_referral_fee(protocol_fee, creator_fee, referral_fee_rate)?;
 
         Some(SwapResult {
             new_swap_source_amount: swap_source_amount.checked_add(source_amount)?,
@@ -150,19 +463,192 @@ impl CurveCalculator {
             trade_fee,
             protocol_fee,
             creator_fee,
+            referral_fee,
         })
     }
 
+    /// Whether the token 0 price of the reserves left to the pool after a swap is within the
+    /// limit, a sell can't push it below the limit and a buy can't push it above
+    fn within_price_limit(
+        &self,
+        result: &SwapResult,
+        trade_direction: TradeDirection,
+        price_limit_x32: u128,
+    ) -> bool {
+        let new_swap_source_amount = match result
+            .new_swap_source_amount
+            .checked_sub(result.protocol_fee + result.creator_fee + result.referral_fee)
+        {
+            Some(new_swap_source_amount) => new_swap_source_amount,
+            None => return false,
+        };
+        let (swap_token_0_amount, swap_token_1_amount) = trade_direction
+            .to_token_amounts(new_swap_source_amount, result.new_swap_destination_amount);
+        match self.spot_price_x32(swap_token_0_amount, swap_token_1_amount) {
+            Some((token_0_price_x32, _)) => match trade_direction {
+                TradeDirection::ZeroForOne => token_0_price_x32 >= price_limit_x32,
+                TradeDirection::OneForZero => token_0_price_x32 <= price_limit_x32,
+            },
+            None => false,
+        }
+    }
+
+    /// Reserves with the same invariant at which the marginal token 0 price is `price_x32`,
+    /// as source and destination amounts
+    fn reserves_at_price_x32(&self, params: &SwapParams, price_x32: u128) -> Option<(u128, u128)> {
+        let (swap_token_0_amount, swap_token_1_amount) = params
+            .trade_direction
+            .to_token_amounts(params.swap_source_amount, params.swap_destination_amount);
+        let (token_0_amount, token_1_amount) = self.calculator.reserves_at_price_x32(
+            swap_token_0_amount,
+            swap_token_1_amount,
+            price_x32,
+        )?;
+        Some(
+            params
+                .trade_direction
+                .to_token_amounts(token_0_amount, token_1_amount),
+        )
+    }
+
+    /// Source amounts around the swap base input reaching `price_x32`: the amount the curve
+    /// alone needs, and the amount leaving that much to the curve once the trade fee is taken
+    pub fn source_amounts_to_price_x32(
+        &self,
+        params: &SwapParams,
+        price_x32: u128,
+    ) -> Option<[u128; 2]> {
+        let (swap_source_amount, _) = self.reserves_at_price_x32(params, price_x32)?;
+        let source_amount = swap_source_amount.checked_sub(params.swap_source_amount)?;
+        let source_amount_with_fee = Fees::calculate_pre_fee_amount(
+            source_amount,
+            Self::effective_trade_fee_rate(params.trade_fee_rate, params.launch_fee),
+        )?;
+        Some([source_amount, source_amount_with_fee.checked_add(1)?])
+    }
+
+    /// Destination amount the curve alone gives out before the price reaches `price_x32`
+    pub fn destination_amount_to_price_x32(
+        &self,
+        params: &SwapParams,
+        price_x32: u128,
+    ) -> Option<u128> {
+        let (_, swap_destination_amount) = self.reserves_at_price_x32(params, price_x32)?;
+        params
+            .swap_destination_amount
+            .checked_sub(swap_destination_amount)
+    }
+
+    /// Swaps the largest amount, at most `amount`, that keeps the token 0 price within the limit.
+    /// `swap` is `swap_base_input` or `swap_base_output` bound to the pool, the price moves
+    /// monotonically with the amount so the partial fill is found by bisection, starting from
+    /// the `estimates` of curves that have a closed form.
+    pub fn swap_within_price_limit(
+        &self,
+        amount: u128,
+        estimates: impl IntoIterator<Item = u128>,
+        trade_direction: TradeDirection,
+        price_limit_x32: u128,
+        swap: impl Fn(u128) -> Option<SwapResult>,
+    ) -> Option<SwapResult> {
+        let within_price_limit = |amount: u128| {
+            swap(amount)
+                .filter(|result| self.within_price_limit(result, trade_direction, price_limit_x32))
+        };
+        if let Some(result) = within_price_limit(amount) {
+            return Some(result);
+        }
+        let (mut low, mut high) = (0u128, amount);
+        for estimate in estimates {
+            if estimate <= low || estimate >= high {
+                continue;
+            }
+            if within_price_limit(estimate).is_some() {
+                low = estimate;
+            } else {
+                high = estimate;
+            }
+        }
+        for _ in 0..PRICE_LIMIT_ITERATIONS {
+            if high - low <= 1 {
+                break;
+            }
+            let mid = low + (high - low) / 2;
+            if within_price_limit(mid).is_some() {
+                low = mid;
+            } else {
+                high = mid;
+            }
+        }
+        within_price_limit(map_zero_to_none(low)?)
+    }
+
+    /// Checks a swap did not decrease the pool invariant beyond the precision of the curve
+    pub fn validate_swap(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        result: &SwapResult,
+        trade_direction: TradeDirection,
+    ) -> Result<()> {
+        self.validate_invariant(
+            swap_source_amount,
+            swap_destination_amount,
+            result.new_swap_source_amount,
+            result.new_swap_destination_amount,
+            trade_direction,
+        )
+    }
+
+    /// Checks the invariant of the new reserves is not below the one of the old reserves,
+    /// within the rounding tolerance of the curve
+    pub fn validate_invariant(
+        &self,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        new_swap_source_amount: u128,
+        new_swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Result<()> {
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(swap_source_amount, swap_destination_amount);
+        let invariant_before = self
+            .calculator
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .ok_or(ErrorCode::InvariantViolated)?;
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(new_swap_source_amount, new_swap_destination_amount);
+        let invariant_after = self
+            .calculator
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .ok_or(ErrorCode::InvariantViolated)?;
+        #[cfg(feature = "enable-log")]
+        msg!(
+            "invariant_before:{}, invariant_after:{}",
+            invariant_before,
+            invariant_after
+        );
+        if invariant_after
+            .checked_add(self.calculator.invariant_tolerance(invariant_after))
+            .ok_or(ErrorCode::InvariantViolated)?
+            < invariant_before
+        {
+            return err!(ErrorCode::InvariantViolated);
+        }
+        Ok(())
+    }
+
     /// Get the amount of trading tokens for the given amount of pool tokens,
     /// provided the total trading tokens and supply of pool tokens.
     pub fn lp_tokens_to_trading_tokens(
+        &self,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
         round_direction: RoundDirection,
     ) -> Option<TradingTokenResult> {
-        ConstantProductCurve::lp_tokens_to_trading_tokens(
+        self.calculator.lp_tokens_to_trading_tokens(
             lp_token_amount,
             lp_token_supply,
             swap_token_0_amount,
@@ -170,14 +656,24 @@ impl CurveCalculator {
             round_direction,
         )
     }
+
+    /// Marginal price of token 0 in token 1 and of token 1 in token 0, as Q32 fixed point numbers
+    pub fn spot_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<(u128, u128)> {
+        self.calculator
+            .spot_price_x32(swap_token_0_amount, swap_token_1_amount)
+    }
 }
 
 /// Test helpers for curves
 #[cfg(test)]
 pub mod test {
     use {
-        super::*, proptest::prelude::*, spl_math::precise_number::PreciseNumber,
-        spl_math::uint::U256,
+        super::*, crate::states::Q32, proptest::prelude::*,
+        spl_math::precise_number::PreciseNumber, spl_math::uint::U256,
     };
 
     /// The epsilon for most curves when performing the conversion test,
@@ -244,6 +740,43 @@ pub mod test {
         assert!(new_value >= previous_value);
     }
 
+    /// Test function checking that a swap never reduces the StableSwap invariant of
+    /// the pool.
+    ///
+    /// The invariant D is itself only computed to within 1 by newton's method, so
+    /// it is allowed to move by that much.
+    pub fn check_stable_curve_value_from_swap(
+        curve: &StableSwapCurve,
+        source_amount_swapped: u128,
+        destination_amount_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (swap_source_amount, swap_destination_amount),
+            TradeDirection::OneForZero => (swap_destination_amount, swap_source_amount),
+        };
+        let previous_value = curve
+            .compute_d(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+
+        let new_swap_source_amount = swap_source_amount
+            .checked_add(source_amount_swapped)
+            .unwrap();
+        let new_swap_destination_amount = swap_destination_amount
+            .checked_sub(destination_amount_swapped)
+            .unwrap();
+        let (swap_token_0_amount, swap_token_1_amount) = match trade_direction {
+            TradeDirection::ZeroForOne => (new_swap_source_amount, new_swap_destination_amount),
+            TradeDirection::OneForZero => (new_swap_destination_amount, new_swap_source_amount),
+        };
+        let new_value = curve
+            .compute_d(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        assert!(new_value + crate::utils::U256::one() >= previous_value);
+    }
+
     /// Test function checking that a deposit never reduces the value of pool
     /// tokens.
     ///
@@ -251,19 +784,21 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_deposit(
+        curve_type: CurveType,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let deposit_result = CurveCalculator::lp_tokens_to_trading_tokens(
-            lp_token_amount,
-            lp_token_supply,
-            swap_token_0_amount,
-            swap_token_1_amount,
-            RoundDirection::Ceiling,
-        )
-        .unwrap();
+        let deposit_result = CurveCalculator::new(curve_type)
+            .lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                RoundDirection::Ceiling,
+            )
+            .unwrap();
         let new_swap_token_0_amount = swap_token_0_amount + deposit_result.token_0_amount;
         let new_swap_token_1_amount = swap_token_1_amount + deposit_result.token_1_amount;
         let new_lp_token_supply = lp_token_supply + lp_token_amount;
@@ -298,19 +833,21 @@ pub mod test {
     /// truncation at some point, meaning a potential for value to be lost if
     /// too much is given to the depositor.
     pub fn check_pool_value_from_withdraw(
+        curve_type: CurveType,
         lp_token_amount: u128,
         lp_token_supply: u128,
         swap_token_0_amount: u128,
         swap_token_1_amount: u128,
     ) {
-        let withdraw_result = CurveCalculator::lp_tokens_to_trading_tokens(
-            lp_token_amount,
-            lp_token_supply,
-            swap_token_0_amount,
-            swap_token_1_amount,
-            RoundDirection::Floor,
-        )
-        .unwrap();
+        let withdraw_result = CurveCalculator::new(curve_type)
+            .lp_tokens_to_trading_tokens(
+                lp_token_amount,
+                lp_token_supply,
+                swap_token_0_amount,
+                swap_token_1_amount,
+                RoundDirection::Floor,
+            )
+            .unwrap();
         let new_swap_token_0_amount = swap_token_0_amount - withdraw_result.token_0_amount;
         let new_swap_token_1_amount = swap_token_1_amount - withdraw_result.token_1_amount;
         let new_pool_token_supply = lp_token_supply - lp_token_amount;
@@ -334,6 +871,206 @@ pub mod test {
             .greater_than_or_equal(&value.checked_mul(&new_lp_token_supply).unwrap()));
     }
 
+    #[test]
+    fn curve_type_discriminator_round_trip() {
+        for curve_type in [
+            CurveType::ConstantProduct,
+            CurveType::StableSwap { amp: 100 },
+            CurveType::Weighted {
+                weight_0: 80,
+                weight_1: 20,
+            },
+            CurveType::LinearBonding {
+                initial_price_x32: 1 << 32,
+                scale_amount: 1_000_000_000,
+            },
+            CurveType::ExponentialBonding {
+                initial_price_x32: 1 << 32,
+                scale_amount: 1_000_000_000,
+            },
+        ] {
+            assert_eq!(
+                CurveType::from_discriminator(curve_type.discriminator(), curve_type.parameters()),
+                Some(curve_type)
+            );
+        }
+        assert_eq!(CurveType::from_discriminator(u8::MAX, [0, 0]), None);
+    }
+
+    #[test]
+    fn launch_fee_excess_goes_to_recipient() {
+        let trade_fee_rate = 2_500;
+        let protocol_fee_rate = 120_000;
+        let launch_fee = LaunchFee {
+            fee_rate: 100_000,
+            to_protocol: true,
+        };
+        assert_eq!(
+            CurveCalculator::split_trade_fee(
+                1_000_000,
+                trade_fee_rate,
+                protocol_fee_rate,
+                Some(launch_fee)
+            ),
+            Some((100_000, 97_800, 2_200))
+        );
+        assert_eq!(
+            CurveCalculator::split_trade_fee(
+                1_000_000,
+                trade_fee_rate,
+                protocol_fee_rate,
+                Some(LaunchFee {
+                    to_protocol: false,
+                    ..launch_fee
+                })
+            ),
+            Some((100_000, 300, 99_700))
+        );
+        // a launch fee below the trade fee is ignored
+        assert_eq!(
+            CurveCalculator::split_trade_fee(
+                1_000_000,
+                trade_fee_rate,
+                protocol_fee_rate,
+                Some(LaunchFee {
+                    fee_rate: 1_000,
+                    ..launch_fee
+                })
+            ),
+            CurveCalculator::split_trade_fee(1_000_000, trade_fee_rate, protocol_fee_rate, None)
+        );
+    }
+
+    #[test]
+    fn referral_fee_comes_out_of_both_buckets() {
+        let curve_calculator = CurveCalculator::new(CurveType::ConstantProduct);
+        let params = SwapParams {
+            swap_source_amount: 1_000_000_000,
+            swap_destination_amount: 1_000_000_000,
+            trade_fee_rate: 2_500,
+            protocol_fee_rate: 120_000,
+            referral_fee_rate: 200_000,
+            launch_fee: None,
+            trade_direction: TradeDirection::ZeroForOne,
+        };
+        let result = curve_calculator
+            .swap_base_input(1_000_000, &params)
+            .unwrap();
+        assert_eq!(result.trade_fee, 2_500);
+        assert_eq!(result.protocol_fee, 240);
+        assert_eq!(result.creator_fee, 1_760);
+        assert_eq!(result.referral_fee, 500);
+
+        let result = curve_calculator
+            .swap_base_output(
+                1_000_000,
+                &SwapParams {
+                    referral_fee_rate: 0,
+                    ..params
+                },
+            )
+            .unwrap();
+        assert_eq!(result.referral_fee, 0);
+        assert_eq!(result.protocol_fee + result.creator_fee, result.trade_fee);
+    }
+
+    #[test]
+    fn swap_within_price_limit_fills_up_to_the_limit() {
+        let curve_calculator = CurveCalculator::new(CurveType::ConstantProduct);
+        let params = SwapParams {
+            swap_source_amount: 1_000_000_000,
+            swap_destination_amount: 1_000_000_000,
+            trade_fee_rate: 2_500,
+            protocol_fee_rate: 120_000,
+            referral_fee_rate: 0,
+            launch_fee: None,
+            trade_direction: TradeDirection::OneForZero,
+        };
+        let swap = |source_amount| curve_calculator.swap_base_input(source_amount, &params);
+        // Buying until the price is up 21% takes about 10% of the quote reserve
+        let price_limit_x32 = 121 * Q32 / 100;
+        let estimates = curve_calculator
+            .source_amounts_to_price_x32(&params, price_limit_x32)
+            .unwrap();
+        assert!(estimates[0] > 99_000_000 && estimates[1] < 100_300_000);
+        let result = curve_calculator
+            .swap_within_price_limit(
+                500_000_000,
+                estimates,
+                TradeDirection::OneForZero,
+                price_limit_x32,
+                swap,
+            )
+            .unwrap();
+        assert!(result.source_amount_swapped > 100_000_000);
+        assert!(result.source_amount_swapped < 100_300_000);
+        assert!(curve_calculator.within_price_limit(
+            &result,
+            TradeDirection::OneForZero,
+            price_limit_x32
+        ));
+        assert!(!curve_calculator.within_price_limit(
+            &swap(result.source_amount_swapped + 1).unwrap(),
+            TradeDirection::OneForZero,
+            price_limit_x32
+        ));
+        // Without the closed form the bisection stops within its tolerance of the same fill
+        let bisected = curve_calculator
+            .swap_within_price_limit(
+                500_000_000,
+                None,
+                TradeDirection::OneForZero,
+                price_limit_x32,
+                swap,
+            )
+            .unwrap();
+        assert!(bisected.source_amount_swapped <= result.source_amount_swapped);
+        assert!(
+            result.source_amount_swapped - bisected.source_amount_swapped
+                <= 500_000_000 >> PRICE_LIMIT_ITERATIONS
+        );
+
+        // A swap within the limit fills entirely
+        let result = curve_calculator
+            .swap_within_price_limit(
+                50_000_000,
+                None,
+                TradeDirection::OneForZero,
+                price_limit_x32,
+                swap,
+            )
+            .unwrap();
+        assert_eq!(result.source_amount_swapped, 50_000_000);
+
+        // Nothing fills once the price is past the limit
+        assert!(curve_calculator
+            .swap_within_price_limit(50_000_000, None, TradeDirection::OneForZero, Q32 - 1, swap)
+            .is_none());
+    }
+
+    #[test]
+    fn validate_invariant_of_repaid_reserves() {
+        let curve_calculator = CurveCalculator::new(CurveType::ConstantProduct);
+        assert!(curve_calculator
+            .validate_invariant(
+                1_000_000_000,
+                1_000_000_000,
+                1_001_003_011,
+                999_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .is_ok());
+        assert!(curve_calculator
+            .validate_invariant(
+                1_000_000_000,
+                1_000_000_000,
+                1_001_000_000,
+                999_000_000,
+                TradeDirection::ZeroForOne,
+            )
+            .is_err());
+    }
+
     prop_compose! {
         pub fn total_and_intermediate(max_value: u64)(total in 1..max_value)
                         (intermediate in 1..total, total in Just(total))
@@ -1,11 +1,12 @@
 //! The Uniswap invariantConstantProductCurve::
 
 use crate::{
-    curve::calculator::{RoundDirection, TradingTokenResult},
-    utils::CheckedCeilDiv,
+    curve::calculator::{Curve, RoundDirection, TradeDirection, TradingTokenResult},
+    states::Q32,
+    utils::{CheckedCeilDiv, U256},
 };
 
-/// ConstantProductCurve struct implementing CurveCalculator
+/// ConstantProductCurve struct implementing Curve
 #[derive(Clone, Debug, Default, PartialEq)]
 pub struct ConstantProductCurve;
 
@@ -92,6 +93,79 @@ impl ConstantProductCurve {
     }
 }
 
+impl Curve for ConstantProductCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_input_without_fees(
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        Some(ConstantProductCurve::swap_base_output_without_fees(
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        ))
+    }
+
+    /// The price is the ratio of the reserves
+    fn spot_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<(u128, u128)> {
+        Some((
+            swap_token_1_amount
+                .checked_mul(Q32)?
+                .checked_div(swap_token_0_amount)?,
+            swap_token_0_amount
+                .checked_mul(Q32)?
+                .checked_div(swap_token_1_amount)?,
+        ))
+    }
+
+    /// The price y / x is reached at x = sqrt(k / price) and y = sqrt(k * price)
+    fn reserves_at_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        price_x32: u128,
+    ) -> Option<(u128, u128)> {
+        let invariant = self.invariant(swap_token_0_amount, swap_token_1_amount)?;
+        let token_0_amount = invariant
+            .checked_mul(U256::from(Q32))?
+            .checked_div(U256::from(price_x32))?
+            .integer_sqrt();
+        let token_1_amount = invariant
+            .checked_mul(U256::from(price_x32))?
+            .checked_div(U256::from(Q32))?
+            .integer_sqrt();
+        Some((
+            u128::try_from(token_0_amount).ok()?,
+            u128::try_from(token_1_amount).ok()?,
+        ))
+    }
+
+    /// x * y
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<U256> {
+        U256::from(swap_token_0_amount).checked_mul(U256::from(swap_token_1_amount))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use {
@@ -101,7 +175,7 @@ mod tests {
                 check_curve_value_from_swap, check_pool_value_from_deposit,
                 check_pool_value_from_withdraw, total_and_intermediate,
             },
-            RoundDirection, TradeDirection,
+            CurveType, RoundDirection, TradeDirection,
         },
         proptest::prelude::*,
     };
@@ -251,6 +325,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_deposit(
+                CurveType::ConstantProduct,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
@@ -275,6 +350,7 @@ mod tests {
             prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
             prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
             check_pool_value_from_withdraw(
+                CurveType::ConstantProduct,
                 pool_token_amount,
                 pool_token_supply,
                 swap_token_a_amount,
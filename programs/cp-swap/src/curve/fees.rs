@@ -1,9 +1,31 @@
 //! All fee information, to be used for validation currently
 
 pub const FEE_RATE_DENOMINATOR_VALUE: u64 = 1_000_000;
+/// Cap of the share of the protocol and creator fees paid to a referrer, 10%
+pub const MAX_REFERRAL_FEE_RATE: u64 = 100_000;
 
 pub struct Fees {}
 
+/// Launch fee charged in place of the trade fee during a pool's launch window
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LaunchFee {
+    /// Launch fee rate in effect, the trade fee rate still applies if it is higher
+    pub fee_rate: u64,
+    /// Whether the fee above the trade fee goes to the protocol instead of the creator
+    pub to_protocol: bool,
+}
+
+/// Volatility based fee charged on top of the trade fee
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DynamicFee {
+    /// Recent relative price movement, denominated in hundredths of a bip (10^-6)
+    pub volatility: u64,
+    /// Share of the volatility charged as fee, denominated in hundredths of a bip (10^-6)
+    pub variable_fee_control: u64,
+    /// Cap of the trade fee rate including the variable fee
+    pub max_fee_rate: u64,
+}
+
 fn ceil_div(token_amount: u128, fee_numerator: u128, fee_denominator: u128) -> Option<u128> {
     token_amount
         .checked_mul(u128::from(fee_numerator))
@@ -32,6 +54,22 @@ impl Fees {
         )
     }
 
+    /// Trade fee rate including the variable fee derived from recent volatility, never above the
+    /// dynamic fee cap unless the trade fee rate itself is
+    pub fn dynamic_trade_fee_rate(trade_fee_rate: u64, dynamic_fee: Option<DynamicFee>) -> u64 {
+        match dynamic_fee {
+            Some(dynamic_fee) => {
+                let variable_fee_rate = u128::from(dynamic_fee.volatility)
+                    * u128::from(dynamic_fee.variable_fee_control)
+                    / u128::from(FEE_RATE_DENOMINATOR_VALUE);
+                let fee_rate = (u128::from(trade_fee_rate) + variable_fee_rate)
+                    .min(u128::from(dynamic_fee.max_fee_rate.max(trade_fee_rate)));
+                u64::try_from(fee_rate).unwrap()
+            }
+            None => trade_fee_rate,
+        }
+    }
+
     /// Calculate the owner trading fee in trading tokens
     pub fn protocol_fee(amount: u128, protocol_fee_rate: u64) -> Option<u128> {
         floor_div(
@@ -50,6 +88,15 @@ impl Fees {
         )
     }
 
+    /// Calculate the referrer share of a fee in trading tokens
+    pub fn referral_fee(amount: u128, referral_fee_rate: u64) -> Option<u128> {
+        floor_div(
+            amount,
+            u128::from(referral_fee_rate),
+            u128::from(FEE_RATE_DENOMINATOR_VALUE),
+        )
+    }
+
     pub fn calculate_pre_fee_amount(post_fee_amount: u128, trade_fee_rate: u64) -> Option<u128> {
         if trade_fee_rate == 0 {
             Some(post_fee_amount)
@@ -65,3 +112,39 @@ impl Fees {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::states::MAX_VOLATILITY;
+
+    #[test]
+    fn dynamic_fee_is_capped() {
+        let dynamic_fee = DynamicFee {
+            volatility: 100_000,
+            variable_fee_control: 100_000,
+            max_fee_rate: 15_000,
+        };
+        assert_eq!(Fees::dynamic_trade_fee_rate(2_500, None), 2_500);
+        // 10% of a 10% move
+        assert_eq!(
+            Fees::dynamic_trade_fee_rate(2_500, Some(dynamic_fee)),
+            12_500
+        );
+        assert_eq!(
+            Fees::dynamic_trade_fee_rate(
+                2_500,
+                Some(DynamicFee {
+                    volatility: MAX_VOLATILITY,
+                    ..dynamic_fee
+                })
+            ),
+            15_000
+        );
+        // the cap never lowers the trade fee
+        assert_eq!(
+            Fees::dynamic_trade_fee_rate(20_000, Some(dynamic_fee)),
+            20_000
+        );
+    }
+}
@@ -1,9 +1,15 @@
 //! Curve invariant implementations
 
+pub mod bonding;
 pub mod calculator;
 pub mod constant_product;
 pub mod fees;
+pub mod stable_swap;
+pub mod weighted;
 
+pub use bonding::*;
 pub use calculator::*;
 pub use constant_product::*;
 pub use fees::*;
+pub use stable_swap::*;
+pub use weighted::*;
@@ -0,0 +1,423 @@
+//! The StableSwap invariant, an amplified constant sum for pegged pairs
+
+use crate::{
+    curve::calculator::{Curve, RoundDirection, TradeDirection, TradingTokenResult},
+    curve::constant_product::ConstantProductCurve,
+    error::ErrorCode,
+    states::Q32,
+    utils::U256,
+};
+use anchor_lang::prelude::*;
+
+/// Minimum amplification coefficient
+pub const MIN_AMP: u64 = 1;
+/// Maximum amplification coefficient
+pub const MAX_AMP: u64 = 1_000_000;
+
+/// Number of tokens in a pool
+const N_COINS: u8 = 2;
+/// Maximum newton iterations before giving up on convergence
+const ITERATIONS: u16 = 256;
+
+/// StableSwapCurve struct implementing the Curve-style invariant
+/// A * n^n * sum(x_i) + D = A * D * n^n + D^(n+1) / (n^n * prod(x_i))
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StableSwapCurve {
+    /// Amplification coefficient, the higher the flatter the curve around the peg
+    pub amp: u64,
+}
+
+impl StableSwapCurve {
+    /// Computes the invariant D of the given reserves with newton's method
+    pub fn compute_d(&self, amount_a: u128, amount_b: u128) -> Option<U256> {
+        let sum = U256::from(amount_a).checked_add(U256::from(amount_b))?;
+        if sum.is_zero() {
+            return Some(U256::zero());
+        }
+        let n_coins = U256::from(N_COINS);
+        let ann = U256::from(self.amp).checked_mul(n_coins)?;
+        let amount_a_times_coins = U256::from(amount_a).checked_mul(n_coins)?;
+        let amount_b_times_coins = U256::from(amount_b).checked_mul(n_coins)?;
+
+        let mut d = sum;
+        for _ in 0..ITERATIONS {
+            // d_p = D^(n+1) / (n^n * prod(x_i))
+            let d_p = d
+                .checked_mul(d)?
+                .checked_div(amount_a_times_coins)?
+                .checked_mul(d)?
+                .checked_div(amount_b_times_coins)?;
+            let d_previous = d;
+            // D = (Ann * S + D_p * n) * D / ((Ann - 1) * D + (n + 1) * D_p)
+            let numerator = ann
+                .checked_mul(sum)?
+                .checked_add(d_p.checked_mul(n_coins)?)?
+                .checked_mul(d)?;
+            let denominator = ann
+                .checked_sub(U256::one())?
+                .checked_mul(d)?
+                .checked_add(n_coins.checked_add(U256::one())?.checked_mul(d_p)?)?;
+            d = numerator.checked_div(denominator)?;
+            if abs_diff(d, d_previous) <= U256::one() {
+                break;
+            }
+        }
+        Some(d)
+    }
+
+    /// Computes the reserve of the other token that keeps the invariant at `d`
+    /// when one reserve becomes `new_amount`
+    pub fn compute_y(&self, new_amount: u128, d: U256) -> Option<U256> {
+        let n_coins = U256::from(N_COINS);
+        let ann = U256::from(self.amp).checked_mul(n_coins)?;
+        let new_amount = U256::from(new_amount);
+
+        // c = D^(n+1) / (n^n * x * Ann)
+        let c = d
+            .checked_mul(d)?
+            .checked_div(new_amount.checked_mul(n_coins)?)?
+            .checked_mul(d)?
+            .checked_div(ann.checked_mul(n_coins)?)?;
+        // b = x + D / Ann
+        let b = new_amount.checked_add(d.checked_div(ann)?)?;
+
+        // y = (y^2 + c) / (2y + b - D)
+        let mut y = d;
+        for _ in 0..ITERATIONS {
+            let y_previous = y;
+            let numerator = y.checked_mul(y)?.checked_add(c)?;
+            let denominator = y
+                .checked_mul(U256::from(2u8))?
+                .checked_add(b)?
+                .checked_sub(d)?;
+            y = numerator.checked_div(denominator)?;
+            if abs_diff(y, y_previous) <= U256::one() {
+                break;
+            }
+        }
+        Some(y)
+    }
+
+    /// Calculate how much destination token will be provided given an amount of source token,
+    /// rounded down in favor of the pool.
+    pub fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_destination_amount =
+            u128::try_from(self.compute_y(swap_source_amount.checked_add(source_amount)?, d)?)
+                .ok()?;
+        // newton's method lands within 1 of the exact reserve, give it to the pool
+        Some(
+            swap_destination_amount
+                .saturating_sub(new_destination_amount)
+                .saturating_sub(1),
+        )
+    }
+
+    /// Calculate how much source token is needed to get an amount of destination token,
+    /// rounded up in favor of the pool.
+    pub fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+    ) -> Option<u128> {
+        let d = self.compute_d(swap_source_amount, swap_destination_amount)?;
+        let new_source_amount = u128::try_from(
+            self.compute_y(swap_destination_amount.checked_sub(destination_amount)?, d)?,
+        )
+        .ok()?;
+        new_source_amount
+            .saturating_sub(swap_source_amount)
+            .checked_add(1)
+    }
+
+    /// Get the amount of trading tokens for the given amount of pool tokens,
+    /// provided the total trading tokens and supply of pool tokens.
+    ///
+    /// Pool tokens are a pro rata share of both reserves, whatever the invariant
+    pub fn lp_tokens_to_trading_tokens(
+        lp_token_amount: u128,
+        lp_token_supply: u128,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+        round_direction: RoundDirection,
+    ) -> Option<TradingTokenResult> {
+        ConstantProductCurve::lp_tokens_to_trading_tokens(
+            lp_token_amount,
+            lp_token_supply,
+            swap_token_0_amount,
+            swap_token_1_amount,
+            round_direction,
+        )
+    }
+}
+
+impl Curve for StableSwapCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        StableSwapCurve::swap_base_input_without_fees(
+            self,
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        )
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        _trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        StableSwapCurve::swap_base_output_without_fees(
+            self,
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+        )
+    }
+
+    /// The price is the ratio of the partial derivatives of the invariant,
+    /// (4 * Ann * x * y + D^3 / x) / (4 * Ann * x * y + D^3 / y) for token 0
+    fn spot_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<(u128, u128)> {
+        let d = self.compute_d(swap_token_0_amount, swap_token_1_amount)?;
+        let d_cubed = d.checked_mul(d)?.checked_mul(d)?;
+        let amount_0 = U256::from(swap_token_0_amount);
+        let amount_1 = U256::from(swap_token_1_amount);
+        let ann_term = U256::from(self.amp)
+            .checked_mul(U256::from(N_COINS))?
+            .checked_mul(U256::from(4u8))?
+            .checked_mul(amount_0)?
+            .checked_mul(amount_1)?;
+        let derivative_0 = ann_term.checked_add(d_cubed.checked_div(amount_0)?)?;
+        let derivative_1 = ann_term.checked_add(d_cubed.checked_div(amount_1)?)?;
+        Some((
+            derivative_0
+                .checked_mul(U256::from(Q32))?
+                .checked_div(derivative_1)?
+                .as_u128(),
+            derivative_1
+                .checked_mul(U256::from(Q32))?
+                .checked_div(derivative_0)?
+                .as_u128(),
+        ))
+    }
+
+    /// D
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<U256> {
+        self.compute_d(swap_token_0_amount, swap_token_1_amount)
+    }
+
+    /// D is only computed to within 1 by newton's method
+    fn invariant_tolerance(&self, _invariant: U256) -> U256 {
+        U256::one()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if !(MIN_AMP..=MAX_AMP).contains(&self.amp) {
+            return err!(ErrorCode::InvalidCurve);
+        }
+        Ok(())
+    }
+}
+
+fn abs_diff(a: U256, b: U256) -> U256 {
+    if a > b {
+        a - b
+    } else {
+        b - a
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::calculator::{
+            test::{
+                check_pool_value_from_deposit, check_pool_value_from_withdraw,
+                check_stable_curve_value_from_swap, total_and_intermediate,
+            },
+            CurveType, RoundDirection, TradeDirection,
+        },
+        proptest::prelude::*,
+    };
+
+    #[test]
+    fn balanced_swap_close_to_peg() {
+        let curve = StableSwapCurve { amp: 100 };
+        let reserve = 1_000_000_000u128;
+        assert_eq!(
+            curve.swap_base_input_without_fees(1_000, reserve, reserve),
+            Some(999)
+        );
+        assert_eq!(
+            curve.swap_base_output_without_fees(1_000, reserve, reserve),
+            Some(1_001)
+        );
+        // a constant product pool gives a much worse price for a large trade
+        let stable_amount = curve
+            .swap_base_input_without_fees(reserve / 10, reserve, reserve)
+            .unwrap();
+        let constant_product_amount =
+            ConstantProductCurve::swap_base_input_without_fees(reserve / 10, reserve, reserve);
+        assert!(stable_amount > constant_product_amount);
+        assert!(stable_amount < reserve / 10);
+    }
+
+    #[test]
+    fn compute_d_of_balanced_pool() {
+        let curve = StableSwapCurve { amp: 100 };
+        assert_eq!(curve.compute_d(0, 0), Some(U256::zero()));
+        assert_eq!(curve.compute_d(1_000, 1_000), Some(U256::from(2_000u64)));
+    }
+
+    #[test]
+    fn spot_price_at_peg() {
+        let curve = StableSwapCurve { amp: 100 };
+        let (price_0, price_1) = curve.spot_price_x32(1_000_000, 1_000_000).unwrap();
+        assert_eq!(price_0, Q32);
+        assert_eq!(price_1, Q32);
+        // token 0 is scarcer, so it is worth a bit more than token 1
+        let (price_0, price_1) = curve.spot_price_x32(900_000, 1_100_000).unwrap();
+        assert!(price_0 > Q32 && price_0 < Q32 * 11 / 10);
+        assert!(price_1 < Q32);
+    }
+
+    #[test]
+    fn trading_token_conversion() {
+        let results =
+            StableSwapCurve::lp_tokens_to_trading_tokens(5, 101, 100, 202, RoundDirection::Ceiling)
+                .unwrap();
+        assert_eq!(results.token_0_amount, 5);
+        assert_eq!(results.token_1_amount, 10);
+    }
+
+    prop_compose! {
+        /// Reserves within 1000x of each other, the range a pegged pair trades in
+        fn pegged_reserves(max_value: u64)(token_0 in 1_000..max_value)
+                        (token_1 in (token_0 / 1_000).max(1_000)..(token_0.saturating_mul(1_000)).min(max_value),
+                         token_0 in Just(token_0))
+                        -> (u64, u64) {
+           (token_0, token_1)
+       }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_input(
+            amp in MIN_AMP..10_000,
+            (swap_source_amount, swap_destination_amount) in pegged_reserves(u64::MAX / 2),
+            source_token_amount in 1..u64::MAX / 2,
+        ) {
+            prop_assume!(source_token_amount <= swap_source_amount);
+            let curve = StableSwapCurve { amp };
+            let destination_amount_swapped = curve.swap_base_input_without_fees(
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+            ).unwrap();
+            check_stable_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                destination_amount_swapped,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_output(
+            amp in MIN_AMP..10_000,
+            (swap_source_amount, swap_destination_amount) in pegged_reserves(u64::MAX / 2),
+            destination_token_amount in 1..u64::MAX / 2,
+        ) {
+            prop_assume!(destination_token_amount <= swap_destination_amount / 2);
+            let curve = StableSwapCurve { amp };
+            let source_amount_swapped = curve.swap_base_output_without_fees(
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+            ).unwrap();
+            check_stable_curve_value_from_swap(
+                &curve,
+                source_amount_swapped,
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::OneForZero,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..10_000,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                CurveType::StableSwap { amp },
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            amp in MIN_AMP..10_000,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                CurveType::StableSwap { amp },
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
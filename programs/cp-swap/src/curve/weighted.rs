@@ -0,0 +1,416 @@
+//! The Balancer invariant, a constant product of weighted reserves
+
+use crate::{
+    curve::calculator::{Curve, TradeDirection},
+    error::ErrorCode,
+    states::Q32,
+    utils::{exp_x64, ln_x64, pow_x64, to_u128, ONE_X64, U256},
+};
+use anchor_lang::prelude::*;
+
+/// The weights of a pool add up to this
+pub const WEIGHT_DENOMINATOR: u64 = 100;
+/// Minimum weight of a token
+pub const MIN_WEIGHT: u64 = 2;
+
+/// The fixed point pow is rounded up by 2^-40 relative, well above its error,
+/// so swaps are always rounded in favor of the pool
+const POW_ERROR_SHIFT: usize = 40;
+
+/// WeightedCurve struct implementing the invariant x^w0 * y^w1 = k
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WeightedCurve {
+    /// Weight of token 0, out of `WEIGHT_DENOMINATOR`
+    pub weight_0: u64,
+    /// Weight of token 1, out of `WEIGHT_DENOMINATOR`
+    pub weight_1: u64,
+}
+
+impl WeightedCurve {
+    /// Weights of the source and destination tokens
+    fn weights(&self, trade_direction: TradeDirection) -> (u64, u64) {
+        match trade_direction {
+            TradeDirection::ZeroForOne => (self.weight_0, self.weight_1),
+            TradeDirection::OneForZero => (self.weight_1, self.weight_0),
+        }
+    }
+
+    /// `base ^ (numerator / denominator)` rounded up
+    fn pow_up(base: u128, numerator: u64, denominator: u64) -> Option<U256> {
+        let pow = pow_x64(base, numerator, denominator)?;
+        pow.checked_add(pow >> POW_ERROR_SHIFT)?
+            .checked_add(U256::one())
+    }
+
+    /// Weighted swap ensures x^w_x * y^w_y = constant
+    /// delta_y = y * (1 - (x / (x + delta_x)) ^ (w_x / w_y))
+    pub fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        let ratio = ceil_div(
+            U256::from(swap_source_amount).checked_mul(U256::from(ONE_X64))?,
+            U256::from(swap_source_amount.checked_add(source_amount)?),
+        )?;
+        let pow = Self::pow_up(to_u128(ratio)?, source_weight, destination_weight)?
+            .min(U256::from(ONE_X64));
+        let new_swap_destination_amount = ceil_div(
+            U256::from(swap_destination_amount).checked_mul(pow)?,
+            U256::from(ONE_X64),
+        )?;
+        swap_destination_amount.checked_sub(to_u128(new_swap_destination_amount)?)
+    }
+
+    /// delta_x = x * ((y / (y - delta_y)) ^ (w_y / w_x) - 1)
+    pub fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        let (source_weight, destination_weight) = self.weights(trade_direction);
+        let ratio = ceil_div(
+            U256::from(swap_destination_amount).checked_mul(U256::from(ONE_X64))?,
+            U256::from(swap_destination_amount.checked_sub(destination_amount)?),
+        )?;
+        let pow = Self::pow_up(to_u128(ratio)?, destination_weight, source_weight)?;
+        let source_amount_swapped = ceil_div(
+            U256::from(swap_source_amount).checked_mul(pow.checked_sub(U256::from(ONE_X64))?)?,
+            U256::from(ONE_X64),
+        )?;
+        to_u128(source_amount_swapped)
+    }
+}
+
+impl Curve for WeightedCurve {
+    fn swap_base_input_without_fees(
+        &self,
+        source_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        WeightedCurve::swap_base_input_without_fees(
+            self,
+            source_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        )
+    }
+
+    fn swap_base_output_without_fees(
+        &self,
+        destination_amount: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) -> Option<u128> {
+        WeightedCurve::swap_base_output_without_fees(
+            self,
+            destination_amount,
+            swap_source_amount,
+            swap_destination_amount,
+            trade_direction,
+        )
+    }
+
+    /// The price of token 0 is (y / w1) / (x / w0)
+    fn spot_price_x32(
+        &self,
+        swap_token_0_amount: u128,
+        swap_token_1_amount: u128,
+    ) -> Option<(u128, u128)> {
+        let token_0_value = swap_token_0_amount.checked_mul(u128::from(self.weight_1))?;
+        let token_1_value = swap_token_1_amount.checked_mul(u128::from(self.weight_0))?;
+        Some((
+            token_1_value.checked_mul(Q32)?.checked_div(token_0_value)?,
+            token_0_value.checked_mul(Q32)?.checked_div(token_1_value)?,
+        ))
+    }
+
+    /// (x^w0 * y^w1) ^ (1 / (w0 + w1)) as a Q64.64 fixed point number
+    fn invariant(&self, swap_token_0_amount: u128, swap_token_1_amount: u128) -> Option<U256> {
+        let ln_0 = ln_x64(swap_token_0_amount.checked_mul(ONE_X64)?)?;
+        let ln_1 = ln_x64(swap_token_1_amount.checked_mul(ONE_X64)?)?;
+        exp_x64(
+            ln_0.checked_mul(i128::from(self.weight_0))?
+                .checked_add(ln_1.checked_mul(i128::from(self.weight_1))?)?
+                .div_euclid(i128::from(WEIGHT_DENOMINATOR)),
+        )
+    }
+
+    /// The invariant carries the error of the fixed point ln and exp
+    fn invariant_tolerance(&self, invariant: U256) -> U256 {
+        (invariant >> POW_ERROR_SHIFT) + U256::one()
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.weight_0 < MIN_WEIGHT
+            || self.weight_1 < MIN_WEIGHT
+            || self.weight_0.checked_add(self.weight_1) != Some(WEIGHT_DENOMINATOR)
+        {
+            return err!(ErrorCode::InvalidCurve);
+        }
+        Ok(())
+    }
+}
+
+fn ceil_div(numerator: U256, denominator: U256) -> Option<U256> {
+    numerator
+        .checked_add(denominator.checked_sub(U256::one())?)?
+        .checked_div(denominator)
+}
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::curve::{
+            calculator::{
+                test::{
+                    check_pool_value_from_deposit, check_pool_value_from_withdraw,
+                    total_and_intermediate,
+                },
+                CurveType, RoundDirection,
+            },
+            constant_product::ConstantProductCurve,
+        },
+        proptest::prelude::*,
+    };
+
+    /// Checks a swap never reduces the weighted invariant beyond its precision
+    fn check_weighted_curve_value_from_swap(
+        curve: &WeightedCurve,
+        source_amount_swapped: u128,
+        destination_amount_swapped: u128,
+        swap_source_amount: u128,
+        swap_destination_amount: u128,
+        trade_direction: TradeDirection,
+    ) {
+        let (swap_token_0_amount, swap_token_1_amount) =
+            trade_direction.to_token_amounts(swap_source_amount, swap_destination_amount);
+        let previous_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        let (swap_token_0_amount, swap_token_1_amount) = trade_direction.to_token_amounts(
+            swap_source_amount + source_amount_swapped,
+            swap_destination_amount - destination_amount_swapped,
+        );
+        let new_value = curve
+            .invariant(swap_token_0_amount, swap_token_1_amount)
+            .unwrap();
+        assert!(new_value + curve.invariant_tolerance(new_value) >= previous_value);
+    }
+
+    #[test]
+    fn validate_weights() {
+        assert!(WeightedCurve {
+            weight_0: 80,
+            weight_1: 20
+        }
+        .validate()
+        .is_ok());
+        assert!(WeightedCurve {
+            weight_0: 99,
+            weight_1: 1
+        }
+        .validate()
+        .is_err());
+        assert!(WeightedCurve {
+            weight_0: 50,
+            weight_1: 40
+        }
+        .validate()
+        .is_err());
+    }
+
+    #[test]
+    fn equal_weights_match_constant_product() {
+        let curve = WeightedCurve {
+            weight_0: 50,
+            weight_1: 50,
+        };
+        let reserve = 1_000_000_000u128;
+        assert_eq!(
+            curve.swap_base_input_without_fees(
+                1_000_000,
+                reserve,
+                reserve,
+                TradeDirection::ZeroForOne
+            ),
+            Some(ConstantProductCurve::swap_base_input_without_fees(
+                1_000_000, reserve, reserve
+            ))
+        );
+    }
+
+    #[test]
+    fn weighted_swap() {
+        let curve = WeightedCurve {
+            weight_0: 80,
+            weight_1: 20,
+        };
+        let reserve = 1_000_000_000u128;
+        // token 0 is worth 4 token 1
+        assert_eq!(
+            curve.spot_price_x32(reserve, reserve),
+            Some((4 * Q32, Q32 / 4))
+        );
+        assert_eq!(
+            curve.swap_base_input_without_fees(
+                1_000_000,
+                reserve,
+                reserve,
+                TradeDirection::ZeroForOne
+            ),
+            Some(3_990_019)
+        );
+        assert_eq!(
+            curve.swap_base_output_without_fees(
+                1_000_000,
+                reserve,
+                reserve,
+                TradeDirection::ZeroForOne
+            ),
+            Some(250_157)
+        );
+    }
+
+    #[test]
+    fn trading_token_conversion() {
+        let curve = WeightedCurve {
+            weight_0: 80,
+            weight_1: 20,
+        };
+        let results = curve
+            .lp_tokens_to_trading_tokens(5, 101, 100, 202, RoundDirection::Ceiling)
+            .unwrap();
+        assert_eq!(results.token_0_amount, 5);
+        assert_eq!(results.token_1_amount, 10);
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_input(
+            weight_0 in MIN_WEIGHT..WEIGHT_DENOMINATOR - MIN_WEIGHT + 1,
+            source_token_amount in 1..u64::MAX / 2,
+            swap_source_amount in 1..u64::MAX / 2,
+            swap_destination_amount in 1..u64::MAX,
+        ) {
+            let curve = WeightedCurve {
+                weight_0,
+                weight_1: WEIGHT_DENOMINATOR - weight_0,
+            };
+            let destination_amount_swapped = curve.swap_base_input_without_fees(
+                source_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne,
+            ).unwrap();
+            check_weighted_curve_value_from_swap(
+                &curve,
+                source_token_amount as u128,
+                destination_amount_swapped,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::ZeroForOne,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_swap_base_output(
+            weight_0 in MIN_WEIGHT..WEIGHT_DENOMINATOR - MIN_WEIGHT + 1,
+            swap_source_amount in 1..u32::MAX as u64,
+            (swap_destination_amount, destination_token_amount) in total_and_intermediate(u64::MAX),
+        ) {
+            let curve = WeightedCurve {
+                weight_0,
+                weight_1: WEIGHT_DENOMINATOR - weight_0,
+            };
+            // Large outputs need more source tokens than the pool could ever hold
+            if let Some(source_amount_swapped) = curve.swap_base_output_without_fees(
+                destination_token_amount as u128,
+                swap_source_amount as u128,
+                swap_destination_amount as u128,
+                TradeDirection::OneForZero,
+            ) {
+                prop_assume!(swap_source_amount as u128 + source_amount_swapped <= u64::MAX as u128);
+                check_weighted_curve_value_from_swap(
+                    &curve,
+                    source_amount_swapped,
+                    destination_token_amount as u128,
+                    swap_source_amount as u128,
+                    swap_destination_amount as u128,
+                    TradeDirection::OneForZero,
+                );
+            }
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_deposit(
+            pool_token_amount in 1..u64::MAX,
+            pool_token_supply in 1..u64::MAX,
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            weight_0 in MIN_WEIGHT..WEIGHT_DENOMINATOR - MIN_WEIGHT + 1,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_deposit(
+                CurveType::Weighted {
+                    weight_0,
+                    weight_1: WEIGHT_DENOMINATOR - weight_0,
+                },
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn curve_value_does_not_decrease_from_withdraw(
+            (pool_token_supply, pool_token_amount) in total_and_intermediate(u64::MAX),
+            swap_token_a_amount in 1..u64::MAX,
+            swap_token_b_amount in 1..u64::MAX,
+            weight_0 in MIN_WEIGHT..WEIGHT_DENOMINATOR - MIN_WEIGHT + 1,
+        ) {
+            let pool_token_amount = pool_token_amount as u128;
+            let pool_token_supply = pool_token_supply as u128;
+            let swap_token_a_amount = swap_token_a_amount as u128;
+            let swap_token_b_amount = swap_token_b_amount as u128;
+            // Make sure we will get at least one trading token out for each
+            // side, otherwise the calculation fails
+            prop_assume!(pool_token_amount * swap_token_a_amount / pool_token_supply >= 1);
+            prop_assume!(pool_token_amount * swap_token_b_amount / pool_token_supply >= 1);
+            check_pool_value_from_withdraw(
+                CurveType::Weighted {
+                    weight_0,
+                    weight_1: WEIGHT_DENOMINATOR - weight_0,
+                },
+                pool_token_amount,
+                pool_token_supply,
+                swap_token_a_amount,
+                swap_token_b_amount,
+            );
+        }
+    }
+}
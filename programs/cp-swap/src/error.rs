@@ -28,6 +28,66 @@ pub enum ErrorCode {
     NotSupportMint,
     #[msg("invaild vault")]
     InvalidVault,
-    #[msg("Init lp amount is too less(Because 100 amount lp will be locked)")]
+    #[msg("Init lp amount is too less")]
     InitLpAmountTooLess,
+    /// The oracle observations do not cover the requested window
+    #[msg("Not enough observations for the requested twap window")]
+    InsufficientObservations,
+    /// The quote mint does not match the one of the amm config or pool
+    #[msg("Invalid quote mint")]
+    InvalidQuoteMint,
+    /// The fee rate is out of range
+    #[msg("Invalid fee rate")]
+    InvalidFeeRate,
+    /// There is no queued change to cancel
+    #[msg("No pending change")]
+    NoPendingChange,
+    /// The pool reached its graduation threshold and must graduate before trading again
+    #[msg("Pool must graduate before trading")]
+    GraduationPending,
+    /// The pool did not reach its graduation threshold or already graduated
+    #[msg("Pool can not graduate")]
+    GraduationNotReached,
+    /// The launch fee schedule is invalid
+    #[msg("Invalid launch fee")]
+    InvalidLaunchFee,
+    /// The purchase cap needs a window and at least one cap
+    #[msg("Invalid purchase cap")]
+    InvalidPurchaseCap,
+    /// The wallet bought more than the pool allows during its purchase cap window
+    #[msg("Purchase cap exceeded")]
+    PurchaseCapExceeded,
+    /// Buys during the purchase cap window must pass the user purchase account
+    #[msg("User purchase account is required")]
+    MissingUserPurchaseState,
+    /// The curve type or its parameters are invalid
+    #[msg("Invalid curve")]
+    InvalidCurve,
+    /// The swap would decrease the pool invariant
+    #[msg("Invariant violated")]
+    InvariantViolated,
+    /// The fee split recipients or their token accounts are invalid
+    #[msg("Invalid fee split")]
+    InvalidFeeSplit,
+    /// The flash swap callback did not pay enough input back into the pool
+    #[msg("Flash swap not repaid")]
+    FlashSwapNotRepaid,
+    /// The swap landed after its deadline
+    #[msg("Deadline exceeded")]
+    DeadlineExceeded,
+    /// The pool price is already past the swap price limit
+    #[msg("Price limit reached")]
+    PriceLimitReached,
+    /// Native SOL swaps must pass the native mint, the native SOL account and the system program
+    #[msg("Native SOL account is required")]
+    MissingNativeSolAccount,
+    /// The pool state is not of the layout version the instruction expects
+    #[msg("Invalid pool version")]
+    InvalidPoolVersion,
+    /// The pool is in the middle of a flash swap, or the callback changed its state
+    #[msg("Pool locked by a flash swap")]
+    PoolLocked,
+    /// The amm config is not of the layout version the instruction expects
+    #[msg("Invalid config version")]
+    InvalidConfigVersion,
 }
@@ -0,0 +1,35 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptPoolCreator<'info> {
+    /// The proposed pool creator
+    #[account(address = pool_state.load()?.pending_pool_creator @ ErrorCode::InvalidOwner)]
+    pub pending_pool_creator: Signer<'info>,
+
+    /// The pool to take over
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn accept_pool_creator(ctx: Context<AcceptPoolCreator>) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let old_creator = pool_state.pool_creator;
+    pool_state.pool_creator = ctx.accounts.pending_pool_creator.key();
+    pool_state.pending_pool_creator = Pubkey::default();
+
+    emit_cpi!(PoolCreatorUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_creator,
+        new_creator: ctx.accounts.pending_pool_creator.key(),
+        pending: false,
+        signer: ctx.accounts.pending_pool_creator.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
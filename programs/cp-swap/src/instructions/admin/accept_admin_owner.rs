@@ -0,0 +1,37 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdminOwner<'info> {
+    /// The proposed admin owner
+    #[account(address = admin_state.pending_owner @ ErrorCode::InvalidOwner)]
+    pub pending_owner: Signer<'info>,
+
+    /// Admin state account to be changed
+    #[account(
+        mut,
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+}
+
+pub fn accept_admin_owner(ctx: Context<AcceptAdminOwner>) -> Result<()> {
+    let admin_state = &mut ctx.accounts.admin_state;
+    let old_owner = admin_state.owner;
+    admin_state.owner = ctx.accounts.pending_owner.key();
+    admin_state.pending_owner = Pubkey::default();
+
+    emit_cpi!(AdminOwnerUpdated {
+        old_owner,
+        new_owner: ctx.accounts.pending_owner.key(),
+        pending: false,
+        signer: ctx.accounts.pending_owner.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
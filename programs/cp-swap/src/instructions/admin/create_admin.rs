@@ -0,0 +1,47 @@
+use crate::error::ErrorCode;
+use crate::{states::*, PROTOCOL_AUTHORITY};
+use anchor_lang::prelude::*;
+use std::ops::DerefMut;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CreateAdmin<'info> {
+    /// Bootstrap authority, only needed once to create the registry.
+    #[account(
+        mut,
+        address = PROTOCOL_AUTHORITY @ ErrorCode::InvalidOwner
+    )]
+    pub payer: Signer<'info>,
+
+    /// Initialize admin state account to store the owner and the role holders.
+    #[account(
+        init,
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump,
+        payer = payer,
+        space = AdminState::LEN
+    )]
+    pub admin_state: Account<'info, AdminState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn create_admin(ctx: Context<CreateAdmin>, owner: Pubkey) -> Result<()> {
+    require_keys_neq!(owner, Pubkey::default(), ErrorCode::InvalidInput);
+    let admin_state = ctx.accounts.admin_state.deref_mut();
+    admin_state.bump = ctx.bumps.admin_state;
+    admin_state.owner = owner;
+    admin_state.pending_owner = Pubkey::default();
+    admin_state.fee_manager = owner;
+    admin_state.pauser = owner;
+    admin_state.offset_manager = owner;
+
+    emit_cpi!(AdminCreated {
+        owner,
+        signer: ctx.accounts.payer.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
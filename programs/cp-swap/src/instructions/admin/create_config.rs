@@ -1,18 +1,30 @@
+use crate::curve::{CurveCalculator, CurveType};
 use crate::error::ErrorCode;
-use crate::{states::*, PROTOCOL_AUTHORITY};
+use crate::states::*;
 use anchor_lang::prelude::*;
+use anchor_spl::token_interface::Mint;
 use std::ops::DerefMut;
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(index: u16)]
 pub struct CreateAmmConfig<'info> {
     /// Address to be set as protocol owner.
     #[account(
         mut,
-        address = PROTOCOL_AUTHORITY @ ErrorCode::InvalidOwner
+        constraint = admin_state.has_role(&owner.key(), AdminRole::FeeManager) @ ErrorCode::InvalidOwner
     )]
     pub owner: Signer<'info>,
 
+    /// The admin registry
+    #[account(
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Box<Account<'info, AdminState>>,
+
     /// Initialize config state account to store protocol owner address and fee rates.
     #[account(
         init,
@@ -26,15 +38,23 @@ pub struct CreateAmmConfig<'info> {
     )]
     pub amm_config: Account<'info, AmmConfig>,
 
+    /// The mint every pool of this config will be quoted in
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
     pub system_program: Program<'info, System>,
 }
 
+pub fn validate_curve_type(curve_type: CurveType) -> Result<()> {
+    CurveCalculator::new(curve_type).validate()
+}
+
 pub fn create_amm_config(
     ctx: Context<CreateAmmConfig>,
     index: u16,
     trade_fee_rate: u64,
     protocol_fee_rate: u64,
     protocol_fee_collector: Pubkey,
+    curve_type: CurveType,
 ) -> Result<()> {
     let amm_config = ctx.accounts.amm_config.deref_mut();
     amm_config.protocol_fee_collector = protocol_fee_collector;
@@ -43,5 +63,19 @@ pub fn create_amm_config(
     amm_config.index = index;
     amm_config.trade_fee_rate = trade_fee_rate;
     amm_config.protocol_fee_rate = protocol_fee_rate;
+    amm_config.quote_mint = ctx.accounts.quote_mint.key();
+    amm_config.curve_type = curve_type;
+
+    emit_cpi!(AmmConfigCreated {
+        amm_config: ctx.accounts.amm_config.key(),
+        index,
+        trade_fee_rate,
+        protocol_fee_rate,
+        protocol_fee_collector,
+        quote_mint: ctx.accounts.quote_mint.key(),
+        curve_type,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
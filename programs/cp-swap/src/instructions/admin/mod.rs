@@ -9,3 +9,15 @@ pub use update_pool_status::*;
 
 pub mod update_pool_offset;
 pub use update_pool_offset::*;
+
+pub mod create_admin;
+pub use create_admin::*;
+
+pub mod transfer_admin_owner;
+pub use transfer_admin_owner::*;
+
+pub mod accept_admin_owner;
+pub use accept_admin_owner::*;
+
+pub mod update_admin_role;
+pub use update_admin_role::*;
@@ -0,0 +1,42 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferAdminOwner<'info> {
+    /// The current admin owner
+    #[account(address = admin_state.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Admin state account to be changed
+    #[account(
+        mut,
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+}
+
+pub fn transfer_admin_owner(ctx: Context<TransferAdminOwner>, new_owner: Pubkey) -> Result<()> {
+    let admin_state = &mut ctx.accounts.admin_state;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "admin_state, owner:{}, pending_owner:{}",
+        admin_state.owner.to_string(),
+        new_owner.to_string()
+    );
+    // Proposing the default key cancels a pending transfer
+    admin_state.pending_owner = new_owner;
+
+    emit_cpi!(AdminOwnerUpdated {
+        old_owner: ctx.accounts.owner.key(),
+        new_owner,
+        pending: true,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
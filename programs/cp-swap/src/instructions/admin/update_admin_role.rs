@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct UpdateAdminRole<'info> {
+    /// The admin owner
+    #[account(address = admin_state.owner @ ErrorCode::InvalidOwner)]
+    pub owner: Signer<'info>,
+
+    /// Admin state account to be changed
+    #[account(
+        mut,
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Account<'info, AdminState>,
+}
+
+pub fn update_admin_role(
+    ctx: Context<UpdateAdminRole>,
+    role: AdminRole,
+    holder: Pubkey,
+) -> Result<()> {
+    require_keys_neq!(holder, Pubkey::default(), ErrorCode::InvalidInput);
+    let old_holder = ctx.accounts.admin_state.role_holder(role);
+    ctx.accounts.admin_state.set_role_holder(role, holder);
+
+    emit_cpi!(AdminRoleUpdated {
+        role,
+        old_holder,
+        new_holder: holder,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
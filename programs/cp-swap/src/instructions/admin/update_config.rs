@@ -1,54 +1,189 @@
-use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::curve::fees::{FEE_RATE_DENOMINATOR_VALUE, MAX_REFERRAL_FEE_RATE};
 use crate::error::ErrorCode;
-use crate::{states::*, PROTOCOL_AUTHORITY};
+use crate::states::*;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdateAmmConfig<'info> {
     /// The amm config owner or admin
-    #[account(address = PROTOCOL_AUTHORITY @ ErrorCode::InvalidOwner)]
+    #[account(
+        constraint = admin_state.has_role(&owner.key(), AdminRole::FeeManager) @ ErrorCode::InvalidOwner
+    )]
     pub owner: Signer<'info>,
 
+    /// The admin registry
+    #[account(
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Box<Account<'info, AdminState>>,
+
     /// Amm config account to be changed
     #[account(mut)]
     pub amm_config: Account<'info, AmmConfig>,
 }
 
-pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
+pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: AmmConfigParam) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
     let amm_config = &mut ctx.accounts.amm_config;
-    let match_param = Some(param);
-    match match_param {
-        Some(0) => update_trade_fee_rate(amm_config, value),
-        Some(1) => update_protocol_fee_rate(amm_config, value),
-        Some(2) => {
-            let new_procotol_owner = *ctx.remaining_accounts.iter().next().unwrap().key;
-            set_new_protocol_owner(amm_config, new_procotol_owner)?;
+    amm_config.apply_pending_trade_fee_rate(block_timestamp);
+    amm_config.apply_pending_dynamic_fee(block_timestamp);
+    let old_value = amm_config.get_param(&param);
+    let mut effective_time = block_timestamp;
+    match param {
+        AmmConfigParam::TradeFeeRate(trade_fee_rate) => {
+            effective_time = block_timestamp.checked_add(TIMELOCK_DURATION).unwrap();
+            queue_trade_fee_rate(amm_config, trade_fee_rate, effective_time)?
+        }
+        AmmConfigParam::ProtocolFeeRate(protocol_fee_rate) => {
+            update_protocol_fee_rate(amm_config, protocol_fee_rate)?
+        }
+        AmmConfigParam::ProtocolFeeCollector(new_protocol_owner) => {
+            set_new_protocol_owner(amm_config, new_protocol_owner)?
+        }
+        AmmConfigParam::DisableCreatePool(disable_create_pool) => {
+            amm_config.disable_create_pool = disable_create_pool
+        }
+        AmmConfigParam::GraduationThreshold(graduation_threshold) => {
+            amm_config.graduation_threshold = graduation_threshold
         }
-        Some(3) => amm_config.disable_create_pool = if value == 0 { false } else { true },
-        _ => return err!(ErrorCode::InvalidInput),
+        AmmConfigParam::DynamicFee(dynamic_fee) => {
+            // The variable fee raises the trade fee, so it is timelocked the same way
+            effective_time = block_timestamp.checked_add(TIMELOCK_DURATION).unwrap();
+            queue_dynamic_fee(amm_config, dynamic_fee, effective_time)?
+        }
+        AmmConfigParam::ReferralFeeRate(referral_fee_rate) => {
+            require_gte!(
+                MAX_REFERRAL_FEE_RATE,
+                referral_fee_rate,
+                ErrorCode::InvalidFeeRate
+            );
+            amm_config.referral_fee_rate = referral_fee_rate
+        }
+        AmmConfigParam::QuoteMint(quote_mint) => amm_config.set_quote_mint(quote_mint)?,
     }
 
+    emit_cpi!(AmmConfigUpdated {
+        amm_config: ctx.accounts.amm_config.key(),
+        old_value,
+        new_value: param,
+        effective_time,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
+
     Ok(())
 }
 
-fn update_protocol_fee_rate(amm_config: &mut Account<AmmConfig>, protocol_fee_rate: u64) {
-    assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
-    assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+pub fn validate_trade_fee_rate(trade_fee_rate: u64) -> Result<()> {
+    require_gt!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        trade_fee_rate,
+        ErrorCode::InvalidFeeRate
+    );
+    Ok(())
+}
+
+pub fn validate_protocol_fee_rate(protocol_fee_rate: u64) -> Result<()> {
+    require_gte!(
+        FEE_RATE_DENOMINATOR_VALUE,
+        protocol_fee_rate,
+        ErrorCode::InvalidFeeRate
+    );
+    Ok(())
+}
+
+fn update_protocol_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    protocol_fee_rate: u64,
+) -> Result<()> {
+    validate_protocol_fee_rate(protocol_fee_rate)?;
     amm_config.protocol_fee_rate = protocol_fee_rate;
+    Ok(())
 }
 
-fn update_trade_fee_rate(amm_config: &mut Account<AmmConfig>, trade_fee_rate: u64) {
-    assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
-    amm_config.trade_fee_rate = trade_fee_rate;
+fn queue_dynamic_fee(
+    amm_config: &mut Account<AmmConfig>,
+    dynamic_fee: DynamicFeeParam,
+    effective_time: u64,
+) -> Result<()> {
+    if dynamic_fee.variable_fee_control != 0 {
+        validate_trade_fee_rate(dynamic_fee.max_fee_rate)?;
+        require_gt!(
+            dynamic_fee.volatility_decay_period,
+            0,
+            ErrorCode::InvalidFeeRate
+        );
+    }
+    amm_config.pending_dynamic_fee = dynamic_fee;
+    amm_config.pending_dynamic_fee_effective_time = effective_time;
+    Ok(())
+}
+
+fn queue_trade_fee_rate(
+    amm_config: &mut Account<AmmConfig>,
+    trade_fee_rate: u64,
+    effective_time: u64,
+) -> Result<()> {
+    validate_trade_fee_rate(trade_fee_rate)?;
+    amm_config.pending_trade_fee_rate = trade_fee_rate;
+    amm_config.pending_trade_fee_rate_effective_time = effective_time;
+    Ok(())
+}
+
+pub fn cancel_pending_trade_fee_rate(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let amm_config = &mut ctx.accounts.amm_config;
+    // A change whose timelock has elapsed is already in effect and can not be cancelled
+    amm_config.apply_pending_trade_fee_rate(block_timestamp);
+    if amm_config.pending_trade_fee_rate_effective_time == 0 {
+        return err!(ErrorCode::NoPendingChange);
+    }
+    let pending_trade_fee_rate = amm_config.pending_trade_fee_rate;
+    amm_config.pending_trade_fee_rate = 0;
+    amm_config.pending_trade_fee_rate_effective_time = 0;
+
+    emit_cpi!(AmmConfigUpdated {
+        amm_config: ctx.accounts.amm_config.key(),
+        old_value: AmmConfigParam::TradeFeeRate(pending_trade_fee_rate),
+        new_value: AmmConfigParam::TradeFeeRate(ctx.accounts.amm_config.trade_fee_rate),
+        effective_time: block_timestamp,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_pending_dynamic_fee(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let amm_config = &mut ctx.accounts.amm_config;
+    // A change whose timelock has elapsed is already in effect and can not be cancelled
+    let pending_dynamic_fee = amm_config
+        .cancel_pending_dynamic_fee(block_timestamp)
+        .ok_or(ErrorCode::NoPendingChange)?;
+    let new_value = amm_config.get_param(&AmmConfigParam::DynamicFee(pending_dynamic_fee));
+
+    emit_cpi!(AmmConfigUpdated {
+        amm_config: ctx.accounts.amm_config.key(),
+        old_value: AmmConfigParam::DynamicFee(pending_dynamic_fee),
+        new_value,
+        effective_time: block_timestamp,
+        signer: ctx.accounts.owner.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
 }
 
 fn set_new_protocol_owner(amm_config: &mut Account<AmmConfig>, new_owner: Pubkey) -> Result<()> {
-    require_keys_neq!(new_owner, Pubkey::default());
+    require_keys_neq!(new_owner, Pubkey::default(), ErrorCode::InvalidInput);
     #[cfg(feature = "enable-log")]
     msg!(
         "amm_config, old_protocol_owner:{}, new_owner:{}",
-        amm_config.protocol_owner.to_string(),
-        new_owner.key().to_string()
+        amm_config.protocol_fee_collector.to_string(),
+        new_owner.to_string()
     );
     amm_config.protocol_fee_collector = new_owner;
     Ok(())
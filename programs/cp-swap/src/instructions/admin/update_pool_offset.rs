@@ -1,19 +1,74 @@
-use crate::{states::*, PROTOCOL_AUTHORITY};
+use crate::error::ErrorCode;
+use crate::states::*;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdatePoolOffset<'info> {
     #[account(
-        address = PROTOCOL_AUTHORITY
+        constraint = admin_state.has_role(&authority.key(), AdminRole::OffsetManager) @ ErrorCode::InvalidOwner
     )]
     pub authority: Signer<'info>,
 
-    #[account(mut)]
+    /// The admin registry
+    #[account(
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Box<Account<'info, AdminState>>,
+
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
     pub pool_state: AccountLoader<'info, PoolState>,
 }
 
 pub fn update_pool_offset(ctx: Context<UpdatePoolOffset>, offset: u64) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
-    pool_state.off_set = offset;
+    pool_state.apply_pending_off_set(block_timestamp);
+    // A bonding curve starts at its initial price only without a virtual offset
+    if pool_state.curve_type().is_bonding_curve() {
+        return err!(ErrorCode::InvalidCurve);
+    }
+    let old_off_set = pool_state.off_set;
+    let effective_time = block_timestamp.checked_add(TIMELOCK_DURATION).unwrap();
+    pool_state.pending_off_set = offset;
+    pool_state.pending_off_set_effective_time = effective_time;
+
+    emit_cpi!(PoolOffsetUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_off_set,
+        new_off_set: offset,
+        effective_time,
+        signer: ctx.accounts.authority.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
+
+pub fn cancel_pending_pool_offset(ctx: Context<UpdatePoolOffset>) -> Result<()> {
+    let block_timestamp = Clock::get()?.unix_timestamp as u64;
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    // A change whose timelock has elapsed is already in effect and can not be cancelled
+    pool_state.apply_pending_off_set(block_timestamp);
+    if pool_state.pending_off_set_effective_time == 0 {
+        return err!(ErrorCode::NoPendingChange);
+    }
+    let pending_off_set = pool_state.pending_off_set;
+    pool_state.pending_off_set = 0;
+    pool_state.pending_off_set_effective_time = 0;
+
+    emit_cpi!(PoolOffsetUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_off_set: pending_off_set,
+        new_off_set: pool_state.off_set,
+        effective_time: block_timestamp,
+        signer: ctx.accounts.authority.key(),
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
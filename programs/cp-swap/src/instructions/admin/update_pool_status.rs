@@ -1,21 +1,44 @@
-use crate::{states::*, PROTOCOL_AUTHORITY};
+use crate::error::ErrorCode;
+use crate::states::*;
 use anchor_lang::prelude::*;
 
+#[event_cpi]
 #[derive(Accounts)]
 pub struct UpdatePoolStatus<'info> {
     #[account(
-        address = PROTOCOL_AUTHORITY
+        constraint = admin_state.has_role(&authority.key(), AdminRole::Pauser) @ ErrorCode::InvalidOwner
     )]
     pub authority: Signer<'info>,
 
-    #[account(mut)]
+    /// The admin registry
+    #[account(
+        seeds = [
+            ADMIN_SEED.as_bytes(),
+        ],
+        bump = admin_state.bump,
+    )]
+    pub admin_state: Box<Account<'info, AdminState>>,
+
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
     pub pool_state: AccountLoader<'info, PoolState>,
 }
 
 pub fn update_pool_status(ctx: Context<UpdatePoolStatus>, status: u8) -> Result<()> {
     require_gte!(255, status);
     let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    let old_status = pool_state.status;
     pool_state.set_status(status);
     pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_cpi!(PoolStatusUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_status,
+        new_status: status,
+        signer: ctx.accounts.authority.key(),
+        slot: Clock::get()?.slot,
+    });
     Ok(())
 }
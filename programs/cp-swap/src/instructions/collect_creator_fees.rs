@@ -0,0 +1,143 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct CollectCreatorFees<'info> {
+    /// Only the pool creator can collect the creator fees
+    #[account(address = pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner)]
+    pub pool_creator: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated creator fee amount
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for the pool mint
+    #[account(
+        mut,
+        address = pool_state.load()?.token_mint_vault
+    )]
+    pub token_mint_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for the quote token
+    #[account(
+        mut,
+        address = pool_state.load()?.token_quote_vault
+    )]
+    pub token_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the pool mint vault
+    #[account(
+        address = pool_state.load()?.mint
+    )]
+    pub vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the quote vault
+    #[account(
+        address = pool_state.load()?.quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub vault_quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The address that receives the collected pool mint fees
+    #[account(
+        mut,
+        token::mint = vault_mint,
+    )]
+    pub recipient_token_mint_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that receives the collected quote fees
+    #[account(
+        mut,
+        token::mint = vault_quote_mint,
+    )]
+    pub recipient_token_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The SPL program of the pool mint to perform token transfers
+    #[account(address = pool_state.load()?.mint_token_program)]
+    pub mint_token_program: Interface<'info, TokenInterface>,
+
+    /// The SPL program of the quote mint to perform token transfers
+    #[account(address = pool_state.load()?.quote_token_program)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn collect_creator_fees(
+    ctx: Context<CollectCreatorFees>,
+    amount_mint_requested: Option<u64>,
+    amount_quote_requested: Option<u64>,
+) -> Result<()> {
+    let creator_amount_0: u64;
+    let creator_amount_1: u64;
+    let auth_bump;
+    let mint;
+    {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        // Split creator fees are only paid out by `distribute_creator_fees`
+        if pool_state.creator_fee_split {
+            return err!(ErrorCode::InvalidFeeSplit);
+        }
+        creator_amount_0 = amount_mint_requested
+            .unwrap_or(u64::MAX)
+            .min(pool_state.creator_fees_token_mint);
+        creator_amount_1 = amount_quote_requested
+            .unwrap_or(u64::MAX)
+            .min(pool_state.creator_fees_token_quote);
+        pool_state.creator_fees_token_mint = pool_state
+            .creator_fees_token_mint
+            .checked_sub(creator_amount_0)
+            .unwrap();
+        pool_state.creator_fees_token_quote = pool_state
+            .creator_fees_token_quote
+            .checked_sub(creator_amount_1)
+            .unwrap();
+        auth_bump = pool_state.auth_bump;
+        mint = pool_state.mint;
+    }
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_mint_vault.to_account_info(),
+        ctx.accounts.recipient_token_mint_account.to_account_info(),
+        ctx.accounts.vault_mint.to_account_info(),
+        ctx.accounts.mint_token_program.to_account_info(),
+        creator_amount_0,
+        ctx.accounts.vault_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_quote_vault.to_account_info(),
+        ctx.accounts.recipient_token_quote_account.to_account_info(),
+        ctx.accounts.vault_quote_mint.to_account_info(),
+        ctx.accounts.quote_token_program.to_account_info(),
+        creator_amount_1,
+        ctx.accounts.vault_quote_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    emit_cpi!(CollectFees {
+        mint,
+        creator_mint_fees: creator_amount_0,
+        creator_quote_fees: creator_amount_1,
+        protocol_mint_fees: 0,
+        protocol_quote_fees: 0
+    });
+
+    Ok(())
+}
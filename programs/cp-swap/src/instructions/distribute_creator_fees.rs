@@ -0,0 +1,164 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::transfer_from_pool_vault_to_user;
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct DistributeCreatorFees<'info> {
+    /// Anyone can distribute the creator fees to the fee split recipients
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state stores accumulated creator fee amount
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The fee split of the pool
+    #[account(
+        seeds = [
+            FEE_SPLIT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump = fee_split_state.bump,
+    )]
+    pub fee_split_state: Box<Account<'info, FeeSplitState>>,
+
+    /// The address that holds pool tokens for the pool mint
+    #[account(
+        mut,
+        address = pool_state.load()?.token_mint_vault
+    )]
+    pub token_mint_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for the quote token
+    #[account(
+        mut,
+        address = pool_state.load()?.token_quote_vault
+    )]
+    pub token_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the pool mint vault
+    #[account(
+        address = pool_state.load()?.mint
+    )]
+    pub vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the quote vault
+    #[account(
+        address = pool_state.load()?.quote_mint @ ErrorCode::InvalidQuoteMint
+    )]
+    pub vault_quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The SPL program of the pool mint to perform token transfers
+    #[account(address = pool_state.load()?.mint_token_program)]
+    pub mint_token_program: Interface<'info, TokenInterface>,
+
+    /// The SPL program of the quote mint to perform token transfers
+    #[account(address = pool_state.load()?.quote_token_program)]
+    pub quote_token_program: Interface<'info, TokenInterface>,
+}
+
+/// Loads the token account of a fee split recipient passed in the remaining accounts
+fn recipient_token_account<'info>(
+    account_info: &'info AccountInfo<'info>,
+    recipient: &Pubkey,
+    mint: &Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let token_account = InterfaceAccount::<TokenAccount>::try_from(account_info)?;
+    require_keys_eq!(token_account.owner, *recipient, ErrorCode::InvalidFeeSplit);
+    require_keys_eq!(token_account.mint, *mint, ErrorCode::InvalidFeeSplit);
+    Ok(account_info.clone())
+}
+
+/// Distributes the creator fees to the fee split recipients.
+/// The remaining accounts are the pool mint and quote token accounts of every recipient, in order.
+pub fn distribute_creator_fees<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, DistributeCreatorFees<'info>>,
+) -> Result<()> {
+    let fee_split_state = &ctx.accounts.fee_split_state;
+    let recipients = fee_split_state.recipients();
+    // A cleared split pays the creator through `collect_creator_fees` again
+    require_gt!(recipients.len(), 0, ErrorCode::InvalidFeeSplit);
+    require_eq!(
+        ctx.remaining_accounts.len(),
+        recipients.len() * 2,
+        ErrorCode::InvalidFeeSplit
+    );
+
+    let creator_amount_0: u64;
+    let creator_amount_1: u64;
+    let auth_bump;
+    let mint;
+    {
+        let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+        creator_amount_0 = pool_state.creator_fees_token_mint;
+        creator_amount_1 = pool_state.creator_fees_token_quote;
+        pool_state.creator_fees_token_mint = 0;
+        pool_state.creator_fees_token_quote = 0;
+        auth_bump = pool_state.auth_bump;
+        mint = pool_state.mint;
+    }
+
+    let mint_amounts = fee_split_state.split(creator_amount_0);
+    let quote_amounts = fee_split_state.split(creator_amount_1);
+    for (index, fee_split_recipient) in recipients.iter().enumerate() {
+        let recipient_token_mint_account = recipient_token_account(
+            &ctx.remaining_accounts[2 * index],
+            &fee_split_recipient.recipient,
+            &ctx.accounts.vault_mint.key(),
+        )?;
+        let recipient_token_quote_account = recipient_token_account(
+            &ctx.remaining_accounts[2 * index + 1],
+            &fee_split_recipient.recipient,
+            &ctx.accounts.vault_quote_mint.key(),
+        )?;
+
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_mint_vault.to_account_info(),
+            recipient_token_mint_account,
+            ctx.accounts.vault_mint.to_account_info(),
+            ctx.accounts.mint_token_program.to_account_info(),
+            mint_amounts[index],
+            ctx.accounts.vault_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            ctx.accounts.token_quote_vault.to_account_info(),
+            recipient_token_quote_account,
+            ctx.accounts.vault_quote_mint.to_account_info(),
+            ctx.accounts.quote_token_program.to_account_info(),
+            quote_amounts[index],
+            ctx.accounts.vault_quote_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+        )?;
+    }
+
+    emit_cpi!(CreatorFeesDistributed {
+        pool_id: ctx.accounts.pool_state.key(),
+        mint,
+        recipients: recipients
+            .iter()
+            .map(|fee_split_recipient| fee_split_recipient.recipient)
+            .collect(),
+        mint_amounts,
+        quote_amounts,
+    });
+
+    Ok(())
+}
@@ -0,0 +1,286 @@
+use super::quote::{quote_swap_base_output, QuoteContext};
+use super::swap_base_input::update_observation;
+use crate::curve::TradeDirection;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    self,
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct FlashSwap<'info> {
+    /// The user performing the flash swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool in which the swap will be performed
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The token account receiving the output token before the callback
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for input token, the callback has to pay the input into it
+    #[account(
+        mut,
+        constraint = input_vault.key() == pool_state.load()?.token_mint_vault || input_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(
+        mut,
+        constraint = output_vault.key() == pool_state.load()?.token_mint_vault || output_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for output token transfers
+    pub output_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// CHECK: the program called back once the output is sent, it has to pay the input
+    #[account(
+        executable,
+        constraint = callback_program.key() != crate::id() @ ErrorCode::InvalidInput
+    )]
+    pub callback_program: UncheckedAccount<'info>,
+}
+
+/// Sends `amount_out_less_fee` to the user first, then calls back `callback_program` with `data`
+/// and the remaining accounts. The callback has to pay the input a regular `swap_base_output`
+/// would charge, fees included, into the input vault. The pool is locked during the callback.
+pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+    amount_out_less_fee: u64,
+    data: Vec<u8>,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let input_vault_before = ctx.accounts.input_vault.amount;
+    let output_vault_before = ctx.accounts.output_vault.amount;
+    let trade_direction;
+    let swap_quote;
+    let token_0_price_x32;
+    let auth_bump;
+    let pool_state_before;
+    {
+        let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+        if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+            || block_timestamp < pool_state.open_time
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+        pool_state.apply_pending_off_set(block_timestamp);
+
+        // Charged exactly as a swap base output of the same amount, without referrer
+        let quote_context = QuoteContext {
+            pool_state,
+            amm_config: &ctx.accounts.amm_config,
+            input_vault: &ctx.accounts.input_vault,
+            output_vault: &ctx.accounts.output_vault,
+            input_token_mint: &ctx.accounts.input_token_mint,
+            output_token_mint: &ctx.accounts.output_token_mint,
+            block_timestamp,
+            referral_fee_rate: 0,
+        };
+        (trade_direction, swap_quote) =
+            quote_swap_base_output(&quote_context, amount_out_less_fee, None)?;
+        // Flash swaps don't carry a purchase account, so they can't buy while buys are capped
+        if trade_direction == TradeDirection::OneForZero
+            && pool_state.purchase_cap_active(block_timestamp)
+        {
+            return err!(ErrorCode::NotApproved);
+        }
+
+        token_0_price_x32 = update_observation(
+            pool_state,
+            &ctx.accounts.observation_state,
+            trade_direction,
+            input_vault_before,
+            output_vault_before,
+        )?;
+        auth_bump = pool_state.auth_bump;
+
+        // Every pool instruction rejects the pool until the callback returns
+        pool_state_before = flash_swap_state(pool_state);
+        pool_state.locked = true;
+    }
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.output_vault.to_account_info(),
+        ctx.accounts.output_token_account.to_account_info(),
+        ctx.accounts.output_token_mint.to_account_info(),
+        ctx.accounts.output_token_program.to_account_info(),
+        swap_quote.amount_out,
+        ctx.accounts.output_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[auth_bump]]],
+    )?;
+
+    // The pool state isn't borrowed here, so the callback may read it
+    let callback_instruction = Instruction {
+        program_id: ctx.accounts.callback_program.key(),
+        accounts: ctx
+            .remaining_accounts
+            .iter()
+            .map(|account_info| AccountMeta {
+                pubkey: account_info.key(),
+                is_signer: account_info.is_signer,
+                is_writable: account_info.is_writable,
+            })
+            .collect(),
+        data,
+    };
+    let mut callback_account_infos = ctx.remaining_accounts.to_vec();
+    callback_account_infos.push(ctx.accounts.callback_program.to_account_info());
+    invoke(&callback_instruction, &callback_account_infos)?;
+
+    ctx.accounts.input_vault.reload()?;
+    ctx.accounts.output_vault.reload()?;
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // The lock keeps the callback out of the pool, its liquidity and fees must be untouched
+    require!(
+        pool_state.locked && flash_swap_state(pool_state) == pool_state_before,
+        ErrorCode::PoolLocked
+    );
+    pool_state.locked = false;
+
+    // The input vault already received the input net of any transfer fee
+    let actual_amount_in = ctx
+        .accounts
+        .input_vault
+        .amount
+        .checked_sub(input_vault_before)
+        .ok_or(ErrorCode::FlashSwapNotRepaid)?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "actual_amount_in:{}, amount_in:{}, amount_out:{}",
+        actual_amount_in,
+        swap_quote.amount_in,
+        swap_quote.amount_out
+    );
+    require_gte!(
+        actual_amount_in,
+        swap_quote
+            .amount_in
+            .checked_sub(swap_quote.input_transfer_fee)
+            .unwrap(),
+        ErrorCode::FlashSwapNotRepaid
+    );
+
+    match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.protocol_fees_token_mint = pool_state
+                .protocol_fees_token_mint
+                .checked_add(swap_quote.protocol_fee)
+                .unwrap();
+            pool_state.creator_fees_token_mint = pool_state
+                .creator_fees_token_mint
+                .checked_add(swap_quote.creator_fee)
+                .unwrap();
+        }
+        TradeDirection::OneForZero => {
+            pool_state.protocol_fees_token_quote = pool_state
+                .protocol_fees_token_quote
+                .checked_add(swap_quote.protocol_fee)
+                .unwrap();
+            pool_state.creator_fees_token_quote = pool_state
+                .creator_fees_token_quote
+                .checked_add(swap_quote.creator_fee)
+                .unwrap();
+        }
+    };
+
+    let input_vault_after = ctx.accounts.input_vault.amount;
+    let output_vault_after = ctx.accounts.output_vault.amount;
+    let token_0_price_x64 = match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state
+                .token_price_x32(input_vault_after, output_vault_after)
+                .0
+        }
+        TradeDirection::OneForZero => {
+            pool_state
+                .token_price_x32(output_vault_after, input_vault_after)
+                .0
+        }
+    };
+    pool_state.update_volatility(
+        token_0_price_x32,
+        token_0_price_x64,
+        block_timestamp,
+        ctx.accounts
+            .amm_config
+            .dynamic_fee_param_at(block_timestamp)
+            .volatility_decay_period,
+    );
+
+    let (liquidity_before, liquidity_after) = match trade_direction {
+        TradeDirection::ZeroForOne => (output_vault_before, output_vault_after),
+        TradeDirection::OneForZero => (input_vault_before, input_vault_after),
+    };
+    emit_cpi!(SwapPriceEvent {
+        timestamp: oracle::block_timestamp(),
+        mint: pool_state.mint,
+        price: token_0_price_x64,
+        liquidity_before,
+        liquidity_after,
+        input_amount: actual_amount_in,
+        output_amount: swap_quote.amount_out,
+        buy: trade_direction == TradeDirection::OneForZero,
+        user: ctx.accounts.payer.key(),
+        referrer: None,
+    });
+
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
+
+/// The liquidity and fees of the pool, which the callback of a flash swap must not change
+fn flash_swap_state(pool_state: &PoolState) -> [u64; 6] {
+    [
+        pool_state.lp_supply,
+        pool_state.off_set,
+        pool_state.protocol_fees_token_mint,
+        pool_state.protocol_fees_token_quote,
+        pool_state.creator_fees_token_mint,
+        pool_state.creator_fees_token_quote,
+    ]
+}
@@ -0,0 +1,58 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[derive(Accounts)]
+pub struct GetTwap<'info> {
+    /// The oracle observations of the pool to average over
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+/// Time-weighted average prices returned by `get_twap`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct TwapPrice {
+    /// The pool the observations belong to
+    pub pool_id: Pubkey,
+    /// Start of the averaging window
+    pub start_timestamp: u64,
+    /// End of the averaging window, the timestamp of the most recent observation
+    pub end_timestamp: u64,
+    /// Average price of token_0 in token_1, Q32.32
+    pub token_0_price_x32: u128,
+    /// Average price of token_1 in token_0, Q32.32
+    pub token_1_price_x32: u128,
+}
+
+pub fn get_twap(ctx: Context<GetTwap>, seconds: u64) -> Result<TwapPrice> {
+    require_gt!(seconds, 0, ErrorCode::InvalidInput);
+    let observation_state = ctx.accounts.observation_state.load()?;
+    if !observation_state.initialized {
+        return err!(ErrorCode::InsufficientObservations);
+    }
+    let end_timestamp = observation_state.observations
+        [observation_state.observation_index as usize]
+        .block_timestamp;
+    let start_timestamp = end_timestamp
+        .checked_sub(seconds)
+        .ok_or(ErrorCode::InsufficientObservations)?;
+    let (token_0_price_x32, token_1_price_x32) = observation_state
+        .get_twap(start_timestamp, end_timestamp)
+        .ok_or(ErrorCode::InsufficientObservations)?;
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "twap start_timestamp:{}, end_timestamp:{}, token_0_price_x32:{}, token_1_price_x32:{}",
+        start_timestamp,
+        end_timestamp,
+        token_0_price_x32,
+        token_1_price_x32
+    );
+
+    Ok(TwapPrice {
+        pool_id: observation_state.pool_id,
+        start_timestamp,
+        end_timestamp,
+        token_0_price_x32,
+        token_1_price_x32,
+    })
+}
@@ -0,0 +1,140 @@
+use crate::curve::CurveType;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::*;
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token::Token,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Graduate<'info> {
+    /// Address paying to create the graduated lp mint. Can be anyone
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The pool leaving its launch curve
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for the pool mint
+    #[account(
+        mut,
+        constraint = token_mint_vault.key() == pool_state.load()?.token_mint_vault
+    )]
+    pub token_mint_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for the quote token
+    #[account(
+        constraint = token_quote_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub token_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the pool mint vault, the excess supply is burned from the vault
+    #[account(
+        mut,
+        address = token_mint_vault.mint
+    )]
+    pub vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The lp mint of the graduated pool, the launch lp mint is retired
+    #[account(
+        init,
+        seeds = [
+            POOL_GRADUATED_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = 9,
+        mint::authority = authority,
+        payer = payer,
+        mint::token_program = token_program,
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Spl token program or token program 2022 of the pool mint
+    #[account(address = pool_state.load()?.mint_token_program)]
+    pub mint_token_program: Interface<'info, TokenInterface>,
+
+    /// Program to create the lp mint
+    pub token_program: Program<'info, Token>,
+
+    /// To create a new program account
+    pub system_program: Program<'info, System>,
+}
+
+pub fn graduate(ctx: Context<Graduate>) -> Result<()> {
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    pool_state.apply_pending_off_set(Clock::get()?.unix_timestamp as u64);
+    if !pool_state.graduation_reached(ctx.accounts.token_quote_vault.amount) {
+        return err!(ErrorCode::GraduationNotReached);
+    }
+    let (_, total_quote_amount) = pool_state.lp_vault_amount(
+        ctx.accounts.token_mint_vault.amount,
+        ctx.accounts.token_quote_vault.amount,
+    );
+    let (graduated_mint_amount, burn_amount, liquidity) = pool_state.graduation_amounts(
+        ctx.accounts.token_mint_vault.amount,
+        ctx.accounts.token_quote_vault.amount,
+    );
+    // The graduated liquidity is never minted, which locks it in the pool for good
+    require_gt!(liquidity, 0, ErrorCode::ZeroTradingTokens);
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "graduate graduated_mint_amount:{}, total_quote_amount:{}, off_set:{}, burn_amount:{}, liquidity:{}",
+        graduated_mint_amount,
+        total_quote_amount,
+        pool_state.off_set,
+        burn_amount,
+        liquidity
+    );
+
+    token_burn(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.mint_token_program.to_account_info(),
+        ctx.accounts.vault_mint.to_account_info(),
+        ctx.accounts.token_mint_vault.to_account_info(),
+        burn_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    let old_off_set = pool_state.off_set;
+    pool_state.off_set = 0;
+    pool_state.pending_off_set = 0;
+    pool_state.pending_off_set_effective_time = 0;
+    pool_state.lp_mint = ctx.accounts.lp_mint.key();
+    pool_state.lp_mint_decimals = ctx.accounts.lp_mint.decimals;
+    pool_state.lp_supply = liquidity;
+    pool_state.graduated = true;
+    pool_state.curve_type = CurveType::ConstantProduct.discriminator();
+    pool_state.curve_parameters = CurveType::ConstantProduct.parameters();
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    emit_cpi!(PoolGraduated {
+        pool_id: ctx.accounts.pool_state.key(),
+        mint: pool_state.mint,
+        off_set: old_off_set,
+        burned_mint_amount: burn_amount,
+        mint_amount: graduated_mint_amount,
+        quote_amount: total_quote_amount,
+        lp_mint: ctx.accounts.lp_mint.key(),
+        liquidity,
+    });
+
+    Ok(())
+}
@@ -2,14 +2,13 @@ use std::ops::Deref;
 
 use crate::curve::CurveCalculator;
 use crate::error::ErrorCode;
+use crate::instructions::validate_trade_fee_rate;
 use crate::states::*;
 use crate::utils::*;
-use crate::USDC;
 use anchor_lang::{
     accounts::interface_account::InterfaceAccount, prelude::*, solana_program::clock,
 };
 use anchor_spl::{
-    associated_token::AssociatedToken,
     token::Token,
     token_2022::spl_token_2022,
     token_interface::{Mint, TokenAccount, TokenInterface},
@@ -52,12 +51,27 @@ pub struct Initialize<'info> {
     )]
     pub mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// Token_1 mint, the key must grater then token_0 mint.
+    /// Quote token mint, must match the quote mint of the amm config.
     #[account(
-        address = USDC,
+        address = amm_config.quote_mint @ ErrorCode::InvalidQuoteMint,
+        mint::token_program = quote_token_program,
+    )]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// pool lp mint
+    #[account(
+        init,
+        seeds = [
+            POOL_LP_MINT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        mint::decimals = 9,
+        mint::authority = authority,
+        payer = creator,
         mint::token_program = token_program,
     )]
-    pub usdc: Box<InterfaceAccount<'info, Mint>>,
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// payer token0 account
     #[account(
@@ -70,10 +84,10 @@ pub struct Initialize<'info> {
     /// creator token1 account
     #[account(
         mut,
-        token::mint = usdc,
+        token::mint = quote_mint,
         token::authority = creator,
     )]
-    pub creator_token_usdc: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub creator_token_quote: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: Token_0 vault for the pool
     #[account(
@@ -93,31 +107,31 @@ pub struct Initialize<'info> {
         seeds = [
             POOL_VAULT_SEED.as_bytes(),
             pool_state.key().as_ref(),
-            usdc.key().as_ref()
+            quote_mint.key().as_ref()
         ],
         bump,
     )]
-    pub token_usdc_vault: UncheckedAccount<'info>,
+    pub token_quote_vault: UncheckedAccount<'info>,
 
     /// an account to store oracle observations
-    // #[account(
-    //     init,
-    //     seeds = [
-    //         OBSERVATION_SEED.as_bytes(),
-    //         pool_state.key().as_ref(),
-    //     ],
-    //     bump,
-    //     payer = creator,
-    //     space = ObservationState::LEN
-    // )]
-    // pub observation_state: AccountLoader<'info, ObservationState>,
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = creator,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
 
     /// Program to create mint account and mint tokens
     pub token_program: Program<'info, Token>,
     /// Spl token program or token program 2022
     pub mint_token_program: Interface<'info, TokenInterface>,
-    /// Program to create an ATA for receiving position NFT
-    pub associated_token_program: Program<'info, AssociatedToken>,
+    /// Spl token program or token program 2022 of the quote mint
+    pub quote_token_program: Interface<'info, TokenInterface>,
     /// To create a new program account
     pub system_program: Program<'info, System>,
     /// Sysvar for program account
@@ -129,9 +143,11 @@ pub fn initialize(
     mint_amount: u64,
     offset: u64,
     mut open_time: u64,
+    launch_fee: Option<LaunchFeeParam>,
+    purchase_cap: Option<PurchaseCapParam>,
 ) -> Result<()> {
     if !(is_supported_mint(&ctx.accounts.mint).unwrap()
-        && is_supported_mint(&ctx.accounts.usdc).unwrap())
+        && is_supported_mint(&ctx.accounts.quote_mint).unwrap())
     {
         return err!(ErrorCode::NotSupportMint);
     }
@@ -139,6 +155,19 @@ pub fn initialize(
     if ctx.accounts.amm_config.disable_create_pool {
         return err!(ErrorCode::NotApproved);
     }
+    if let Some(launch_fee) = launch_fee {
+        validate_trade_fee_rate(launch_fee.start_fee_rate)?;
+        if launch_fee.end_fee_rate > launch_fee.start_fee_rate || launch_fee.duration == 0 {
+            return err!(ErrorCode::InvalidLaunchFee);
+        }
+    }
+    if let Some(purchase_cap) = purchase_cap {
+        if purchase_cap.duration == 0
+            || (purchase_cap.max_quote_amount_in == 0 && purchase_cap.max_mint_amount_out == 0)
+        {
+            return err!(ErrorCode::InvalidPurchaseCap);
+        }
+    }
     let block_timestamp = clock::Clock::get()?.unix_timestamp as u64;
     if open_time <= block_timestamp {
         open_time = block_timestamp + 1;
@@ -162,20 +191,20 @@ pub fn initialize(
     create_token_account(
         &ctx.accounts.authority.to_account_info(),
         &ctx.accounts.creator.to_account_info(),
-        &ctx.accounts.token_usdc_vault.to_account_info(),
-        &ctx.accounts.usdc.to_account_info(),
+        &ctx.accounts.token_quote_vault.to_account_info(),
+        &ctx.accounts.quote_mint.to_account_info(),
         &ctx.accounts.system_program.to_account_info(),
-        &ctx.accounts.token_program.to_account_info(),
+        &ctx.accounts.quote_token_program.to_account_info(),
         &[&[
             POOL_VAULT_SEED.as_bytes(),
             ctx.accounts.pool_state.key().as_ref(),
-            ctx.accounts.usdc.key().as_ref(),
-            &[ctx.bumps.token_usdc_vault][..],
+            ctx.accounts.quote_mint.key().as_ref(),
+            &[ctx.bumps.token_quote_vault][..],
         ][..]],
     )?;
 
-    // let mut observation_state = ctx.accounts.observation_state.load_init()?;
-    // observation_state.pool_id = ctx.accounts.pool_state.key();
+    let mut observation_state = ctx.accounts.observation_state.load_init()?;
+    observation_state.pool_id = ctx.accounts.pool_state.key();
 
     let pool_state = &mut ctx.accounts.pool_state.load_init()?;
 
@@ -199,18 +228,58 @@ pub fn initialize(
         )?
         .base;
 
-    CurveCalculator::validate_supply(token_mint_vault.amount, offset)?;
+    let curve_type = ctx.accounts.amm_config.curve_type;
+    CurveCalculator::new(curve_type).validate_supply(token_mint_vault.amount, offset)?;
+    // A bonding curve has to graduate, its reserves can't back pro rata liquidity
+    if curve_type.is_bonding_curve() && ctx.accounts.amm_config.graduation_threshold == 0 {
+        return err!(ErrorCode::InvalidCurve);
+    }
+
+    // The virtual offset stands in for the quote side of the initial liquidity
+    let liquidity = U128::from(token_mint_vault.amount)
+        .checked_mul(offset.into())
+        .unwrap()
+        .integer_sqrt()
+        .as_u64();
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "liquidity:{}, vault_mint_amount:{},off_set:{}",
+        liquidity,
+        token_mint_vault.amount,
+        offset
+    );
+    // Nothing but the virtual offset backs the quote side, so the initial liquidity is never
+    // minted and stays locked in the pool, no lp can claim the quote tokens paid by buyers.
+    // Bonding curves have no offset, their liquidity only exists once they graduate
+    if !curve_type.is_bonding_curve() {
+        require_gt!(liquidity, 0, ErrorCode::InitLpAmountTooLess);
+    }
 
     pool_state.initialize(
         offset,
+        ctx.accounts.amm_config.graduation_threshold,
+        launch_fee,
+        purchase_cap,
+        curve_type,
         ctx.bumps.authority,
         open_time,
         ctx.accounts.creator.key(),
         ctx.accounts.amm_config.key(),
         ctx.accounts.token_mint_vault.key(),
-        ctx.accounts.token_usdc_vault.key(),
+        ctx.accounts.token_quote_vault.key(),
         &ctx.accounts.mint,
-        Pubkey::default(),
+        &ctx.accounts.quote_mint,
+        &ctx.accounts.lp_mint,
+        ctx.accounts.observation_state.key(),
+    );
+    pool_state.lp_supply = liquidity;
+
+    let (token_0_price_x32, token_1_price_x32, _) =
+        pool_state.token_price_x32(token_mint_vault.amount, 0);
+    observation_state.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
     );
 
     emit_cpi!(InitializePool {
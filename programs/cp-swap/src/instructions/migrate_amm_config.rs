@@ -0,0 +1,50 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::{prelude::*, system_program, Discriminator};
+
+#[derive(Accounts)]
+pub struct MigrateAmmConfig<'info> {
+    /// Address paying the rent of the grown account. Can be anyone
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// CHECK: the version 0 amm config, it can't be deserialized before it is grown to the
+    /// current layout. Its owner and discriminator are checked
+    #[account(mut, owner = crate::id())]
+    pub amm_config: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a version 0 amm config to the current layout. The appended fields are zeroed, the
+/// defaults of a constant product config without graduation, dynamic or referral fee and nothing
+/// queued. The quote mint is left unset until the fee manager records it
+pub fn migrate_amm_config(ctx: Context<MigrateAmmConfig>) -> Result<()> {
+    let amm_config_info = ctx.accounts.amm_config.to_account_info();
+    if amm_config_info.data_len() != AMM_CONFIG_V0_LEN {
+        return err!(ErrorCode::InvalidConfigVersion);
+    }
+    if amm_config_info.try_borrow_data()?[..8] != AmmConfig::DISCRIMINATOR {
+        return err!(ErrorCode::InvalidConfigVersion);
+    }
+    let lamports = Rent::get()?
+        .minimum_balance(AmmConfig::LEN)
+        .saturating_sub(amm_config_info.lamports());
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: amm_config_info.clone(),
+                },
+            ),
+            lamports,
+        )?;
+    }
+    amm_config_info.realloc(AmmConfig::LEN, true)?;
+    #[cfg(feature = "enable-log")]
+    msg!("migrate amm_config:{}", ctx.accounts.amm_config.key());
+
+    Ok(())
+}
@@ -0,0 +1,87 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::{prelude::*, system_program};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct MigratePoolState<'info> {
+    /// Address paying the rent of the grown account. Can be anyone
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    /// The version 0 pool, it can't be loaded before it is grown to the current layout
+    #[account(mut)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The address that holds pool tokens for the quote token, checked once the pool is loaded
+    pub token_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of the quote token
+    #[account(
+        address = token_quote_vault.mint
+    )]
+    pub quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// an account to store oracle observations, version 0 pools were created without one
+    #[account(
+        init,
+        seeds = [
+            OBSERVATION_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = ObservationState::LEN
+    )]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    pub system_program: Program<'info, System>,
+}
+
+/// Grows a version 0 pool state to the current layout. The appended fields are zeroed, the
+/// defaults of a constant product pool without lp mint, except those recorded from the quote mint.
+/// The observation account the swaps write to is created along
+pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+    let pool_state_info = ctx.accounts.pool_state.to_account_info();
+    if pool_state_info.data_len() != POOL_STATE_V0_LEN {
+        return err!(ErrorCode::InvalidPoolVersion);
+    }
+    let lamports = Rent::get()?
+        .minimum_balance(PoolState::LEN)
+        .saturating_sub(pool_state_info.lamports());
+    if lamports > 0 {
+        system_program::transfer(
+            CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                system_program::Transfer {
+                    from: ctx.accounts.payer.to_account_info(),
+                    to: pool_state_info.clone(),
+                },
+            ),
+            lamports,
+        )?;
+    }
+    pool_state_info.realloc(PoolState::LEN, true)?;
+
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    require_keys_eq!(
+        ctx.accounts.token_quote_vault.key(),
+        pool_state.token_quote_vault,
+        ErrorCode::InvalidVault
+    );
+    pool_state.migrate_from_v0(
+        ctx.accounts.quote_mint.key(),
+        *ctx.accounts.quote_mint.to_account_info().owner,
+        ctx.accounts.quote_mint.decimals,
+        ctx.accounts.observation_state.key(),
+    );
+    ctx.accounts.observation_state.load_init()?.pool_id = ctx.accounts.pool_state.key();
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "migrate pool_state:{}, version:{}",
+        ctx.accounts.pool_state.key(),
+        POOL_STATE_VERSION
+    );
+
+    Ok(())
+}
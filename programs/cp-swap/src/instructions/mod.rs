@@ -1,8 +1,12 @@
+pub mod deposit;
 pub mod initialize;
 pub mod swap_base_input;
+pub mod withdraw;
 
+pub use deposit::*;
 pub use initialize::*;
 pub use swap_base_input::*;
+pub use withdraw::*;
 
 pub mod admin;
 pub use admin::*;
@@ -10,5 +14,41 @@ pub use admin::*;
 pub mod swap_base_output;
 pub use swap_base_output::*;
 
-pub mod collect_fee;
-pub use collect_fee::*;
+pub mod flash_swap;
+pub use flash_swap::*;
+
+pub mod swap_route;
+pub use swap_route::*;
+
+pub mod collect_creator_fees;
+pub use collect_creator_fees::*;
+
+pub mod collect_protocol_fees;
+pub use collect_protocol_fees::*;
+
+pub mod get_twap;
+pub use get_twap::*;
+
+pub mod quote;
+pub use quote::*;
+
+pub mod graduate;
+pub use graduate::*;
+
+pub mod migrate_pool_state;
+pub use migrate_pool_state::*;
+
+pub mod migrate_amm_config;
+pub use migrate_amm_config::*;
+
+pub mod transfer_pool_creator;
+pub use transfer_pool_creator::*;
+
+pub mod accept_pool_creator;
+pub use accept_pool_creator::*;
+
+pub mod set_creator_fee_split;
+pub use set_creator_fee_split::*;
+
+pub mod distribute_creator_fees;
+pub use distribute_creator_fees::*;
@@ -0,0 +1,416 @@
+use crate::curve::{Fees, LaunchFee, SwapParams, TradeDirection};
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::{Mint, TokenAccount};
+
+#[derive(Accounts)]
+pub struct Quote<'info> {
+    /// The factory state to read protocol fees
+    #[account(address = pool_state.load()?.amm_config)]
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+
+    /// The program account of the pool in which the swap would be performed
+    #[account(constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked)]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The vault token account for input token
+    #[account(
+        constraint = input_vault.key() == pool_state.load()?.token_mint_vault || input_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault token account for output token
+    #[account(
+        constraint = output_vault.key() == pool_state.load()?.token_mint_vault || output_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The mint of input token
+    #[account(
+        address = input_vault.mint
+    )]
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of output token
+    #[account(
+        address = output_vault.mint
+    )]
+    pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+/// The amounts of a swap returned by `quote_base_input` and `quote_base_output`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug, Default, PartialEq)]
+pub struct SwapQuote {
+    /// Amount transferred from the user, transfer fee included
+    pub amount_in: u64,
+    /// Transfer fee of the input token, not received by the pool
+    pub input_transfer_fee: u64,
+    /// Amount transferred out of the pool, transfer fee included
+    pub amount_out: u64,
+    /// Transfer fee of the output token, not received by the user
+    pub output_transfer_fee: u64,
+    /// Trade fee charged on the input
+    pub trade_fee: u64,
+    /// Share of the trade fee going to the protocol
+    pub protocol_fee: u64,
+    /// Share of the trade fee going to the pool creator
+    pub creator_fee: u64,
+    /// Share of the trade fee going to the referrer
+    pub referral_fee: u64,
+}
+
+/// The pool, config and token accounts a swap is quoted against
+pub struct QuoteContext<'a, 'info> {
+    /// The pool state, with its pending offset applied
+    pub pool_state: &'a PoolState,
+    /// The config of the pool
+    pub amm_config: &'a AmmConfig,
+    /// The vault token account for input token
+    pub input_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    /// The vault token account for output token
+    pub output_vault: &'a InterfaceAccount<'info, TokenAccount>,
+    /// The mint of input token
+    pub input_token_mint: &'a InterfaceAccount<'info, Mint>,
+    /// The mint of output token
+    pub output_token_mint: &'a InterfaceAccount<'info, Mint>,
+    /// The timestamp the swap happens at
+    pub block_timestamp: u64,
+    /// Share of the trade fee paid to the referrer, 0 without referrer
+    pub referral_fee_rate: u64,
+}
+
+/// The direction of a swap between the vaults and the pool amounts it trades against
+fn trade_amounts(
+    pool_state: &PoolState,
+    input_vault: &InterfaceAccount<TokenAccount>,
+    output_vault: &InterfaceAccount<TokenAccount>,
+) -> Result<(TradeDirection, u64, u64)> {
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        if input_vault.key() == pool_state.token_mint_vault
+            && output_vault.key() == pool_state.token_quote_vault
+        {
+            let (total_input_token_amount, total_output_token_amount) =
+                pool_state.vault_amount_without_fee(input_vault.amount, output_vault.amount);
+            (
+                TradeDirection::ZeroForOne,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else if input_vault.key() == pool_state.token_quote_vault
+            && output_vault.key() == pool_state.token_mint_vault
+        {
+            let (total_output_token_amount, total_input_token_amount) =
+                pool_state.vault_amount_without_fee(output_vault.amount, input_vault.amount);
+            (
+                TradeDirection::OneForZero,
+                total_input_token_amount,
+                total_output_token_amount,
+            )
+        } else {
+            return err!(ErrorCode::InvalidVault);
+        };
+    let quote_vault_amount = match trade_direction {
+        TradeDirection::ZeroForOne => output_vault.amount,
+        TradeDirection::OneForZero => input_vault.amount,
+    };
+    if pool_state.graduation_reached(quote_vault_amount) {
+        return err!(ErrorCode::GraduationPending);
+    }
+    Ok((
+        trade_direction,
+        total_input_token_amount,
+        total_output_token_amount,
+    ))
+}
+
+/// The trade fee rate and launch fee a swap pays at `block_timestamp`
+fn trade_fee_rate(
+    pool_state: &PoolState,
+    amm_config: &AmmConfig,
+    block_timestamp: u64,
+) -> (u64, Option<LaunchFee>) {
+    // During the launch window the decaying launch fee replaces the config trade fee
+    let launch_fee = pool_state
+        .launch_fee_rate_at(block_timestamp)
+        .map(|fee_rate| LaunchFee {
+            fee_rate,
+            to_protocol: pool_state.launch_fee_to_protocol(),
+        });
+
+    // Recent volatility adds a variable fee on top of the config trade fee
+    let trade_fee_rate = Fees::dynamic_trade_fee_rate(
+        amm_config.trade_fee_rate_at(block_timestamp),
+        amm_config.dynamic_fee(
+            block_timestamp,
+            pool_state.volatility_at(
+                block_timestamp,
+                amm_config
+                    .dynamic_fee_param_at(block_timestamp)
+                    .volatility_decay_period,
+            ),
+        ),
+    );
+    (trade_fee_rate, launch_fee)
+}
+
+/// Calculates a swap of `amount_in`, shared by `swap_base_input` and `quote_base_input`
+pub fn quote_swap_base_input(
+    quote_context: &QuoteContext,
+    amount_in: u64,
+    price_limit_x32: Option<u128>,
+) -> Result<(TradeDirection, SwapQuote)> {
+    let QuoteContext {
+        pool_state,
+        amm_config,
+        input_vault,
+        output_vault,
+        input_token_mint,
+        output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    } = *quote_context;
+    let input_transfer_fee = get_transfer_fee(&input_token_mint.to_account_info(), amount_in)?;
+    // Take transfer fees into account for actual amount transferred in
+    let actual_amount_in = amount_in.saturating_sub(input_transfer_fee);
+    require_gt!(actual_amount_in, 0);
+
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        trade_amounts(pool_state, input_vault, output_vault)?;
+    let (trade_fee_rate, launch_fee) = trade_fee_rate(pool_state, amm_config, block_timestamp);
+
+    let curve_calculator = pool_state.curve_calculator();
+    let params = SwapParams {
+        swap_source_amount: u128::from(total_input_token_amount),
+        swap_destination_amount: u128::from(total_output_token_amount),
+        trade_fee_rate,
+        protocol_fee_rate: amm_config.protocol_fee_rate,
+        referral_fee_rate,
+        launch_fee,
+        trade_direction,
+    };
+    let swap = |source_amount| curve_calculator.swap_base_input(source_amount, &params);
+    let result = match price_limit_x32 {
+        Some(price_limit_x32) => curve_calculator
+            .swap_within_price_limit(
+                u128::from(actual_amount_in),
+                curve_calculator
+                    .source_amounts_to_price_x32(&params, price_limit_x32)
+                    .into_iter()
+                    .flatten(),
+                trade_direction,
+                price_limit_x32,
+                swap,
+            )
+            .ok_or(ErrorCode::PriceLimitReached)?,
+        None => swap(u128::from(actual_amount_in)).ok_or(ErrorCode::ZeroTradingTokens)?,
+    };
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "source_amount_swapped:{}, destination_amount_swapped:{}",
+        result.source_amount_swapped,
+        result.destination_amount_swapped
+    );
+    curve_calculator.validate_swap(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        &result,
+        trade_direction,
+    )?;
+
+    // A price limit may fill only part of the input, the rest stays with the user
+    let filled_amount_in = u64::try_from(result.source_amount_swapped).unwrap();
+    require_gte!(actual_amount_in, filled_amount_in);
+    let (amount_in, input_transfer_fee) = if filled_amount_in == actual_amount_in {
+        (amount_in, input_transfer_fee)
+    } else {
+        let transfer_fee =
+            get_transfer_inverse_fee(&input_token_mint.to_account_info(), filled_amount_in)?;
+        (
+            filled_amount_in.checked_add(transfer_fee).unwrap(),
+            transfer_fee,
+        )
+    };
+
+    let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+    let output_transfer_fee = get_transfer_fee(&output_token_mint.to_account_info(), amount_out)?;
+    let amount_received = amount_out.checked_sub(output_transfer_fee).unwrap();
+    require_gt!(amount_received, 0);
+
+    Ok((
+        trade_direction,
+        SwapQuote {
+            amount_in,
+            input_transfer_fee,
+            amount_out,
+            output_transfer_fee,
+            trade_fee: u64::try_from(result.trade_fee).unwrap(),
+            protocol_fee: u64::try_from(result.protocol_fee).unwrap(),
+            creator_fee: u64::try_from(result.creator_fee).unwrap(),
+            referral_fee: u64::try_from(result.referral_fee).unwrap(),
+        },
+    ))
+}
+
+/// Calculates a swap receiving `amount_out_less_fee`, shared by `swap_base_output` and
+/// `quote_base_output`
+pub fn quote_swap_base_output(
+    quote_context: &QuoteContext,
+    amount_out_less_fee: u64,
+    price_limit_x32: Option<u128>,
+) -> Result<(TradeDirection, SwapQuote)> {
+    let QuoteContext {
+        pool_state,
+        amm_config,
+        input_vault,
+        output_vault,
+        input_token_mint,
+        output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    } = *quote_context;
+    let out_transfer_fee =
+        get_transfer_inverse_fee(&output_token_mint.to_account_info(), amount_out_less_fee)?;
+    let actual_amount_out = amount_out_less_fee.checked_add(out_transfer_fee).unwrap();
+
+    let (trade_direction, total_input_token_amount, total_output_token_amount) =
+        trade_amounts(pool_state, input_vault, output_vault)?;
+    let (trade_fee_rate, launch_fee) = trade_fee_rate(pool_state, amm_config, block_timestamp);
+
+    let curve_calculator = pool_state.curve_calculator();
+    let params = SwapParams {
+        swap_source_amount: u128::from(total_input_token_amount),
+        swap_destination_amount: u128::from(total_output_token_amount),
+        trade_fee_rate,
+        protocol_fee_rate: amm_config.protocol_fee_rate,
+        referral_fee_rate,
+        launch_fee,
+        trade_direction,
+    };
+    let swap = |destination_amount| curve_calculator.swap_base_output(destination_amount, &params);
+    let result = match price_limit_x32 {
+        Some(price_limit_x32) => curve_calculator
+            .swap_within_price_limit(
+                u128::from(actual_amount_out),
+                curve_calculator.destination_amount_to_price_x32(&params, price_limit_x32),
+                trade_direction,
+                price_limit_x32,
+                swap,
+            )
+            .ok_or(ErrorCode::PriceLimitReached)?,
+        None => swap(u128::from(actual_amount_out)).ok_or(ErrorCode::ZeroTradingTokens)?,
+    };
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "source_amount_swapped:{}, destination_amount_swapped:{}",
+        result.source_amount_swapped,
+        result.destination_amount_swapped
+    );
+    curve_calculator.validate_swap(
+        u128::from(total_input_token_amount),
+        u128::from(total_output_token_amount),
+        &result,
+        trade_direction,
+    )?;
+
+    // A price limit may fill only part of the output
+    let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
+    require_gte!(actual_amount_out, amount_out);
+    let output_transfer_fee = if amount_out == actual_amount_out {
+        out_transfer_fee
+    } else {
+        get_transfer_fee(&output_token_mint.to_account_info(), amount_out)?
+    };
+
+    // Re-calculate the source amount swapped based on what the curve says
+    let source_amount_swapped = u64::try_from(result.source_amount_swapped).unwrap();
+    require_gt!(source_amount_swapped, 0);
+    let input_transfer_fee =
+        get_transfer_inverse_fee(&input_token_mint.to_account_info(), source_amount_swapped)?;
+
+    Ok((
+        trade_direction,
+        SwapQuote {
+            amount_in: source_amount_swapped
+                .checked_add(input_transfer_fee)
+                .unwrap(),
+            input_transfer_fee,
+            amount_out,
+            output_transfer_fee,
+            trade_fee: u64::try_from(result.trade_fee).unwrap(),
+            protocol_fee: u64::try_from(result.protocol_fee).unwrap(),
+            creator_fee: u64::try_from(result.creator_fee).unwrap(),
+            referral_fee: u64::try_from(result.referral_fee).unwrap(),
+        },
+    ))
+}
+
+/// Quotes against a copy of the pool state with its pending offset applied, as a swap would
+fn quoted_pool_state(ctx: &Context<Quote>, block_timestamp: u64) -> Result<PoolState> {
+    let mut pool_state = *ctx.accounts.pool_state.load()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.apply_pending_off_set(block_timestamp);
+    Ok(pool_state)
+}
+
+pub fn quote_base_input(
+    ctx: Context<Quote>,
+    amount_in: u64,
+    with_referrer: bool,
+    price_limit_x32: Option<u128>,
+) -> Result<SwapQuote> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_state = quoted_pool_state(&ctx, block_timestamp)?;
+    let referral_fee_rate = if with_referrer {
+        ctx.accounts.amm_config.referral_fee_rate
+    } else {
+        0
+    };
+    let quote_context = QuoteContext {
+        pool_state: &pool_state,
+        amm_config: &ctx.accounts.amm_config,
+        input_vault: &ctx.accounts.input_vault,
+        output_vault: &ctx.accounts.output_vault,
+        input_token_mint: &ctx.accounts.input_token_mint,
+        output_token_mint: &ctx.accounts.output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    };
+    let (_, swap_quote) = quote_swap_base_input(&quote_context, amount_in, price_limit_x32)?;
+    Ok(swap_quote)
+}
+
+pub fn quote_base_output(
+    ctx: Context<Quote>,
+    amount_out: u64,
+    with_referrer: bool,
+    price_limit_x32: Option<u128>,
+) -> Result<SwapQuote> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let pool_state = quoted_pool_state(&ctx, block_timestamp)?;
+    let referral_fee_rate = if with_referrer {
+        ctx.accounts.amm_config.referral_fee_rate
+    } else {
+        0
+    };
+    let quote_context = QuoteContext {
+        pool_state: &pool_state,
+        amm_config: &ctx.accounts.amm_config,
+        input_vault: &ctx.accounts.input_vault,
+        output_vault: &ctx.accounts.output_vault,
+        input_token_mint: &ctx.accounts.input_token_mint,
+        output_token_mint: &ctx.accounts.output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    };
+    let (_, swap_quote) = quote_swap_base_output(&quote_context, amount_out, price_limit_x32)?;
+    Ok(swap_quote)
+}
@@ -0,0 +1,61 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetCreatorFeeSplit<'info> {
+    /// Only the pool creator can split its fees
+    #[account(
+        mut,
+        address = pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner
+    )]
+    pub pool_creator: Signer<'info>,
+
+    /// The pool whose creator fees are split
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// The fee split of the pool, created on first use
+    #[account(
+        init_if_needed,
+        seeds = [
+            FEE_SPLIT_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+        ],
+        bump,
+        payer = pool_creator,
+        space = FeeSplitState::LEN
+    )]
+    pub fee_split_state: Box<Account<'info, FeeSplitState>>,
+
+    /// To create the fee split account
+    pub system_program: Program<'info, System>,
+}
+
+pub fn set_creator_fee_split(
+    ctx: Context<SetCreatorFeeSplit>,
+    recipients: Vec<FeeSplitRecipient>,
+) -> Result<()> {
+    let fee_split_state = &mut ctx.accounts.fee_split_state;
+    let old_recipients = fee_split_state.recipients().to_vec();
+    fee_split_state.bump = ctx.bumps.fee_split_state;
+    fee_split_state.pool_id = ctx.accounts.pool_state.key();
+    fee_split_state.set_recipients(&recipients)?;
+
+    // From now on creator fees are only paid out through the split, until it is cleared
+    ctx.accounts.pool_state.load_mut()?.creator_fee_split = !recipients.is_empty();
+
+    emit_cpi!(CreatorFeeSplitUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_recipients,
+        new_recipients: recipients,
+        signer: ctx.accounts.pool_creator.key(),
+        slot: Clock::get()?.slot,
+    });
+
+    Ok(())
+}
@@ -1,16 +1,17 @@
-use crate::curve::calculator::CurveCalculator;
+use super::quote::{quote_swap_base_input, QuoteContext};
 use crate::curve::TradeDirection;
 use crate::error::ErrorCode;
 use crate::states::*;
 use crate::utils::token::*;
-use crate::USDC;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
+use anchor_spl::token::spl_token::native_mint;
 use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 #[event_cpi]
 #[derive(Accounts)]
 pub struct Swap<'info> {
     /// The user performing the swap
+    #[account(mut)]
     pub payer: Signer<'info>,
 
     /// CHECK: pool vault and lp mint authority
@@ -27,28 +28,31 @@ pub struct Swap<'info> {
     pub amm_config: Box<Account<'info, AmmConfig>>,
 
     /// The program account of the pool in which the swap will be performed
-    #[account(mut)]
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
     pub pool_state: AccountLoader<'info, PoolState>,
 
-    /// The user token account for input token
+    /// The user token account for input token, left out to pay native SOL
     #[account(mut)]
-    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub input_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
-    /// The user token account for output token
+    /// The user token account for output token, left out to receive native SOL
     #[account(mut)]
-    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub output_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
 
     /// The vault token account for input token
     #[account(
         mut,
-        constraint = input_vault.key() == pool_state.load()?.token_mint_vault || input_vault.key() == pool_state.load()?.token_usdc_vault
+        constraint = input_vault.key() == pool_state.load()?.token_mint_vault || input_vault.key() == pool_state.load()?.token_quote_vault
     )]
     pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// The vault token account for output token
     #[account(
         mut,
-        constraint = output_vault.key() == pool_state.load()?.token_mint_vault || output_vault.key() == pool_state.load()?.token_usdc_vault
+        constraint = output_vault.key() == pool_state.load()?.token_mint_vault || output_vault.key() == pool_state.load()?.token_quote_vault
     )]
     pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
@@ -69,109 +73,333 @@ pub struct Swap<'info> {
         address = output_vault.mint
     )]
     pub output_token_mint: Box<InterfaceAccount<'info, Mint>>,
-    // The program account for the most recent oracle observation
-    // #[account(mut, address = pool_state.load()?.observation_key)]
-    // pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The program account for the most recent oracle observation
+    #[account(mut, address = pool_state.load()?.observation_key)]
+    pub observation_state: AccountLoader<'info, ObservationState>,
+
+    /// The purchases of the payer, required for buys while the pool caps buys per wallet
+    #[account(
+        init_if_needed,
+        seeds = [
+            USER_PURCHASE_SEED.as_bytes(),
+            pool_state.key().as_ref(),
+            payer.key().as_ref(),
+        ],
+        bump,
+        payer = payer,
+        space = UserPurchaseState::LEN
+    )]
+    pub user_purchase_state: Option<Box<Account<'info, UserPurchaseState>>>,
+
+    /// To create the user purchase account or the native SOL account
+    pub system_program: Option<Program<'info, System>>,
+
+    /// The referrer token account for input token, paid a share of the trade fee. It can't be
+    /// owned by the payer, who would otherwise take back the fee of their own swap
+    #[account(
+        mut,
+        token::mint = input_token_mint,
+        constraint = referral_token_account.owner != payer.key() @ ErrorCode::InvalidInput,
+    )]
+    pub referral_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// CHECK: temporary wrapped SOL account of the payer, created and closed within a native SOL swap
+    #[account(
+        mut,
+        seeds = [
+            crate::NATIVE_SOL_SEED.as_bytes(),
+            payer.key().as_ref(),
+        ],
+        bump,
+    )]
+    pub native_sol_account: Option<UncheckedAccount<'info>>,
+}
+
+/// Adds a buy to the payer's purchases while the pool caps buys per wallet
+pub fn record_purchase(
+    pool_state: &PoolState,
+    user_purchase_state: &mut Option<Box<Account<UserPurchaseState>>>,
+    pool_id: Pubkey,
+    payer: Pubkey,
+    block_timestamp: u64,
+    quote_amount_in: u64,
+    mint_amount_out: u64,
+) -> Result<()> {
+    if !pool_state.purchase_cap_active(block_timestamp) {
+        return Ok(());
+    }
+    let user_purchase_state = user_purchase_state
+        .as_mut()
+        .ok_or(ErrorCode::MissingUserPurchaseState)?;
+    if user_purchase_state.owner == Pubkey::default() {
+        user_purchase_state.pool_id = pool_id;
+        user_purchase_state.owner = payer;
+    }
+    user_purchase_state.add_purchase(
+        quote_amount_in,
+        mint_amount_out,
+        pool_state.purchase_cap_quote_amount_in,
+        pool_state.purchase_cap_mint_amount_out,
+    )
+}
+
+/// Records the price in effect since the last observation, before the swap moves it. Returns the
+/// token 0 price.
+pub fn update_observation(
+    pool_state: &PoolState,
+    observation_state: &AccountLoader<ObservationState>,
+    trade_direction: TradeDirection,
+    input_vault_amount: u64,
+    output_vault_amount: u64,
+) -> Result<u128> {
+    let (token_0_price_x32, token_1_price_x32, _) = match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.token_price_x32(input_vault_amount, output_vault_amount)
+        }
+        TradeDirection::OneForZero => {
+            pool_state.token_price_x32(output_vault_amount, input_vault_amount)
+        }
+    };
+    observation_state.load_mut()?.update(
+        oracle::block_timestamp(),
+        token_0_price_x32,
+        token_1_price_x32,
+    );
+    Ok(token_0_price_x32)
+}
+
+/// Fails a swap landing after its deadline
+pub fn check_deadline(deadline: Option<u64>, block_timestamp: u64) -> Result<()> {
+    if let Some(deadline) = deadline {
+        require_gte!(deadline, block_timestamp, ErrorCode::DeadlineExceeded);
+    }
+    Ok(())
+}
+
+/// Pays the referral fee out of the input vault, the input transfer has to come first
+pub fn pay_referral_fee(accounts: &Swap, pool_state: &PoolState, referral_fee: u64) -> Result<()> {
+    let referral_token_account = match &accounts.referral_token_account {
+        Some(referral_token_account) if referral_fee > 0 => referral_token_account,
+        _ => return Ok(()),
+    };
+    transfer_from_pool_vault_to_user(
+        accounts.authority.to_account_info(),
+        accounts.input_vault.to_account_info(),
+        referral_token_account.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
+        referral_fee,
+        accounts.input_token_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )
+}
+
+/// Creates the temporary wrapped SOL account of a native SOL swap, owned by the payer
+fn create_native_sol_account<'info>(
+    accounts: &Swap<'info>,
+    mint: &InterfaceAccount<'info, Mint>,
+    token_program: &Interface<'info, TokenInterface>,
+    native_sol_bump: Option<u8>,
+) -> Result<(AccountInfo<'info>, AccountInfo<'info>)> {
+    require_keys_eq!(mint.key(), native_mint::ID, ErrorCode::NotSupportMint);
+    let (native_sol_account, system_program, native_sol_bump) = match (
+        &accounts.native_sol_account,
+        &accounts.system_program,
+        native_sol_bump,
+    ) {
+        (Some(native_sol_account), Some(system_program), Some(native_sol_bump)) => (
+            native_sol_account.to_account_info(),
+            system_program.to_account_info(),
+            native_sol_bump,
+        ),
+        _ => return err!(ErrorCode::MissingNativeSolAccount),
+    };
+    create_token_account(
+        &accounts.payer.to_account_info(),
+        &accounts.payer.to_account_info(),
+        &native_sol_account,
+        &mint.to_account_info(),
+        &system_program,
+        &token_program.to_account_info(),
+        &[&[
+            crate::NATIVE_SOL_SEED.as_bytes(),
+            accounts.payer.key().as_ref(),
+            &[native_sol_bump],
+        ]],
+    )?;
+    Ok((native_sol_account, system_program))
+}
+
+/// Pays the input into the input vault, wrapping native SOL when there is no input token account
+pub fn transfer_swap_input(
+    accounts: &Swap,
+    amount: u64,
+    native_sol_bump: Option<u8>,
+) -> Result<()> {
+    if let Some(input_token_account) = &accounts.input_token_account {
+        return transfer_from_user_to_pool_vault(
+            accounts.payer.to_account_info(),
+            input_token_account.to_account_info(),
+            accounts.input_vault.to_account_info(),
+            accounts.input_token_mint.to_account_info(),
+            accounts.input_token_program.to_account_info(),
+            amount,
+            accounts.input_token_mint.decimals,
+        );
+    }
+    let (native_sol_account, system_program) = create_native_sol_account(
+        accounts,
+        &accounts.input_token_mint,
+        &accounts.input_token_program,
+        native_sol_bump,
+    )?;
+    wrap_native_sol(
+        &accounts.payer.to_account_info(),
+        &native_sol_account,
+        &system_program,
+        &accounts.input_token_program.to_account_info(),
+        amount,
+    )?;
+    transfer_from_user_to_pool_vault(
+        accounts.payer.to_account_info(),
+        native_sol_account.clone(),
+        accounts.input_vault.to_account_info(),
+        accounts.input_token_mint.to_account_info(),
+        accounts.input_token_program.to_account_info(),
+        amount,
+        accounts.input_token_mint.decimals,
+    )?;
+    close_token_account(
+        &accounts.payer.to_account_info(),
+        &native_sol_account,
+        &accounts.payer.to_account_info(),
+        &accounts.input_token_program.to_account_info(),
+        &[],
+    )
 }
 
-pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u64) -> Result<()> {
+/// Pays the output out of the output vault, unwrapping native SOL when there is no output token account
+pub fn transfer_swap_output(
+    accounts: &Swap,
+    pool_state: &PoolState,
+    amount: u64,
+    native_sol_bump: Option<u8>,
+) -> Result<()> {
+    let auth_bump = [pool_state.auth_bump];
+    let signer_seeds: &[&[&[u8]]] = &[&[crate::AUTH_SEED.as_bytes(), &auth_bump]];
+    if let Some(output_token_account) = &accounts.output_token_account {
+        return transfer_from_pool_vault_to_user(
+            accounts.authority.to_account_info(),
+            accounts.output_vault.to_account_info(),
+            output_token_account.to_account_info(),
+            accounts.output_token_mint.to_account_info(),
+            accounts.output_token_program.to_account_info(),
+            amount,
+            accounts.output_token_mint.decimals,
+            signer_seeds,
+        );
+    }
+    let (native_sol_account, _) = create_native_sol_account(
+        accounts,
+        &accounts.output_token_mint,
+        &accounts.output_token_program,
+        native_sol_bump,
+    )?;
+    transfer_from_pool_vault_to_user(
+        accounts.authority.to_account_info(),
+        accounts.output_vault.to_account_info(),
+        native_sol_account.clone(),
+        accounts.output_token_mint.to_account_info(),
+        accounts.output_token_program.to_account_info(),
+        amount,
+        accounts.output_token_mint.decimals,
+        signer_seeds,
+    )?;
+    close_token_account(
+        &accounts.payer.to_account_info(),
+        &native_sol_account,
+        &accounts.payer.to_account_info(),
+        &accounts.output_token_program.to_account_info(),
+        &[],
+    )
+}
+
+pub fn swap_base_input(
+    ctx: Context<Swap>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+    deadline: Option<u64>,
+    price_limit_x32: Option<u128>,
+) -> Result<()> {
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    check_deadline(deadline, block_timestamp)?;
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
     {
         return err!(ErrorCode::NotApproved);
     }
+    pool_state.apply_pending_off_set(block_timestamp);
 
-    let transfer_fee =
-        get_transfer_fee(&ctx.accounts.input_token_mint.to_account_info(), amount_in)?;
-    // Take transfer fees into account for actual amount transferred in
-    let actual_amount_in = amount_in.saturating_sub(transfer_fee);
-    require_gt!(actual_amount_in, 0);
+    // Only a swap naming a referrer pays the referral fee
+    let referral_fee_rate = if ctx.accounts.referral_token_account.is_some() {
+        ctx.accounts.amm_config.referral_fee_rate
+    } else {
+        0
+    };
 
     // Calculate the trade amounts
-    let (trade_direction, total_input_token_amount, total_output_token_amount, liquidity_before) =
-        if ctx.accounts.input_vault.key() == pool_state.token_mint_vault
-            && ctx.accounts.output_vault.key() == pool_state.token_usdc_vault
-        {
-            let (total_input_token_amount, total_output_token_amount) = pool_state
-                .vault_amount_without_fee(
-                    ctx.accounts.input_vault.amount,
-                    ctx.accounts.output_vault.amount,
-                );
-
-            (
-                TradeDirection::ZeroForOne,
-                total_input_token_amount,
-                total_output_token_amount,
-                ctx.accounts.output_vault.amount,
-            )
-        } else if ctx.accounts.input_vault.key() == pool_state.token_usdc_vault
-            && ctx.accounts.output_vault.key() == pool_state.token_mint_vault
-        {
-            let (total_output_token_amount, total_input_token_amount) = pool_state
-                .vault_amount_without_fee(
-                    ctx.accounts.output_vault.amount,
-                    ctx.accounts.input_vault.amount,
-                );
-
-            (
-                TradeDirection::OneForZero,
-                total_input_token_amount,
-                total_output_token_amount,
-                ctx.accounts.input_vault.amount,
-            )
-        } else {
-            return err!(ErrorCode::InvalidVault);
-        };
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .unwrap();
-
-    let result = CurveCalculator::swap_base_input(
-        u128::from(actual_amount_in),
-        u128::from(total_input_token_amount),
-        u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-        ctx.accounts.amm_config.protocol_fee_rate,
-    )
-    .ok_or(ErrorCode::ZeroTradingTokens)?;
-
-    let constant_after = u128::from(result.new_swap_source_amount)
-        .checked_mul(u128::from(result.new_swap_destination_amount))
-        .unwrap();
-    #[cfg(feature = "enable-log")]
-    msg!(
-        "source_amount_swapped:{}, destination_amount_swapped:{},constant_before:{},constant_after:{}",
-        result.source_amount_swapped,
-        result.destination_amount_swapped,
-        constant_before,
-        constant_after
-    );
-    require_gte!(constant_after, constant_before);
-    require_eq!(
-        u64::try_from(result.source_amount_swapped).unwrap(),
-        actual_amount_in
+    let quote_context = QuoteContext {
+        pool_state,
+        amm_config: &ctx.accounts.amm_config,
+        input_vault: &ctx.accounts.input_vault,
+        output_vault: &ctx.accounts.output_vault,
+        input_token_mint: &ctx.accounts.input_token_mint,
+        output_token_mint: &ctx.accounts.output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    };
+    let (trade_direction, swap_quote) =
+        quote_swap_base_input(&quote_context, amount_in, price_limit_x32)?;
+    let input_transfer_amount = swap_quote.amount_in;
+    let output_transfer_amount = swap_quote.amount_out;
+    // A price limit may fill only part of the input, the slippage bound still holds as given
+    require_gte!(
+        output_transfer_amount
+            .checked_sub(swap_quote.output_transfer_fee)
+            .unwrap(),
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
     );
-    let (input_transfer_amount, _) = (amount_in, transfer_fee);
-    let (output_transfer_amount, _) = {
-        let amount_out = u64::try_from(result.destination_amount_swapped).unwrap();
-        let transfer_fee = get_transfer_fee(
-            &ctx.accounts.output_token_mint.to_account_info(),
-            amount_out,
-        )?;
-        let amount_received = amount_out.checked_sub(transfer_fee).unwrap();
-        require_gt!(amount_received, 0);
-        require_gte!(
-            amount_received,
-            minimum_amount_out,
-            ErrorCode::ExceededSlippage
-        );
-        (amount_out, transfer_fee)
+    let liquidity_before = match trade_direction {
+        TradeDirection::ZeroForOne => ctx.accounts.output_vault.amount,
+        TradeDirection::OneForZero => ctx.accounts.input_vault.amount,
     };
 
-    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
-    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    let token_0_price_x32 = update_observation(
+        pool_state,
+        &ctx.accounts.observation_state,
+        trade_direction,
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+    )?;
+
+    // Buys count towards the payer's purchase cap
+    if trade_direction == TradeDirection::OneForZero {
+        record_purchase(
+            pool_state,
+            &mut ctx.accounts.user_purchase_state,
+            ctx.accounts.pool_state.key(),
+            ctx.accounts.payer.key(),
+            block_timestamp,
+            input_transfer_amount,
+            output_transfer_amount,
+        )?;
+    }
+
+    let protocol_fee = swap_quote.protocol_fee;
+    let creator_fee = swap_quote.creator_fee;
 
     match trade_direction {
         TradeDirection::ZeroForOne => {
@@ -185,50 +413,44 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
                 .unwrap();
         }
         TradeDirection::OneForZero => {
-            pool_state.protocol_fees_token_usdc = pool_state
-                .protocol_fees_token_usdc
+            pool_state.protocol_fees_token_quote = pool_state
+                .protocol_fees_token_quote
                 .checked_add(protocol_fee)
                 .unwrap();
-            pool_state.creator_fees_token_usdc = pool_state
-                .creator_fees_token_usdc
+            pool_state.creator_fees_token_quote = pool_state
+                .creator_fees_token_quote
                 .checked_add(creator_fee)
                 .unwrap();
         }
     };
 
-    transfer_from_user_to_pool_vault(
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.input_vault.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+    transfer_swap_input(
+        ctx.accounts,
         input_transfer_amount,
-        ctx.accounts.input_token_mint.decimals,
+        ctx.bumps.native_sol_account,
     )?;
 
-    transfer_from_pool_vault_to_user(
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.output_vault.to_account_info(),
-        ctx.accounts.output_token_account.to_account_info(),
-        ctx.accounts.output_token_mint.to_account_info(),
-        ctx.accounts.output_token_program.to_account_info(),
+    transfer_swap_output(
+        ctx.accounts,
+        pool_state,
         output_transfer_amount,
-        ctx.accounts.output_token_mint.decimals,
-        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.bumps.native_sol_account,
     )?;
 
+    pay_referral_fee(ctx.accounts, pool_state, swap_quote.referral_fee)?;
+
     ctx.accounts.input_vault.reload()?;
     ctx.accounts.output_vault.reload()?;
 
     let (token_0_price_x64, _, liquidity_after) = if ctx.accounts.input_vault.key()
         == pool_state.token_mint_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_usdc_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_quote_vault
     {
         pool_state.token_price_x32(
             ctx.accounts.input_vault.amount,
             ctx.accounts.output_vault.amount,
         )
-    } else if ctx.accounts.input_vault.key() == pool_state.token_usdc_vault
+    } else if ctx.accounts.input_vault.key() == pool_state.token_quote_vault
         && ctx.accounts.output_vault.key() == pool_state.token_mint_vault
     {
         pool_state.token_price_x32(
@@ -238,6 +460,16 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
     } else {
         return err!(ErrorCode::InvalidVault);
     };
+    pool_state.update_volatility(
+        token_0_price_x32,
+        token_0_price_x64,
+        block_timestamp,
+        ctx.accounts
+            .amm_config
+            .dynamic_fee_param_at(block_timestamp)
+            .volatility_decay_period,
+    );
+
     emit_cpi!(SwapPriceEvent {
         timestamp: oracle::block_timestamp(),
         mint: pool_state.mint,
@@ -246,15 +478,15 @@ pub fn swap_base_input(ctx: Context<Swap>, amount_in: u64, minimum_amount_out: u
         liquidity_after,
         input_amount: u64::try_from(input_transfer_amount).unwrap(),
         output_amount: u64::try_from(output_transfer_amount).unwrap(),
-        buy: ctx.accounts.input_token_mint.key() == USDC,
-        user: ctx.accounts.payer.key()
+        buy: ctx.accounts.input_token_mint.key() == pool_state.quote_mint,
+        user: ctx.accounts.payer.key(),
+        referrer: ctx
+            .accounts
+            .referral_token_account
+            .as_ref()
+            .map(|referral_token_account| referral_token_account.owner),
     });
 
-    // ctx.accounts.observation_state.load_mut()?.update(
-    //     oracle::block_timestamp(),
-    //     token_0_price_x64,
-    //     token_1_price_x64,
-    // );
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
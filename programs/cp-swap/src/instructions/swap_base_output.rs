@@ -1,8 +1,11 @@
-use super::swap_base_input::Swap;
-use crate::curve::{calculator::CurveCalculator, TradeDirection};
+use super::quote::{quote_swap_base_output, QuoteContext};
+use super::swap_base_input::{
+    check_deadline, pay_referral_fee, record_purchase, transfer_swap_input, transfer_swap_output,
+    update_observation, Swap,
+};
+use crate::curve::TradeDirection;
 use crate::error::ErrorCode;
-use crate::utils::token::*;
-use crate::{states::*, USDC};
+use crate::states::*;
 use anchor_lang::prelude::*;
 use anchor_lang::solana_program;
 
@@ -10,106 +13,74 @@ pub fn swap_base_output(
     ctx: Context<Swap>,
     max_amount_in: u64,
     amount_out_less_fee: u64,
+    deadline: Option<u64>,
+    price_limit_x32: Option<u128>,
 ) -> Result<()> {
     let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    check_deadline(deadline, block_timestamp)?;
     let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
     if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
         || block_timestamp < pool_state.open_time
     {
         return err!(ErrorCode::NotApproved);
     }
-    let out_transfer_fee = get_transfer_inverse_fee(
-        &ctx.accounts.output_token_mint.to_account_info(),
-        amount_out_less_fee,
-    )?;
-    let actual_amount_out = amount_out_less_fee.checked_add(out_transfer_fee).unwrap();
+    pool_state.apply_pending_off_set(block_timestamp);
+    // Only a swap naming a referrer pays the referral fee
+    let referral_fee_rate = if ctx.accounts.referral_token_account.is_some() {
+        ctx.accounts.amm_config.referral_fee_rate
+    } else {
+        0
+    };
 
     // Calculate the trade amounts
-    let (trade_direction, total_input_token_amount, total_output_token_amount, liquidity_before) =
-        if ctx.accounts.input_vault.key() == pool_state.token_mint_vault
-            && ctx.accounts.output_vault.key() == pool_state.token_usdc_vault
-        {
-            let (total_input_token_amount, total_output_token_amount) = pool_state
-                .vault_amount_without_fee(
-                    ctx.accounts.input_vault.amount,
-                    ctx.accounts.output_vault.amount,
-                );
-
-            (
-                TradeDirection::ZeroForOne,
-                total_input_token_amount,
-                total_output_token_amount,
-                ctx.accounts.output_vault.amount,
-            )
-        } else if ctx.accounts.input_vault.key() == pool_state.token_usdc_vault
-            && ctx.accounts.output_vault.key() == pool_state.token_mint_vault
-        {
-            let (total_output_token_amount, total_input_token_amount) = pool_state
-                .vault_amount_without_fee(
-                    ctx.accounts.output_vault.amount,
-                    ctx.accounts.input_vault.amount,
-                );
-
-            (
-                TradeDirection::OneForZero,
-                total_input_token_amount,
-                total_output_token_amount,
-                ctx.accounts.input_vault.amount,
-            )
-        } else {
-            return err!(ErrorCode::InvalidVault);
-        };
-    let constant_before = u128::from(total_input_token_amount)
-        .checked_mul(u128::from(total_output_token_amount))
-        .unwrap();
-
-    let result = CurveCalculator::swap_base_output(
-        u128::from(actual_amount_out),
-        u128::from(total_input_token_amount),
-        u128::from(total_output_token_amount),
-        ctx.accounts.amm_config.trade_fee_rate,
-        ctx.accounts.amm_config.protocol_fee_rate,
-    )
-    .ok_or(ErrorCode::ZeroTradingTokens)?;
-
-    let constant_after = u128::from(result.new_swap_source_amount)
-        .checked_mul(u128::from(result.new_swap_destination_amount))
-        .unwrap();
-
-    #[cfg(feature = "enable-log")]
-    msg!(
-        "source_amount_swapped:{}, destination_amount_swapped:{},constant_before:{},constant_after:{}",
-        result.source_amount_swapped,
-        result.destination_amount_swapped,
-        constant_before,
-        constant_after
+    let quote_context = QuoteContext {
+        pool_state,
+        amm_config: &ctx.accounts.amm_config,
+        input_vault: &ctx.accounts.input_vault,
+        output_vault: &ctx.accounts.output_vault,
+        input_token_mint: &ctx.accounts.input_token_mint,
+        output_token_mint: &ctx.accounts.output_token_mint,
+        block_timestamp,
+        referral_fee_rate,
+    };
+    let (trade_direction, swap_quote) =
+        quote_swap_base_output(&quote_context, amount_out_less_fee, price_limit_x32)?;
+    let input_transfer_amount = swap_quote.amount_in;
+    let output_transfer_amount = swap_quote.amount_out;
+    // A price limit may fill only part of the output, the slippage bound still holds as given
+    require_gte!(
+        max_amount_in,
+        input_transfer_amount,
+        ErrorCode::ExceededSlippage
     );
-    require_gte!(constant_after, constant_before);
+    let liquidity_before = match trade_direction {
+        TradeDirection::ZeroForOne => ctx.accounts.output_vault.amount,
+        TradeDirection::OneForZero => ctx.accounts.input_vault.amount,
+    };
 
-    // Re-calculate the source amount swapped based on what the curve says
-    let (input_transfer_amount, _) = {
-        let source_amount_swapped = u64::try_from(result.source_amount_swapped).unwrap();
-        require_gt!(source_amount_swapped, 0);
-        let transfer_fee = get_transfer_inverse_fee(
-            &ctx.accounts.input_token_mint.to_account_info(),
-            source_amount_swapped,
-        )?;
-        let input_transfer_amount = source_amount_swapped.checked_add(transfer_fee).unwrap();
-        require_gte!(
-            max_amount_in,
+    let token_0_price_x32 = update_observation(
+        pool_state,
+        &ctx.accounts.observation_state,
+        trade_direction,
+        ctx.accounts.input_vault.amount,
+        ctx.accounts.output_vault.amount,
+    )?;
+
+    // Buys count towards the payer's purchase cap
+    if trade_direction == TradeDirection::OneForZero {
+        record_purchase(
+            pool_state,
+            &mut ctx.accounts.user_purchase_state,
+            ctx.accounts.pool_state.key(),
+            ctx.accounts.payer.key(),
+            block_timestamp,
             input_transfer_amount,
-            ErrorCode::ExceededSlippage
-        );
-        (input_transfer_amount, transfer_fee)
-    };
-    require_eq!(
-        u64::try_from(result.destination_amount_swapped).unwrap(),
-        actual_amount_out
-    );
-    let (output_transfer_amount, _) = (actual_amount_out, out_transfer_fee);
+            output_transfer_amount,
+        )?;
+    }
 
-    let protocol_fee = u64::try_from(result.protocol_fee).unwrap();
-    let creator_fee = u64::try_from(result.creator_fee).unwrap();
+    let protocol_fee = swap_quote.protocol_fee;
+    let creator_fee = swap_quote.creator_fee;
 
     match trade_direction {
         TradeDirection::ZeroForOne => {
@@ -123,49 +94,43 @@ pub fn swap_base_output(
                 .unwrap();
         }
         TradeDirection::OneForZero => {
-            pool_state.protocol_fees_token_usdc = pool_state
-                .protocol_fees_token_usdc
+            pool_state.protocol_fees_token_quote = pool_state
+                .protocol_fees_token_quote
                 .checked_add(protocol_fee)
                 .unwrap();
-            pool_state.creator_fees_token_usdc = pool_state
-                .creator_fees_token_usdc
+            pool_state.creator_fees_token_quote = pool_state
+                .creator_fees_token_quote
                 .checked_add(creator_fee)
                 .unwrap();
         }
     };
 
-    transfer_from_user_to_pool_vault(
-        ctx.accounts.payer.to_account_info(),
-        ctx.accounts.input_token_account.to_account_info(),
-        ctx.accounts.input_vault.to_account_info(),
-        ctx.accounts.input_token_mint.to_account_info(),
-        ctx.accounts.input_token_program.to_account_info(),
+    transfer_swap_input(
+        ctx.accounts,
         input_transfer_amount,
-        ctx.accounts.input_token_mint.decimals,
+        ctx.bumps.native_sol_account,
     )?;
 
-    transfer_from_pool_vault_to_user(
-        ctx.accounts.authority.to_account_info(),
-        ctx.accounts.output_vault.to_account_info(),
-        ctx.accounts.output_token_account.to_account_info(),
-        ctx.accounts.output_token_mint.to_account_info(),
-        ctx.accounts.output_token_program.to_account_info(),
+    transfer_swap_output(
+        ctx.accounts,
+        pool_state,
         output_transfer_amount,
-        ctx.accounts.output_token_mint.decimals,
-        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+        ctx.bumps.native_sol_account,
     )?;
 
+    pay_referral_fee(ctx.accounts, pool_state, swap_quote.referral_fee)?;
+
     ctx.accounts.input_vault.reload()?;
     ctx.accounts.output_vault.reload()?;
     let (token_0_price_x64, _, liquidity_after) = if ctx.accounts.input_vault.key()
         == pool_state.token_mint_vault
-        && ctx.accounts.output_vault.key() == pool_state.token_usdc_vault
+        && ctx.accounts.output_vault.key() == pool_state.token_quote_vault
     {
         pool_state.token_price_x32(
             ctx.accounts.input_vault.amount,
             ctx.accounts.output_vault.amount,
         )
-    } else if ctx.accounts.input_vault.key() == pool_state.token_usdc_vault
+    } else if ctx.accounts.input_vault.key() == pool_state.token_quote_vault
         && ctx.accounts.output_vault.key() == pool_state.token_mint_vault
     {
         pool_state.token_price_x32(
@@ -175,6 +140,15 @@ pub fn swap_base_output(
     } else {
         return err!(ErrorCode::InvalidVault);
     };
+    pool_state.update_volatility(
+        token_0_price_x32,
+        token_0_price_x64,
+        block_timestamp,
+        ctx.accounts
+            .amm_config
+            .dynamic_fee_param_at(block_timestamp)
+            .volatility_decay_period,
+    );
 
     emit_cpi!(SwapPriceEvent {
         timestamp: oracle::block_timestamp(),
@@ -184,15 +158,15 @@ pub fn swap_base_output(
         liquidity_after,
         input_amount: u64::try_from(input_transfer_amount).unwrap(),
         output_amount: u64::try_from(output_transfer_amount).unwrap(),
-        buy: ctx.accounts.input_token_mint.key() == USDC,
-        user: ctx.accounts.payer.key()
+        buy: ctx.accounts.input_token_mint.key() == pool_state.quote_mint,
+        user: ctx.accounts.payer.key(),
+        referrer: ctx
+            .accounts
+            .referral_token_account
+            .as_ref()
+            .map(|referral_token_account| referral_token_account.owner),
     });
 
-    // ctx.accounts.observation_state.load_mut()?.update(
-    //     oracle::block_timestamp(),
-    //     token_0_price_x64,
-    //     token_1_price_x64,
-    // );
     pool_state.recent_epoch = Clock::get()?.epoch;
 
     Ok(())
@@ -0,0 +1,306 @@
+use super::quote::{quote_swap_base_input, QuoteContext, SwapQuote};
+use super::swap_base_input::update_observation;
+use crate::curve::TradeDirection;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Accounts of every pool in the route: amm config, pool state, input vault, output vault,
+/// output mint, output token program and observation state
+pub const ROUTE_HOP_ACCOUNTS: usize = 7;
+/// Maximum number of pools a route can go through
+pub const MAX_ROUTE_HOPS: usize = 4;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SwapRoute<'info> {
+    /// The user performing the swap
+    pub payer: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// The user token account for the input token of the first pool
+    #[account(mut)]
+    pub input_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The user token account for the output token of the last pool
+    #[account(mut)]
+    pub output_token_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// SPL program for input token transfers
+    pub input_token_program: Interface<'info, TokenInterface>,
+
+    /// The mint of input token
+    pub input_token_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+/// The accounts of one pool in the route
+pub struct RouteHop<'info> {
+    pub amm_config: Box<Account<'info, AmmConfig>>,
+    pub pool_state: AccountLoader<'info, PoolState>,
+    pub input_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub output_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+    pub output_mint: Box<InterfaceAccount<'info, Mint>>,
+    pub output_token_program: Interface<'info, TokenInterface>,
+    pub observation_state: AccountLoader<'info, ObservationState>,
+}
+
+impl<'info> RouteHop<'info> {
+    pub fn try_from(accounts: &'info [AccountInfo<'info>]) -> Result<Self> {
+        let hop = Self {
+            amm_config: Box::new(Account::try_from(&accounts[0])?),
+            pool_state: AccountLoader::try_from(&accounts[1])?,
+            input_vault: Box::new(InterfaceAccount::try_from(&accounts[2])?),
+            output_vault: Box::new(InterfaceAccount::try_from(&accounts[3])?),
+            output_mint: Box::new(InterfaceAccount::try_from(&accounts[4])?),
+            output_token_program: Interface::try_from(&accounts[5])?,
+            observation_state: AccountLoader::try_from(&accounts[6])?,
+        };
+        let pool_state = hop.pool_state.load()?;
+        if pool_state.locked {
+            return err!(ErrorCode::PoolLocked);
+        }
+        require_keys_eq!(hop.amm_config.key(), pool_state.amm_config);
+        require_keys_eq!(hop.observation_state.key(), pool_state.observation_key);
+        require_keys_eq!(hop.output_mint.key(), hop.output_vault.mint);
+        if !(hop.input_vault.key() == pool_state.token_mint_vault
+            && hop.output_vault.key() == pool_state.token_quote_vault
+            || hop.input_vault.key() == pool_state.token_quote_vault
+                && hop.output_vault.key() == pool_state.token_mint_vault)
+        {
+            return err!(ErrorCode::InvalidVault);
+        }
+        drop(pool_state);
+        Ok(hop)
+    }
+}
+
+/// Swaps `amount_in`, already transferred into the input vault, through one pool of the route. Returns the quote of the swap, charged as a swap base input, and its event.
+fn swap_route_hop<'info>(
+    hop: &RouteHop<'info>,
+    input_token_mint: &InterfaceAccount<'info, Mint>,
+    amount_in: u64,
+    block_timestamp: u64,
+    user: Pubkey,
+) -> Result<(SwapQuote, SwapPriceEvent)> {
+    let pool_state = &mut hop.pool_state.load_mut()?;
+    if !pool_state.get_status_by_bit(PoolStatusBitIndex::Swap)
+        || block_timestamp < pool_state.open_time
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.apply_pending_off_set(block_timestamp);
+
+    // The vault amounts were read before any transfer of the route
+    let quote_context = QuoteContext {
+        pool_state,
+        amm_config: &hop.amm_config,
+        input_vault: &hop.input_vault,
+        output_vault: &hop.output_vault,
+        input_token_mint,
+        output_token_mint: &hop.output_mint,
+        block_timestamp,
+        referral_fee_rate: 0,
+    };
+    let (trade_direction, swap_quote) = quote_swap_base_input(&quote_context, amount_in, None)?;
+    // Routes don't carry purchase accounts, so they can't buy while buys are capped
+    if trade_direction == TradeDirection::OneForZero
+        && pool_state.purchase_cap_active(block_timestamp)
+    {
+        return err!(ErrorCode::NotApproved);
+    }
+
+    let input_vault_amount = hop.input_vault.amount;
+    let output_vault_amount = hop.output_vault.amount;
+    let token_0_price_x32 = update_observation(
+        pool_state,
+        &hop.observation_state,
+        trade_direction,
+        input_vault_amount,
+        output_vault_amount,
+    )?;
+
+    match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.protocol_fees_token_mint = pool_state
+                .protocol_fees_token_mint
+                .checked_add(swap_quote.protocol_fee)
+                .unwrap();
+            pool_state.creator_fees_token_mint = pool_state
+                .creator_fees_token_mint
+                .checked_add(swap_quote.creator_fee)
+                .unwrap();
+        }
+        TradeDirection::OneForZero => {
+            pool_state.protocol_fees_token_quote = pool_state
+                .protocol_fees_token_quote
+                .checked_add(swap_quote.protocol_fee)
+                .unwrap();
+            pool_state.creator_fees_token_quote = pool_state
+                .creator_fees_token_quote
+                .checked_add(swap_quote.creator_fee)
+                .unwrap();
+        }
+    };
+
+    let actual_amount_in = swap_quote
+        .amount_in
+        .checked_sub(swap_quote.input_transfer_fee)
+        .unwrap();
+    let input_vault_after = input_vault_amount.checked_add(actual_amount_in).unwrap();
+    let output_vault_after = output_vault_amount
+        .checked_sub(swap_quote.amount_out)
+        .unwrap();
+    let (token_0_price_x64, _, _) = match trade_direction {
+        TradeDirection::ZeroForOne => {
+            pool_state.token_price_x32(input_vault_after, output_vault_after)
+        }
+        TradeDirection::OneForZero => {
+            pool_state.token_price_x32(output_vault_after, input_vault_after)
+        }
+    };
+    pool_state.update_volatility(
+        token_0_price_x32,
+        token_0_price_x64,
+        block_timestamp,
+        hop.amm_config
+            .dynamic_fee_param_at(block_timestamp)
+            .volatility_decay_period,
+    );
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    let (liquidity_before, liquidity_after) = match trade_direction {
+        TradeDirection::ZeroForOne => (output_vault_amount, output_vault_after),
+        TradeDirection::OneForZero => (input_vault_amount, input_vault_after),
+    };
+    let swap_price_event = SwapPriceEvent {
+        timestamp: oracle::block_timestamp(),
+        mint: pool_state.mint,
+        price: token_0_price_x64,
+        liquidity_before,
+        liquidity_after,
+        input_amount: actual_amount_in,
+        output_amount: swap_quote.amount_out,
+        buy: trade_direction == TradeDirection::OneForZero,
+        user,
+        referrer: None,
+    };
+    Ok((swap_quote, swap_price_event))
+}
+
+/// Swaps `amount_in` through the pools of the remaining accounts, `ROUTE_HOP_ACCOUNTS` per pool.
+/// The output of every pool is paid straight into the input vault of the next one.
+pub fn swap_route<'a, 'b, 'c: 'info, 'info>(
+    ctx: Context<'a, 'b, 'c, 'info, SwapRoute<'info>>,
+    amount_in: u64,
+    minimum_amount_out: u64,
+) -> Result<()> {
+    let block_timestamp = solana_program::clock::Clock::get()?.unix_timestamp as u64;
+    let remaining_accounts: &'info [AccountInfo<'info>] = ctx.remaining_accounts;
+    if remaining_accounts.is_empty()
+        || remaining_accounts.len() % ROUTE_HOP_ACCOUNTS != 0
+        || remaining_accounts.len() > ROUTE_HOP_ACCOUNTS * MAX_ROUTE_HOPS
+    {
+        return err!(ErrorCode::InvalidInput);
+    }
+    let hops = remaining_accounts
+        .chunks(ROUTE_HOP_ACCOUNTS)
+        .map(RouteHop::try_from)
+        .collect::<Result<Vec<_>>>()?;
+
+    // Every pool takes the output token of the previous one, and each pool is used once
+    require_keys_eq!(
+        hops[0].input_vault.mint,
+        ctx.accounts.input_token_mint.key(),
+        ErrorCode::InvalidInput
+    );
+    for (index, hop) in hops.iter().enumerate().skip(1) {
+        require_keys_eq!(
+            hop.input_vault.mint,
+            hops[index - 1].output_vault.mint,
+            ErrorCode::InvalidInput
+        );
+        if hops[..index]
+            .iter()
+            .any(|previous| previous.pool_state.key() == hop.pool_state.key())
+        {
+            return err!(ErrorCode::InvalidInput);
+        }
+    }
+    let last_hop = hops.last().unwrap();
+    require_keys_eq!(
+        last_hop.output_vault.mint,
+        ctx.accounts.output_token_account.mint,
+        ErrorCode::InvalidInput
+    );
+
+    transfer_from_user_to_pool_vault(
+        ctx.accounts.payer.to_account_info(),
+        ctx.accounts.input_token_account.to_account_info(),
+        hops[0].input_vault.to_account_info(),
+        ctx.accounts.input_token_mint.to_account_info(),
+        ctx.accounts.input_token_program.to_account_info(),
+        amount_in,
+        ctx.accounts.input_token_mint.decimals,
+    )?;
+
+    // Every pool is charged as a swap base input of the amount transferred into its vault
+    let mut hop_amount_in = amount_in;
+    let mut hop_input_token_mint: &InterfaceAccount<Mint> = &ctx.accounts.input_token_mint;
+    let mut amount_received = 0;
+    for (index, hop) in hops.iter().enumerate() {
+        let (swap_quote, swap_price_event) = swap_route_hop(
+            hop,
+            hop_input_token_mint,
+            hop_amount_in,
+            block_timestamp,
+            ctx.accounts.payer.key(),
+        )?;
+        let destination = match hops.get(index + 1) {
+            Some(next_hop) => next_hop.input_vault.to_account_info(),
+            None => ctx.accounts.output_token_account.to_account_info(),
+        };
+        transfer_from_pool_vault_to_user(
+            ctx.accounts.authority.to_account_info(),
+            hop.output_vault.to_account_info(),
+            destination,
+            hop.output_mint.to_account_info(),
+            hop.output_token_program.to_account_info(),
+            swap_quote.amount_out,
+            hop.output_mint.decimals,
+            &[&[crate::AUTH_SEED.as_bytes(), &[ctx.bumps.authority]]],
+        )?;
+        emit_cpi!(swap_price_event);
+
+        hop_amount_in = swap_quote.amount_out;
+        hop_input_token_mint = &hop.output_mint;
+        amount_received = swap_quote
+            .amount_out
+            .checked_sub(swap_quote.output_transfer_fee)
+            .unwrap();
+    }
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "amount_in:{}, amount_received:{}",
+        amount_in,
+        amount_received
+    );
+    require_gte!(
+        amount_received,
+        minimum_amount_out,
+        ErrorCode::ExceededSlippage
+    );
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+use crate::error::ErrorCode;
+use crate::states::*;
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct TransferPoolCreator<'info> {
+    /// The current pool creator
+    #[account(address = pool_state.load()?.pool_creator @ ErrorCode::InvalidOwner)]
+    pub pool_creator: Signer<'info>,
+
+    /// The pool to hand over
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+}
+
+pub fn transfer_pool_creator(ctx: Context<TransferPoolCreator>, new_creator: Pubkey) -> Result<()> {
+    let mut pool_state = ctx.accounts.pool_state.load_mut()?;
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "pool_state, pool_creator:{}, pending_pool_creator:{}",
+        ctx.accounts.pool_creator.key().to_string(),
+        new_creator.to_string()
+    );
+    // Proposing the default key cancels a pending transfer
+    pool_state.pending_pool_creator = new_creator;
+
+    emit_cpi!(PoolCreatorUpdated {
+        pool_id: ctx.accounts.pool_state.key(),
+        old_creator: ctx.accounts.pool_creator.key(),
+        new_creator,
+        pending: true,
+        signer: ctx.accounts.pool_creator.key(),
+        slot: Clock::get()?.slot,
+    });
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use crate::curve::RoundDirection;
+use crate::error::ErrorCode;
+use crate::states::*;
+use crate::utils::token::*;
+use anchor_lang::prelude::*;
+use anchor_spl::token::Token;
+use anchor_spl::token_interface::{Mint, Token2022, TokenAccount};
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    /// Pays to burn the position
+    pub owner: Signer<'info>,
+
+    /// CHECK: pool vault and lp mint authority
+    #[account(
+        seeds = [
+            crate::AUTH_SEED.as_bytes(),
+        ],
+        bump,
+    )]
+    pub authority: UncheckedAccount<'info>,
+
+    /// Pool state account
+    #[account(
+        mut,
+        constraint = !pool_state.load()?.locked @ ErrorCode::PoolLocked,
+        constraint = pool_state.load()?.has_lp_mint() @ ErrorCode::NotApproved
+    )]
+    pub pool_state: AccountLoader<'info, PoolState>,
+
+    /// Owner lp token account
+    #[account(
+        mut,
+        token::authority = owner
+    )]
+    pub owner_lp_token: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The owner's token account for receiving the pool mint
+    #[account(
+        mut,
+        token::mint = token_mint_vault.mint,
+    )]
+    pub token_mint_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The owner's token account for receiving the quote token
+    #[account(
+        mut,
+        token::mint = token_quote_vault.mint,
+    )]
+    pub token_quote_account: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for the pool mint
+    #[account(
+        mut,
+        constraint = token_mint_vault.key() == pool_state.load()?.token_mint_vault
+    )]
+    pub token_mint_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The address that holds pool tokens for the quote token
+    #[account(
+        mut,
+        constraint = token_quote_vault.key() == pool_state.load()?.token_quote_vault
+    )]
+    pub token_quote_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// token Program
+    pub token_program: Program<'info, Token>,
+
+    /// Token program 2022
+    pub token_program_2022: Program<'info, Token2022>,
+
+    /// The mint of the pool mint vault
+    #[account(
+        address = token_mint_vault.mint
+    )]
+    pub vault_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// The mint of the quote vault
+    #[account(
+        address = token_quote_vault.mint
+    )]
+    pub vault_quote_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Pool lp token mint
+    #[account(
+        mut,
+        address = pool_state.load()?.lp_mint @ ErrorCode::IncorrectLpMint
+    )]
+    pub lp_mint: Box<InterfaceAccount<'info, Mint>>,
+}
+
+pub fn withdraw(
+    ctx: Context<Withdraw>,
+    lp_token_amount: u64,
+    minimum_mint_amount: u64,
+    minimum_quote_amount: u64,
+) -> Result<()> {
+    require_gt!(lp_token_amount, 0);
+    require_gt!(ctx.accounts.lp_mint.supply, 0);
+    let pool_state = &mut ctx.accounts.pool_state.load_mut()?;
+    // Liquidity of a launching pool is locked until it graduates
+    if pool_state.in_launch_phase() || !pool_state.get_status_by_bit(PoolStatusBitIndex::Withdraw) {
+        return err!(ErrorCode::NotApproved);
+    }
+    pool_state.apply_pending_off_set(Clock::get()?.unix_timestamp as u64);
+    let (total_mint_amount, total_quote_amount) = pool_state.lp_vault_amount(
+        ctx.accounts.token_mint_vault.amount,
+        ctx.accounts.token_quote_vault.amount,
+    );
+    let results = pool_state
+        .curve_calculator()
+        .lp_tokens_to_trading_tokens(
+            u128::from(lp_token_amount),
+            u128::from(pool_state.lp_supply),
+            u128::from(total_mint_amount),
+            u128::from(total_quote_amount),
+            RoundDirection::Floor,
+        )
+        .ok_or(ErrorCode::ZeroTradingTokens)?;
+    if results.token_0_amount == 0 && results.token_1_amount == 0 {
+        return err!(ErrorCode::ZeroTradingTokens);
+    }
+    let mint_amount = u64::try_from(results.token_0_amount).unwrap();
+    let (receive_mint_amount, mint_transfer_fee) = {
+        let transfer_fee =
+            get_transfer_fee(&ctx.accounts.vault_mint.to_account_info(), mint_amount)?;
+        (mint_amount.checked_sub(transfer_fee).unwrap(), transfer_fee)
+    };
+
+    let quote_amount = u64::try_from(results.token_1_amount).unwrap();
+    let (receive_quote_amount, quote_transfer_fee) = {
+        let transfer_fee = get_transfer_fee(
+            &ctx.accounts.vault_quote_mint.to_account_info(),
+            quote_amount,
+        )?;
+        (
+            quote_amount.checked_sub(transfer_fee).unwrap(),
+            transfer_fee,
+        )
+    };
+    // The virtual offset, and any queued one, shrinks with the real reserves so the price is unchanged
+    let off_set_amount = pool_state.lp_tokens_to_off_set(lp_token_amount);
+    let pending_off_set_amount = pool_state.lp_tokens_to_pending_off_set(lp_token_amount);
+
+    #[cfg(feature = "enable-log")]
+    msg!(
+        "results.token_0_amount:{}, results.token_1_amount:{}, receive_mint_amount:{}, mint_transfer_fee:{}, receive_quote_amount:{}, quote_transfer_fee:{}, off_set_amount:{}",
+        results.token_0_amount,
+        results.token_1_amount,
+        receive_mint_amount,
+        mint_transfer_fee,
+        receive_quote_amount,
+        quote_transfer_fee,
+        off_set_amount
+    );
+
+    if receive_mint_amount < minimum_mint_amount || receive_quote_amount < minimum_quote_amount {
+        return Err(ErrorCode::ExceededSlippage.into());
+    }
+
+    emit_cpi!(LpChangeEvent {
+        mint: pool_state.mint,
+        lp_amount_before: pool_state.lp_supply,
+        mint_amount,
+        quote_amount,
+        mint_transfer_fee,
+        quote_transfer_fee,
+        off_set_change: off_set_amount,
+        change_type: 1
+    });
+
+    pool_state.off_set = pool_state.off_set.checked_sub(off_set_amount).unwrap();
+    pool_state.pending_off_set = pool_state
+        .pending_off_set
+        .checked_sub(pending_off_set_amount)
+        .unwrap();
+    pool_state.lp_supply = pool_state.lp_supply.checked_sub(lp_token_amount).unwrap();
+
+    token_burn(
+        ctx.accounts.owner.to_account_info(),
+        ctx.accounts.token_program.to_account_info(),
+        ctx.accounts.lp_mint.to_account_info(),
+        ctx.accounts.owner_lp_token.to_account_info(),
+        lp_token_amount,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_mint_vault.to_account_info(),
+        ctx.accounts.token_mint_account.to_account_info(),
+        ctx.accounts.vault_mint.to_account_info(),
+        if ctx.accounts.vault_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        mint_amount,
+        ctx.accounts.vault_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+
+    transfer_from_pool_vault_to_user(
+        ctx.accounts.authority.to_account_info(),
+        ctx.accounts.token_quote_vault.to_account_info(),
+        ctx.accounts.token_quote_account.to_account_info(),
+        ctx.accounts.vault_quote_mint.to_account_info(),
+        if ctx.accounts.vault_quote_mint.to_account_info().owner == ctx.accounts.token_program.key {
+            ctx.accounts.token_program.to_account_info()
+        } else {
+            ctx.accounts.token_program_2022.to_account_info()
+        },
+        quote_amount,
+        ctx.accounts.vault_quote_mint.decimals,
+        &[&[crate::AUTH_SEED.as_bytes(), &[pool_state.auth_bump]]],
+    )?;
+    pool_state.recent_epoch = Clock::get()?.epoch;
+
+    Ok(())
+}
@@ -4,21 +4,76 @@ pub mod instructions;
 pub mod states;
 pub mod utils;
 
-use crate::curve::fees::FEE_RATE_DENOMINATOR_VALUE;
+use crate::curve::CurveType;
+use crate::states::{
+    AdminRole, AmmConfigParam, FeeSplitRecipient, LaunchFeeParam, PurchaseCapParam,
+};
 use anchor_lang::prelude::*;
 use instructions::*;
 
 declare_id!("PkNZ3YjzrtxV16wdfcZPVKaA71dp3kAdhyHb72L2X4k");
 
+/// Bootstrap authority, only allowed to create the admin registry once
 pub const PROTOCOL_AUTHORITY: Pubkey = pubkey!("Eo769i4Q8ExHzQ9gS9S5PexAu8zXPe5G7hYArqqsja7p");
-pub const USDC: Pubkey = pubkey!("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
 pub const AUTH_SEED: &str = "vault_and_lp_mint_auth_seed";
+/// Seed of the temporary wrapped SOL account of a native SOL swap
+pub const NATIVE_SOL_SEED: &str = "native_sol";
 
 #[program]
 pub mod raydium_cp_swap {
 
     use super::*;
 
+    /// Creates the admin registry, can only be called once by the bootstrap authority
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The accounts needed by instruction.
+    /// * `owner` - The admin owner, initially holding every role.
+    ///
+    pub fn create_admin(ctx: Context<CreateAdmin>, owner: Pubkey) -> Result<()> {
+        instructions::create_admin(ctx, owner)
+    }
+
+    /// Proposes a new admin owner, who must accept before taking over
+    /// Must be called by the current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `new_owner` - The proposed owner, the default key cancels a pending transfer
+    ///
+    pub fn transfer_admin_owner(ctx: Context<TransferAdminOwner>, new_owner: Pubkey) -> Result<()> {
+        instructions::transfer_admin_owner(ctx, new_owner)
+    }
+
+    /// Accepts the admin ownership, must be called by the pending owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn accept_admin_owner(ctx: Context<AcceptAdminOwner>) -> Result<()> {
+        instructions::accept_admin_owner(ctx)
+    }
+
+    /// Assigns an admin role to a new holder
+    /// Must be called by the current owner
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `role` - The role to assign
+    /// * `holder` - The new holder of the role
+    ///
+    pub fn update_admin_role(
+        ctx: Context<UpdateAdminRole>,
+        role: AdminRole,
+        holder: Pubkey,
+    ) -> Result<()> {
+        instructions::update_admin_role(ctx, role, holder)
+    }
+
     // The configuation of AMM protocol, include trade fee and protocol fee
     /// # Arguments
     ///
@@ -27,6 +82,9 @@ pub mod raydium_cp_swap {
     /// * `trade_fee_rate` - Trade fee rate, can be changed.
     /// * `protocol_fee_rate` - The rate of protocol fee within tarde fee.
     /// * `fund_fee_rate` - The rate of fund fee within tarde fee.
+    /// * `curve_type` - The invariant every pool of the config trades on, can not be changed.
+    ///
+    /// The quote mint of every pool created under the config is taken from the `quote_mint` account.
     ///
     pub fn create_amm_config(
         ctx: Context<CreateAmmConfig>,
@@ -34,33 +92,57 @@ pub mod raydium_cp_swap {
         trade_fee_rate: u64,
         protocol_fee_rate: u64,
         protocol_owner: Pubkey,
+        curve_type: CurveType,
     ) -> Result<()> {
-        assert!(trade_fee_rate < FEE_RATE_DENOMINATOR_VALUE);
-        assert!(protocol_fee_rate <= FEE_RATE_DENOMINATOR_VALUE);
+        instructions::validate_trade_fee_rate(trade_fee_rate)?;
+        instructions::validate_protocol_fee_rate(protocol_fee_rate)?;
+        instructions::validate_curve_type(curve_type)?;
         instructions::create_amm_config(
             ctx,
             index,
             trade_fee_rate,
             protocol_fee_rate,
             protocol_owner,
+            curve_type,
         )
     }
 
-    /// Updates the owner of the amm config
-    /// Must be called by the current owner or admin
+    /// Updates one field of the amm config
+    /// Must be called by the admin owner or fee manager
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `param`- The field to update with its new value, one of the trade fee rate,
+    ///   the protocol fee rate, the protocol fee collector, the disable create pool flag, the
+    ///   graduation threshold of new pools, the dynamic fee settings, the referral fee rate or
+    ///   the quote mint of a migrated config. A new trade fee rate or dynamic fee setting is
+    ///   queued and only takes effect after the timelock
+    ///
+    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: AmmConfigParam) -> Result<()> {
+        instructions::update_amm_config(ctx, param)
+    }
+
+    /// Cancels the queued trade fee rate of the amm config before it takes effect
+    /// Must be called by the admin owner or fee manager
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `trade_fee_rate`- The new trade fee rate of amm config, be set when `param` is 0
-    /// * `protocol_fee_rate`- The new protocol fee rate of amm config, be set when `param` is 1
-    /// * `fund_fee_rate`- The new fund fee rate of amm config, be set when `param` is 2
-    /// * `new_owner`- The config's new owner, be set when `param` is 3
-    /// * `new_fund_owner`- The config's new fund owner, be set when `param` is 4
-    /// * `param`- The vaule can be 0 | 1 | 2 | 3 | 4, otherwise will report a error
     ///
-    pub fn update_amm_config(ctx: Context<UpdateAmmConfig>, param: u8, value: u64) -> Result<()> {
-        instructions::update_amm_config(ctx, param, value)
+    pub fn cancel_pending_trade_fee_rate(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+        instructions::cancel_pending_trade_fee_rate(ctx)
+    }
+
+    /// Cancels the queued dynamic fee settings of the amm config before they take effect
+    /// Must be called by the admin owner or fee manager
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_pending_dynamic_fee(ctx: Context<UpdateAmmConfig>) -> Result<()> {
+        instructions::cancel_pending_dynamic_fee(ctx)
     }
 
     /// Update pool status for given vaule
@@ -74,23 +156,119 @@ pub mod raydium_cp_swap {
         instructions::update_pool_status(ctx, status)
     }
 
+    /// Queues a new virtual offset for the pool, applied after the timelock
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `offset` - The new virtual quote token offset
+    ///
     pub fn update_pool_offset(ctx: Context<UpdatePoolOffset>, offset: u64) -> Result<()> {
         instructions::update_pool_offset(ctx, offset)
     }
 
-    /// Collect the fund fee accrued to the pool
+    /// Cancels the queued virtual offset of the pool before it takes effect
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn cancel_pending_pool_offset(ctx: Context<UpdatePoolOffset>) -> Result<()> {
+        instructions::cancel_pending_pool_offset(ctx)
+    }
+
+    /// Collect the creator fees accrued to the pool
+    /// Must be called by the pool creator
     ///
     /// # Arguments
     ///
     /// * `ctx` - The context of accounts
-    /// * `amount_0_requested` - The maximum amount of token_0 to send, can be 0 to collect fees in only token_1
-    /// * `amount_1_requested` - The maximum amount of token_1 to send, can be 0 to collect fees in only token_0
+    /// * `amount_mint_requested` - The maximum amount of the pool mint to send, `None` to collect all of it
+    /// * `amount_quote_requested` - The maximum amount of the quote token to send, `None` to collect all of it
+    ///
+    pub fn collect_creator_fees(
+        ctx: Context<CollectCreatorFees>,
+        amount_mint_requested: Option<u64>,
+        amount_quote_requested: Option<u64>,
+    ) -> Result<()> {
+        instructions::collect_creator_fees(ctx, amount_mint_requested, amount_quote_requested)
+    }
+
+    /// Collect the protocol fees accrued to the pool
+    /// Must be called by the protocol fee collector of the amm config
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `amount_mint_requested` - The maximum amount of the pool mint to send, `None` to collect all of it
+    /// * `amount_quote_requested` - The maximum amount of the quote token to send, `None` to collect all of it
+    ///
+    pub fn collect_protocol_fees(
+        ctx: Context<CollectProtocolFees>,
+        amount_mint_requested: Option<u64>,
+        amount_quote_requested: Option<u64>,
+    ) -> Result<()> {
+        instructions::collect_protocol_fees(ctx, amount_mint_requested, amount_quote_requested)
+    }
+
+    /// Proposes a new pool creator, who must accept before taking over the pool fees
+    /// Must be called by the current pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `new_creator` - The proposed creator, the default key cancels a pending transfer
+    ///
+    pub fn transfer_pool_creator(
+        ctx: Context<TransferPoolCreator>,
+        new_creator: Pubkey,
+    ) -> Result<()> {
+        instructions::transfer_pool_creator(ctx, new_creator)
+    }
+
+    /// Accepts the pool creator role, must be called by the pending pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn accept_pool_creator(ctx: Context<AcceptPoolCreator>) -> Result<()> {
+        instructions::accept_pool_creator(ctx)
+    }
+
+    /// Splits the creator fees of a pool between several recipients, replacing any previous
+    /// split. Once split, creator fees are only paid out by `distribute_creator_fees`, an empty
+    /// list of recipients clears the split. Must be called by the pool creator
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx` - The context of accounts
+    /// * `recipients` - Up to `MAX_FEE_SPLIT_RECIPIENTS` distinct recipients whose basis point
+    ///   shares add up to 10000, or none to clear the split
+    ///
+    pub fn set_creator_fee_split(
+        ctx: Context<SetCreatorFeeSplit>,
+        recipients: Vec<FeeSplitRecipient>,
+    ) -> Result<()> {
+        instructions::set_creator_fee_split(ctx, recipients)
+    }
+
+    /// Distributes the accrued creator fees pro rata to the fee split recipients
+    ///
+    /// # Arguments
     ///
-    pub fn collect_fees(ctx: Context<CollectFee>) -> Result<()> {
-        instructions::collect_fee(ctx)
+    /// * `ctx` - The context of accounts, the remaining accounts are the pool mint and quote
+    ///   token accounts of every recipient, in order
+    ///
+    pub fn distribute_creator_fees<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, DistributeCreatorFees<'info>>,
+    ) -> Result<()> {
+        instructions::distribute_creator_fees(ctx)
     }
 
     /// Creates a pool for the given token pair and the initial price
+    /// The initial liquidity is only backed by the virtual offset and stays locked in the pool,
+    /// bonding curve pools take no offset
     ///
     /// # Arguments
     ///
@@ -98,41 +276,244 @@ pub mod raydium_cp_swap {
     /// * `init_amount_0` - the initial amount_0 to deposit
     /// * `init_amount_1` - the initial amount_1 to deposit
     /// * `open_time` - the timestamp allowed for swap
+    /// * `launch_fee` - optional fee schedule decaying from the open time, charged in place of
+    ///   the config trade fee while it is higher
+    /// * `purchase_cap` - optional per-wallet limit on buys for a window from the open time
     ///
     pub fn initialize(
         ctx: Context<Initialize>,
         init_amount_0: u64,
         init_amount_1: u64,
         open_time: u64,
+        launch_fee: Option<LaunchFeeParam>,
+        purchase_cap: Option<PurchaseCapParam>,
     ) -> Result<()> {
-        instructions::initialize(ctx, init_amount_0, init_amount_1, open_time)
+        instructions::initialize(
+            ctx,
+            init_amount_0,
+            init_amount_1,
+            open_time,
+            launch_fee,
+            purchase_cap,
+        )
+    }
+
+    /// Deposit lp token to the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Pool token amount to transfer. pool mint and quote token amount are set by the current exchange rate and size of the pool
+    /// * `maximum_mint_amount` -  Maximum pool mint amount to deposit, prevents excessive slippage
+    /// * `maximum_quote_amount` - Maximum quote token amount to deposit, prevents excessive slippage
+    ///
+    pub fn deposit(
+        ctx: Context<Deposit>,
+        lp_token_amount: u64,
+        maximum_mint_amount: u64,
+        maximum_quote_amount: u64,
+    ) -> Result<()> {
+        instructions::deposit(
+            ctx,
+            lp_token_amount,
+            maximum_mint_amount,
+            maximum_quote_amount,
+        )
+    }
+
+    /// Withdraw lp for the pool mint and quote token
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `lp_token_amount` - Amount of pool tokens to burn. User receives an output of pool mint and quote token based on the percentage of the pool tokens that are returned.
+    /// * `minimum_mint_amount` -  Minimum amount of pool mint to receive, prevents excessive slippage
+    /// * `minimum_quote_amount` -  Minimum amount of quote token to receive, prevents excessive slippage
+    ///
+    pub fn withdraw(
+        ctx: Context<Withdraw>,
+        lp_token_amount: u64,
+        minimum_mint_amount: u64,
+        minimum_quote_amount: u64,
+    ) -> Result<()> {
+        instructions::withdraw(
+            ctx,
+            lp_token_amount,
+            minimum_mint_amount,
+            minimum_quote_amount,
+        )
+    }
+
+    /// Moves a pool that crossed its graduation threshold from the launch curve to a plain
+    /// constant product pool. The virtual offset is removed, the pool mint not backed by real
+    /// quote tokens is burned and the liquidity is locked under a new lp mint.
+    /// Can be called by anyone
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn graduate(ctx: Context<Graduate>) -> Result<()> {
+        instructions::graduate(ctx)
+    }
+
+    /// Grows a pool state created before `POOL_STATE_VERSION` 1 to the current layout and creates
+    /// its observation account. Migrated pools have no lp mint and reject deposits and withdrawals
+    /// Can be called by anyone, the payer covers the additional rent
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_pool_state(ctx: Context<MigratePoolState>) -> Result<()> {
+        instructions::migrate_pool_state(ctx)
+    }
+
+    /// Grows an amm config created before the quote mint was added to the current layout.
+    /// The fee manager then records its quote mint with `update_amm_config`
+    /// Can be called by anyone, the payer covers the additional rent
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    ///
+    pub fn migrate_amm_config(ctx: Context<MigrateAmmConfig>) -> Result<()> {
+        instructions::migrate_amm_config(ctx)
+    }
+
+    /// Read the time-weighted average prices of a pool, returned through return data
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `seconds` - The length of the averaging window, ending at the most recent observation
+    ///
+    pub fn get_twap(ctx: Context<GetTwap>, seconds: u64) -> Result<TwapPrice> {
+        instructions::get_twap(ctx, seconds)
+    }
+
+    /// Quote a swap in the pool base input amount, returned through return data.
+    /// Runs the same calculation as `swap_base_input` without transferring
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_in` -  input amount the user would transfer
+    /// * `with_referrer` -  Whether the swap would name a referrer and pay the referral fee
+    /// * `price_limit_x32` -  Optional pool mint price in the quote token, Q32.32, only the part of the swap up to it is quoted
+    ///
+    pub fn quote_base_input(
+        ctx: Context<Quote>,
+        amount_in: u64,
+        with_referrer: bool,
+        price_limit_x32: Option<u128>,
+    ) -> Result<SwapQuote> {
+        instructions::quote_base_input(ctx, amount_in, with_referrer, price_limit_x32)
+    }
+
+    /// Quote a swap in the pool base output amount, returned through return data.
+    /// Runs the same calculation as `swap_base_output` without transferring
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `amount_out` -  amount of output token the user would receive
+    /// * `with_referrer` -  Whether the swap would name a referrer and pay the referral fee
+    /// * `price_limit_x32` -  Optional pool mint price in the quote token, Q32.32, only the part of the swap up to it is quoted
+    ///
+    pub fn quote_base_output(
+        ctx: Context<Quote>,
+        amount_out: u64,
+        with_referrer: bool,
+        price_limit_x32: Option<u128>,
+    ) -> Result<SwapQuote> {
+        instructions::quote_base_output(ctx, amount_out, with_referrer, price_limit_x32)
     }
 
     /// Swap the tokens in the pool base input amount
+    /// Leaving out the input or output token account swaps native SOL, wrapped and unwrapped
+    /// through a temporary account of the payer
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
     /// * `amount_in` -  input amount to transfer, output to DESTINATION is based on the exchange rate
-    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage
+    /// * `minimum_amount_out` -  Minimum amount of output token, prevents excessive slippage,
+    ///   a partial fill must receive it too
+    /// * `deadline` -  Optional unix timestamp after which the swap fails
+    /// * `price_limit_x32` -  Optional pool mint price in the quote token, Q32.32, the swap only
+    ///   fills up to it and the rest of the input stays with the user
     ///
     pub fn swap_base_input(
         ctx: Context<Swap>,
         amount_in: u64,
         minimum_amount_out: u64,
+        deadline: Option<u64>,
+        price_limit_x32: Option<u128>,
     ) -> Result<()> {
-        instructions::swap_base_input(ctx, amount_in, minimum_amount_out)
+        instructions::swap_base_input(
+            ctx,
+            amount_in,
+            minimum_amount_out,
+            deadline,
+            price_limit_x32,
+        )
     }
 
     /// Swap the tokens in the pool base output amount
+    /// Leaving out the input or output token account swaps native SOL, wrapped and unwrapped
+    /// through a temporary account of the payer
     ///
     /// # Arguments
     ///
     /// * `ctx`- The context of accounts
-    /// * `max_amount_in` -  input amount prevents excessive slippage
+    /// * `max_amount_in` -  input amount prevents excessive slippage, also for a partial fill
     /// * `amount_out` -  amount of output token
+    /// * `deadline` -  Optional unix timestamp after which the swap fails
+    /// * `price_limit_x32` -  Optional pool mint price in the quote token, Q32.32, the swap only
+    ///   fills up to it and sends out less than `amount_out`
+    ///
+    pub fn swap_base_output(
+        ctx: Context<Swap>,
+        max_amount_in: u64,
+        amount_out: u64,
+        deadline: Option<u64>,
+        price_limit_x32: Option<u128>,
+    ) -> Result<()> {
+        instructions::swap_base_output(ctx, max_amount_in, amount_out, deadline, price_limit_x32)
+    }
+
+    /// Flash swap, sends the output first and calls back a program which has to pay
+    /// the input and fees of the same swap base output into the input vault. The pool is locked
+    /// until the callback returns
+    ///
+    /// # Arguments
     ///
-    pub fn swap_base_output(ctx: Context<Swap>, max_amount_in: u64, amount_out: u64) -> Result<()> {
-        instructions::swap_base_output(ctx, max_amount_in, amount_out)
+    /// * `ctx`- The context of accounts, the remaining accounts are passed to the callback
+    /// * `amount_out_less_fee` -  amount of output token received before the callback
+    /// * `data` -  instruction data of the callback
+    ///
+    pub fn flash_swap<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, FlashSwap<'info>>,
+        amount_out_less_fee: u64,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        instructions::flash_swap(ctx, amount_out_less_fee, data)
+    }
+
+    /// Swap the tokens through several pools, base input amount
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts, the remaining accounts are the `ROUTE_HOP_ACCOUNTS` of every pool in order
+    /// * `amount_in` -  input amount to transfer into the first pool, output to the user from the last pool
+    /// * `minimum_amount_out` -  minimum amount received from the last pool, prevents excessive slippage
+    ///
+    pub fn swap_route<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, SwapRoute<'info>>,
+        amount_in: u64,
+        minimum_amount_out: u64,
+    ) -> Result<()> {
+        instructions::swap_route(ctx, amount_in, minimum_amount_out)
     }
 }
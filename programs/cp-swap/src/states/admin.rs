@@ -0,0 +1,82 @@
+use anchor_lang::prelude::*;
+
+pub const ADMIN_SEED: &str = "admin";
+
+/// The roles the admin owner can delegate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AdminRole {
+    /// Can create and update amm configs
+    FeeManager,
+    /// Can update the status of pools
+    Pauser,
+    /// Can update the virtual offset of pools
+    OffsetManager,
+}
+
+/// Holds the protocol admin and the delegated roles
+#[account]
+#[derive(Default, Debug)]
+pub struct AdminState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// Address allowed to manage the registry, also holds every role
+    pub owner: Pubkey,
+    /// Address proposed as the next owner, must accept to take over
+    pub pending_owner: Pubkey,
+    /// Address allowed to create and update amm configs
+    pub fee_manager: Pubkey,
+    /// Address allowed to update pool status
+    pub pauser: Pubkey,
+    /// Address allowed to update pool offset
+    pub offset_manager: Pubkey,
+    /// padding
+    pub padding: [u64; 16],
+}
+
+impl AdminState {
+    pub const LEN: usize = 8 + std::mem::size_of::<AdminState>();
+
+    /// Returns true if the key is the owner or holds the given role
+    pub fn has_role(&self, key: &Pubkey, role: AdminRole) -> bool {
+        *key == self.owner || *key == self.role_holder(role)
+    }
+
+    pub fn role_holder(&self, role: AdminRole) -> Pubkey {
+        match role {
+            AdminRole::FeeManager => self.fee_manager,
+            AdminRole::Pauser => self.pauser,
+            AdminRole::OffsetManager => self.offset_manager,
+        }
+    }
+
+    pub fn set_role_holder(&mut self, role: AdminRole, holder: Pubkey) {
+        match role {
+            AdminRole::FeeManager => self.fee_manager = holder,
+            AdminRole::Pauser => self.pauser = holder,
+            AdminRole::OffsetManager => self.offset_manager = holder,
+        }
+    }
+}
+
+#[cfg(test)]
+pub mod admin_test {
+    use super::*;
+
+    #[test]
+    fn owner_holds_every_role() {
+        let owner = Pubkey::new_unique();
+        let pauser = Pubkey::new_unique();
+        let mut admin_state = AdminState {
+            owner,
+            ..Default::default()
+        };
+        admin_state.set_role_holder(AdminRole::Pauser, pauser);
+
+        assert!(admin_state.has_role(&owner, AdminRole::FeeManager));
+        assert!(admin_state.has_role(&owner, AdminRole::Pauser));
+        assert!(admin_state.has_role(&pauser, AdminRole::Pauser));
+        assert!(!admin_state.has_role(&pauser, AdminRole::FeeManager));
+        assert!(!admin_state.has_role(&pauser, AdminRole::OffsetManager));
+        assert_eq!(admin_state.role_holder(AdminRole::Pauser), pauser);
+    }
+}
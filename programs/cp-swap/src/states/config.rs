@@ -1,6 +1,12 @@
+use crate::curve::{CurveType, DynamicFee};
+use crate::error::ErrorCode;
 use anchor_lang::prelude::*;
 
 pub const AMM_CONFIG_SEED: &str = "amm_config";
+/// Delay before a queued trade fee rate, dynamic fee or pool offset change takes effect
+pub const TIMELOCK_DURATION: u64 = 60 * 60 * 24;
+/// Size of a version 0 amm config account, before the fields from the quote mint on were added
+pub const AMM_CONFIG_V0_LEN: usize = 8 + 184;
 
 /// Holds the current owner of the factory
 #[account]
@@ -18,10 +24,323 @@ pub struct AmmConfig {
     pub protocol_fee_rate: u64,
     /// Address of the protocol fee owner
     pub protocol_fee_collector: Pubkey,
+    /// The mint every pool of this config is quoted in, the fields from here on start out zeroed
+    /// in configs grown by `migrate_amm_config`
+    pub quote_mint: Pubkey,
+    /// The queued trade fee rate, applied once `pending_trade_fee_rate_effective_time` is reached
+    pub pending_trade_fee_rate: u64,
+    /// The timestamp the queued trade fee rate takes effect, 0 if nothing is queued
+    pub pending_trade_fee_rate_effective_time: u64,
+    /// Real quote token reserves at which new pools graduate to a plain constant product pool,
+    /// 0 to disable graduation
+    pub graduation_threshold: u64,
+    /// The invariant every pool of this config trades on
+    pub curve_type: CurveType,
+    /// Share of a pool's recent volatility charged on top of the trade fee, denominated in
+    /// hundredths of a bip (10^-6), 0 to disable the dynamic fee
+    pub variable_fee_control: u64,
+    /// Cap of the trade fee rate including the variable fee
+    pub max_fee_rate: u64,
+    /// Seconds for a pool's volatility to decay to zero
+    pub volatility_decay_period: u64,
+    /// Share of the trade fee paid to the referrer of a swap, denominated in hundredths of a bip
+    /// (10^-6)
+    pub referral_fee_rate: u64,
+    /// The queued dynamic fee settings, applied once `pending_dynamic_fee_effective_time` is
+    /// reached
+    pub pending_dynamic_fee: DynamicFeeParam,
+    /// The timestamp the queued dynamic fee settings take effect, 0 if nothing is queued
+    pub pending_dynamic_fee_effective_time: u64,
     /// padding
-    pub padding: [u64; 16],
+    pub padding: [u64; 3],
 }
 
 impl AmmConfig {
     pub const LEN: usize = 8 + std::mem::size_of::<AmmConfig>();
+
+    /// Returns the trade fee rate in effect at `block_timestamp`, including a queued rate whose
+    /// timelock has elapsed but has not been written back yet
+    pub fn trade_fee_rate_at(&self, block_timestamp: u64) -> u64 {
+        if self.pending_trade_fee_rate_effective_time != 0
+            && block_timestamp >= self.pending_trade_fee_rate_effective_time
+        {
+            self.pending_trade_fee_rate
+        } else {
+            self.trade_fee_rate
+        }
+    }
+
+    /// Writes back a queued trade fee rate whose timelock has elapsed
+    pub fn apply_pending_trade_fee_rate(&mut self, block_timestamp: u64) {
+        self.trade_fee_rate = self.trade_fee_rate_at(block_timestamp);
+        if self.pending_trade_fee_rate_effective_time != 0
+            && block_timestamp >= self.pending_trade_fee_rate_effective_time
+        {
+            self.pending_trade_fee_rate = 0;
+            self.pending_trade_fee_rate_effective_time = 0;
+        }
+    }
+
+    /// Returns the dynamic fee settings in effect at `block_timestamp`, including queued settings
+    /// whose timelock has elapsed but have not been written back yet
+    pub fn dynamic_fee_param_at(&self, block_timestamp: u64) -> DynamicFeeParam {
+        if self.pending_dynamic_fee_effective_time != 0
+            && block_timestamp >= self.pending_dynamic_fee_effective_time
+        {
+            self.pending_dynamic_fee
+        } else {
+            DynamicFeeParam {
+                variable_fee_control: self.variable_fee_control,
+                max_fee_rate: self.max_fee_rate,
+                volatility_decay_period: self.volatility_decay_period,
+            }
+        }
+    }
+
+    /// Writes back queued dynamic fee settings whose timelock has elapsed
+    pub fn apply_pending_dynamic_fee(&mut self, block_timestamp: u64) {
+        let dynamic_fee = self.dynamic_fee_param_at(block_timestamp);
+        self.variable_fee_control = dynamic_fee.variable_fee_control;
+        self.max_fee_rate = dynamic_fee.max_fee_rate;
+        self.volatility_decay_period = dynamic_fee.volatility_decay_period;
+        if self.pending_dynamic_fee_effective_time != 0
+            && block_timestamp >= self.pending_dynamic_fee_effective_time
+        {
+            self.pending_dynamic_fee = DynamicFeeParam::default();
+            self.pending_dynamic_fee_effective_time = 0;
+        }
+    }
+
+    /// Drops queued dynamic fee settings whose timelock has yet to elapse, returning them. None if
+    /// nothing is queued, settings whose timelock has elapsed are written back instead
+    pub fn cancel_pending_dynamic_fee(&mut self, block_timestamp: u64) -> Option<DynamicFeeParam> {
+        self.apply_pending_dynamic_fee(block_timestamp);
+        if self.pending_dynamic_fee_effective_time == 0 {
+            return None;
+        }
+        let pending_dynamic_fee = self.pending_dynamic_fee;
+        self.pending_dynamic_fee = DynamicFeeParam::default();
+        self.pending_dynamic_fee_effective_time = 0;
+        Some(pending_dynamic_fee)
+    }
+
+    /// Returns the dynamic fee in effect at `block_timestamp` for the given pool volatility, None
+    /// if the dynamic fee is disabled
+    pub fn dynamic_fee(&self, block_timestamp: u64, volatility: u64) -> Option<DynamicFee> {
+        let dynamic_fee = self.dynamic_fee_param_at(block_timestamp);
+        if dynamic_fee.variable_fee_control == 0 {
+            return None;
+        }
+        Some(DynamicFee {
+            volatility,
+            variable_fee_control: dynamic_fee.variable_fee_control,
+            max_fee_rate: dynamic_fee.max_fee_rate,
+        })
+    }
+
+    /// Records the quote mint of a config grown by `migrate_amm_config`, it can't be changed once
+    /// pools may be quoted in it
+    pub fn set_quote_mint(&mut self, quote_mint: Pubkey) -> Result<()> {
+        require_keys_eq!(self.quote_mint, Pubkey::default(), ErrorCode::NotApproved);
+        require_keys_neq!(quote_mint, Pubkey::default(), ErrorCode::InvalidQuoteMint);
+        self.quote_mint = quote_mint;
+        Ok(())
+    }
+
+    /// Returns the current value of the field targeted by `param`
+    pub fn get_param(&self, param: &AmmConfigParam) -> AmmConfigParam {
+        match param {
+            AmmConfigParam::TradeFeeRate(_) => AmmConfigParam::TradeFeeRate(self.trade_fee_rate),
+            AmmConfigParam::ProtocolFeeRate(_) => {
+                AmmConfigParam::ProtocolFeeRate(self.protocol_fee_rate)
+            }
+            AmmConfigParam::ProtocolFeeCollector(_) => {
+                AmmConfigParam::ProtocolFeeCollector(self.protocol_fee_collector)
+            }
+            AmmConfigParam::DisableCreatePool(_) => {
+                AmmConfigParam::DisableCreatePool(self.disable_create_pool)
+            }
+            AmmConfigParam::GraduationThreshold(_) => {
+                AmmConfigParam::GraduationThreshold(self.graduation_threshold)
+            }
+            AmmConfigParam::DynamicFee(_) => AmmConfigParam::DynamicFee(DynamicFeeParam {
+                variable_fee_control: self.variable_fee_control,
+                max_fee_rate: self.max_fee_rate,
+                volatility_decay_period: self.volatility_decay_period,
+            }),
+            AmmConfigParam::ReferralFeeRate(_) => {
+                AmmConfigParam::ReferralFeeRate(self.referral_fee_rate)
+            }
+            AmmConfigParam::QuoteMint(_) => AmmConfigParam::QuoteMint(self.quote_mint),
+        }
+    }
+}
+
+/// A single updatable field of the amm config with its new value
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AmmConfigParam {
+    /// The trade fee, denominated in hundredths of a bip (10^-6)
+    TradeFeeRate(u64),
+    /// The rate of protocol fee within the trade fee
+    ProtocolFeeRate(u64),
+    /// Address of the protocol fee owner
+    ProtocolFeeCollector(Pubkey),
+    /// Status to control if new pool can be create
+    DisableCreatePool(bool),
+    /// Real quote token reserves at which new pools graduate, 0 to disable graduation
+    GraduationThreshold(u64),
+    /// Volatility based fee charged on top of the trade fee
+    DynamicFee(DynamicFeeParam),
+    /// The share of the trade fee paid to the referrer of a swap
+    ReferralFeeRate(u64),
+    /// The mint every pool of this config is quoted in, can only be set once on configs grown
+    /// by `migrate_amm_config`
+    QuoteMint(Pubkey),
+}
+
+/// Dynamic fee settings of an amm config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DynamicFeeParam {
+    /// Share of a pool's recent volatility charged as fee, 0 to disable the dynamic fee
+    pub variable_fee_control: u64,
+    /// Cap of the trade fee rate including the variable fee
+    pub max_fee_rate: u64,
+    /// Seconds for a pool's volatility to decay to zero
+    pub volatility_decay_period: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    mod layout_test {
+        use super::*;
+        use crate::states::MAX_VOLATILITY;
+        use anchor_lang::Discriminator;
+
+        /// The amm config as created before the quote mint was added
+        #[allow(dead_code)]
+        struct AmmConfigV0 {
+            bump: u8,
+            disable_create_pool: bool,
+            index: u16,
+            trade_fee_rate: u64,
+            protocol_fee_rate: u64,
+            protocol_fee_collector: Pubkey,
+            padding: [u64; 16],
+        }
+
+        const _: () = assert!(8 + std::mem::size_of::<AmmConfigV0>() == AMM_CONFIG_V0_LEN);
+        // Migrated configs only ever grow
+        const _: () = assert!(AmmConfig::LEN > AMM_CONFIG_V0_LEN);
+
+        #[test]
+        fn largest_config_fits() {
+            let amm_config = AmmConfig {
+                curve_type: CurveType::LinearBonding {
+                    initial_price_x32: u64::MAX,
+                    scale_amount: u64::MAX,
+                },
+                ..Default::default()
+            };
+            assert!(8 + amm_config.try_to_vec().unwrap().len() <= AmmConfig::LEN);
+        }
+
+        #[test]
+        fn migrated_version_0_config_keeps_its_fields() {
+            let protocol_fee_collector = Pubkey::new_unique();
+            let mut data = AmmConfig::DISCRIMINATOR.to_vec();
+            // bump, disable_create_pool and index
+            data.extend_from_slice(&[254, 1, 3, 0]);
+            data.extend_from_slice(&2500u64.to_le_bytes());
+            data.extend_from_slice(&120_000u64.to_le_bytes());
+            data.extend_from_slice(protocol_fee_collector.as_ref());
+            // padding
+            data.resize(AMM_CONFIG_V0_LEN, 0);
+
+            // realloc zero fills the grown account
+            data.resize(AmmConfig::LEN, 0);
+            let amm_config = AmmConfig::try_deserialize(&mut data.as_slice()).unwrap();
+            assert_eq!(amm_config.bump, 254);
+            assert!(amm_config.disable_create_pool);
+            assert_eq!(amm_config.index, 3);
+            assert_eq!(amm_config.trade_fee_rate, 2500);
+            assert_eq!(amm_config.protocol_fee_rate, 120_000);
+            assert_eq!(amm_config.protocol_fee_collector, protocol_fee_collector);
+            assert_eq!(amm_config.quote_mint, Pubkey::default());
+            assert_eq!(amm_config.curve_type, CurveType::ConstantProduct);
+            assert_eq!(amm_config.graduation_threshold, 0);
+            assert_eq!(amm_config.trade_fee_rate_at(u64::MAX), 2500);
+            assert!(amm_config.dynamic_fee(u64::MAX, MAX_VOLATILITY).is_none());
+            assert_eq!(amm_config.referral_fee_rate, 0);
+        }
+    }
+
+    #[test]
+    fn quote_mint_is_set_once() {
+        let mut amm_config = AmmConfig::default();
+        assert!(amm_config.set_quote_mint(Pubkey::default()).is_err());
+
+        let quote_mint = Pubkey::new_unique();
+        amm_config.set_quote_mint(quote_mint).unwrap();
+        assert_eq!(amm_config.quote_mint, quote_mint);
+        assert!(amm_config.set_quote_mint(Pubkey::new_unique()).is_err());
+        assert_eq!(amm_config.quote_mint, quote_mint);
+    }
+
+    #[test]
+    fn dynamic_fee_is_timelocked() {
+        let mut amm_config = AmmConfig {
+            variable_fee_control: 100_000,
+            max_fee_rate: 15_000,
+            volatility_decay_period: 100,
+            ..Default::default()
+        };
+        let dynamic_fee = DynamicFeeParam {
+            variable_fee_control: 200_000,
+            max_fee_rate: 900_000,
+            volatility_decay_period: 200,
+        };
+        amm_config.pending_dynamic_fee = dynamic_fee;
+        amm_config.pending_dynamic_fee_effective_time = 1000;
+
+        amm_config.apply_pending_dynamic_fee(999);
+        assert_eq!(amm_config.dynamic_fee(999, 0).unwrap().max_fee_rate, 15_000);
+        assert_eq!(amm_config.dynamic_fee_param_at(1000), dynamic_fee);
+
+        amm_config.apply_pending_dynamic_fee(1000);
+        assert_eq!(amm_config.max_fee_rate, 900_000);
+        assert_eq!(amm_config.pending_dynamic_fee_effective_time, 0);
+        assert_eq!(amm_config.pending_dynamic_fee, DynamicFeeParam::default());
+    }
+
+    #[test]
+    fn cancel_pending_dynamic_fee() {
+        let dynamic_fee = DynamicFeeParam {
+            variable_fee_control: 200_000,
+            max_fee_rate: 900_000,
+            volatility_decay_period: 200,
+        };
+        let mut amm_config = AmmConfig {
+            max_fee_rate: 15_000,
+            pending_dynamic_fee: dynamic_fee,
+            pending_dynamic_fee_effective_time: 1000,
+            ..Default::default()
+        };
+
+        // already in effect
+        let mut applied = AmmConfig { ..amm_config };
+        assert_eq!(applied.cancel_pending_dynamic_fee(1000), None);
+        assert_eq!(applied.max_fee_rate, 900_000);
+
+        assert_eq!(
+            amm_config.cancel_pending_dynamic_fee(999),
+            Some(dynamic_fee)
+        );
+        assert_eq!(amm_config.pending_dynamic_fee_effective_time, 0);
+        assert_eq!(amm_config.pending_dynamic_fee, DynamicFeeParam::default());
+        assert_eq!(amm_config.dynamic_fee_param_at(1000).max_fee_rate, 15_000);
+        // nothing left to cancel
+        assert_eq!(amm_config.cancel_pending_dynamic_fee(999), None);
+    }
 }
@@ -1,3 +1,5 @@
+use crate::curve::CurveType;
+use crate::states::{AdminRole, AmmConfigParam, FeeSplitRecipient};
 use anchor_lang::prelude::*;
 
 #[event]
@@ -14,9 +16,152 @@ pub struct InitializePool {
 pub struct CollectFees {
     pub mint: Pubkey,
     pub creator_mint_fees: u64,
-    pub creator_usdc_fees: u64,
+    pub creator_quote_fees: u64,
     pub protocol_mint_fees: u64,
-    pub protocol_usdc_fees: u64,
+    pub protocol_quote_fees: u64,
+}
+
+/// Emitted when an amm config is created
+#[event]
+pub struct AmmConfigCreated {
+    pub amm_config: Pubkey,
+    pub index: u16,
+    pub trade_fee_rate: u64,
+    pub protocol_fee_rate: u64,
+    pub protocol_fee_collector: Pubkey,
+    pub quote_mint: Pubkey,
+    pub curve_type: CurveType,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when an amm config field is updated
+#[event]
+pub struct AmmConfigUpdated {
+    pub amm_config: Pubkey,
+    pub old_value: AmmConfigParam,
+    pub new_value: AmmConfigParam,
+    /// The timestamp the new value takes effect
+    pub effective_time: u64,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the status bits of a pool are updated
+#[event]
+pub struct PoolStatusUpdated {
+    pub pool_id: Pubkey,
+    pub old_status: u8,
+    pub new_status: u8,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the virtual offset of a pool is updated
+#[event]
+pub struct PoolOffsetUpdated {
+    pub pool_id: Pubkey,
+    pub old_off_set: u64,
+    pub new_off_set: u64,
+    /// The timestamp the new offset takes effect
+    pub effective_time: u64,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the admin registry is created
+#[event]
+pub struct AdminCreated {
+    pub owner: Pubkey,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the admin owner is proposed or accepted
+#[event]
+pub struct AdminOwnerUpdated {
+    pub old_owner: Pubkey,
+    pub new_owner: Pubkey,
+    /// true while the new owner has yet to accept
+    pub pending: bool,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when an admin role is assigned
+#[event]
+pub struct AdminRoleUpdated {
+    pub role: AdminRole,
+    pub old_holder: Pubkey,
+    pub new_holder: Pubkey,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the pool creator is proposed or accepted
+#[event]
+pub struct PoolCreatorUpdated {
+    pub pool_id: Pubkey,
+    pub old_creator: Pubkey,
+    pub new_creator: Pubkey,
+    /// true while the new creator has yet to accept
+    pub pending: bool,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when the pool creator changes how its fees are split
+#[event]
+pub struct CreatorFeeSplitUpdated {
+    pub pool_id: Pubkey,
+    pub old_recipients: Vec<FeeSplitRecipient>,
+    pub new_recipients: Vec<FeeSplitRecipient>,
+    pub signer: Pubkey,
+    pub slot: u64,
+}
+
+/// Emitted when creator fees are distributed to the fee split recipients
+#[event]
+pub struct CreatorFeesDistributed {
+    pub pool_id: Pubkey,
+    pub mint: Pubkey,
+    pub recipients: Vec<Pubkey>,
+    pub mint_amounts: Vec<u64>,
+    pub quote_amounts: Vec<u64>,
+}
+
+/// Emitted when a pool leaves its launch curve
+#[event]
+pub struct PoolGraduated {
+    pub pool_id: Pubkey,
+    pub mint: Pubkey,
+    /// virtual offset removed from the pool
+    pub off_set: u64,
+    /// pool mint amount not backed by real quote tokens, burned from the vault
+    pub burned_mint_amount: u64,
+    pub mint_amount: u64,
+    pub quote_amount: u64,
+    /// lp mint of the graduated pool
+    pub lp_mint: Pubkey,
+    /// locked liquidity of the graduated pool
+    pub liquidity: u64,
+}
+
+/// Emitted when deposit and withdraw
+#[event]
+pub struct LpChangeEvent {
+    pub mint: Pubkey,
+    pub lp_amount_before: u64,
+    /// pool mint amount without transfer fee
+    pub mint_amount: u64,
+    /// quote amount without transfer fee
+    pub quote_amount: u64,
+    pub mint_transfer_fee: u64,
+    pub quote_transfer_fee: u64,
+    /// virtual offset added on deposit or removed on withdraw
+    pub off_set_change: u64,
+    /// 0: deposit, 1: withdraw
+    pub change_type: u8,
 }
 
 /// Emitted when swap
@@ -33,4 +178,6 @@ pub struct SwapPriceEvent {
     pub output_amount: u64,
     pub buy: bool,
     pub user: Pubkey,
+    /// Owner of the referral token account paid a share of the trade fee, if any
+    pub referrer: Option<Pubkey>,
 }
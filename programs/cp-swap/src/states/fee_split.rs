@@ -0,0 +1,151 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+pub const FEE_SPLIT_SEED: &str = "fee_split";
+/// Maximum number of recipients sharing the creator fees of a pool
+pub const MAX_FEE_SPLIT_RECIPIENTS: usize = 8;
+/// The shares of a fee split add up to this
+pub const FEE_SPLIT_DENOMINATOR: u16 = 10_000;
+
+/// A recipient of the creator fees and its share in basis points
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeeSplitRecipient {
+    /// Owner of the token accounts receiving the fees
+    pub recipient: Pubkey,
+    /// Share of the creator fees, in basis points
+    pub share_bps: u16,
+}
+
+/// Splits the creator fees of a pool between several recipients
+#[account]
+#[derive(Default, Debug)]
+pub struct FeeSplitState {
+    /// Bump to identify PDA
+    pub bump: u8,
+    /// The pool whose creator fees are split
+    pub pool_id: Pubkey,
+    /// Number of recipients in use
+    pub recipient_count: u8,
+    /// The recipients, only the first `recipient_count` are in use
+    pub recipients: [FeeSplitRecipient; MAX_FEE_SPLIT_RECIPIENTS],
+    /// padding
+    pub padding: [u64; 8],
+}
+
+impl FeeSplitState {
+    pub const LEN: usize = 8 + std::mem::size_of::<FeeSplitState>();
+
+    /// Checks the recipients are distinct and their shares add up to `FEE_SPLIT_DENOMINATOR`
+    pub fn validate_recipients(recipients: &[FeeSplitRecipient]) -> Result<()> {
+        if recipients.is_empty() || recipients.len() > MAX_FEE_SPLIT_RECIPIENTS {
+            return err!(ErrorCode::InvalidFeeSplit);
+        }
+        let mut total_share_bps = 0u16;
+        for (index, fee_split_recipient) in recipients.iter().enumerate() {
+            if fee_split_recipient.share_bps == 0
+                || fee_split_recipient.recipient == Pubkey::default()
+                || recipients[..index]
+                    .iter()
+                    .any(|other| other.recipient == fee_split_recipient.recipient)
+            {
+                return err!(ErrorCode::InvalidFeeSplit);
+            }
+            total_share_bps = total_share_bps
+                .checked_add(fee_split_recipient.share_bps)
+                .ok_or(ErrorCode::InvalidFeeSplit)?;
+        }
+        require_eq!(
+            total_share_bps,
+            FEE_SPLIT_DENOMINATOR,
+            ErrorCode::InvalidFeeSplit
+        );
+        Ok(())
+    }
+
+    /// Replaces the recipients, no recipients clears the split
+    pub fn set_recipients(&mut self, recipients: &[FeeSplitRecipient]) -> Result<()> {
+        if !recipients.is_empty() {
+            Self::validate_recipients(recipients)?;
+        }
+        self.recipients = [FeeSplitRecipient::default(); MAX_FEE_SPLIT_RECIPIENTS];
+        self.recipients[..recipients.len()].copy_from_slice(recipients);
+        self.recipient_count = recipients.len() as u8;
+        Ok(())
+    }
+
+    /// The recipients in use
+    pub fn recipients(&self) -> &[FeeSplitRecipient] {
+        &self.recipients[..usize::from(self.recipient_count)]
+    }
+
+    /// Splits an amount pro rata to the shares, the rounding dust goes to the first recipient
+    pub fn split(&self, amount: u64) -> Vec<u64> {
+        let mut amounts: Vec<u64> = self
+            .recipients()
+            .iter()
+            .map(|fee_split_recipient| {
+                (u128::from(amount) * u128::from(fee_split_recipient.share_bps)
+                    / u128::from(FEE_SPLIT_DENOMINATOR)) as u64
+            })
+            .collect();
+        let dust = amount - amounts.iter().sum::<u64>();
+        if let Some(first) = amounts.first_mut() {
+            *first += dust;
+        }
+        amounts
+    }
+}
+
+#[cfg(test)]
+pub mod fee_split_test {
+    use super::*;
+
+    fn recipient(share_bps: u16) -> FeeSplitRecipient {
+        FeeSplitRecipient {
+            recipient: Pubkey::new_unique(),
+            share_bps,
+        }
+    }
+
+    #[test]
+    fn validate_recipients() {
+        assert!(FeeSplitState::validate_recipients(&[recipient(10_000)]).is_ok());
+        assert!(FeeSplitState::validate_recipients(&[recipient(6_000), recipient(4_000)]).is_ok());
+        assert!(FeeSplitState::validate_recipients(&[]).is_err());
+        assert!(FeeSplitState::validate_recipients(&[recipient(6_000), recipient(3_000)]).is_err());
+        assert!(FeeSplitState::validate_recipients(&[recipient(10_000), recipient(0)]).is_err());
+        assert!(FeeSplitState::validate_recipients(&[recipient(1_250); 8]).is_err());
+        assert!(FeeSplitState::validate_recipients(&[recipient(1_000); 10]).is_err());
+        assert!(FeeSplitState::validate_recipients(&[FeeSplitRecipient {
+            recipient: Pubkey::default(),
+            share_bps: 10_000
+        }])
+        .is_err());
+    }
+
+    #[test]
+    fn split_pro_rata() {
+        let mut fee_split_state = FeeSplitState::default();
+        fee_split_state
+            .set_recipients(&[recipient(5_000), recipient(3_333), recipient(1_667)])
+            .unwrap();
+        assert_eq!(fee_split_state.recipients().len(), 3);
+        assert_eq!(fee_split_state.split(10_000), vec![5_000, 3_333, 1_667]);
+        assert_eq!(fee_split_state.split(100), vec![51, 33, 16]);
+        assert_eq!(fee_split_state.split(0), vec![0, 0, 0]);
+    }
+
+    #[test]
+    fn no_recipients_clears_split() {
+        let mut fee_split_state = FeeSplitState::default();
+        fee_split_state
+            .set_recipients(&[recipient(6_000), recipient(4_000)])
+            .unwrap();
+        fee_split_state.set_recipients(&[]).unwrap();
+        assert!(fee_split_state.recipients().is_empty());
+        assert_eq!(
+            fee_split_state.recipients,
+            [FeeSplitRecipient::default(); MAX_FEE_SPLIT_RECIPIENTS]
+        );
+    }
+}
@@ -9,3 +9,12 @@ pub use events::*;
 
 pub mod oracle;
 pub use oracle::*;
+
+pub mod admin;
+pub use admin::*;
+
+pub mod user_purchase;
+pub use user_purchase::*;
+
+pub mod fee_split;
+pub use fee_split::*;
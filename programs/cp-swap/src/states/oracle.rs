@@ -109,6 +109,106 @@ impl ObservationState {
             self.observation_index = next_observation_index;
         }
     }
+
+    /// Returns the cumulative prices at `target_timestamp`, linearly interpolated between the
+    /// two observations surrounding it. Returns None if the target is outside the ring buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The ObservationState account to read from
+    /// * `target_timestamp` - The timestamp to read the cumulative prices at
+    ///
+    /// # Return
+    /// * `(cumulative_token_0_price_x32, cumulative_token_1_price_x32)`
+    ///
+    pub fn cumulative_price_at(&self, target_timestamp: u64) -> Option<(u128, u128)> {
+        if !self.initialized {
+            return None;
+        }
+        let newest_index = self.observation_index as usize;
+        if target_timestamp > self.observations[newest_index].block_timestamp {
+            return None;
+        }
+        // walk backwards from the most recent observation towards the oldest one
+        let mut index = newest_index;
+        for _ in 0..OBSERVATION_NUM {
+            let current = self.observations[index];
+            if current.block_timestamp == target_timestamp {
+                return Some((
+                    current.cumulative_token_0_price_x32,
+                    current.cumulative_token_1_price_x32,
+                ));
+            }
+            let previous_index = if index == 0 {
+                OBSERVATION_NUM - 1
+            } else {
+                index - 1
+            };
+            let previous = self.observations[previous_index];
+            if previous_index == newest_index
+                || previous.block_timestamp == 0
+                || previous.block_timestamp >= current.block_timestamp
+            {
+                return None;
+            }
+            if previous.block_timestamp < target_timestamp {
+                let elapsed = u128::from(target_timestamp - previous.block_timestamp);
+                let duration = u128::from(current.block_timestamp - previous.block_timestamp);
+                let interpolate = |previous_x32: u128, current_x32: u128| -> Option<u128> {
+                    Some(
+                        previous_x32.wrapping_add(
+                            current_x32
+                                .wrapping_sub(previous_x32)
+                                .checked_mul(elapsed)?
+                                .checked_div(duration)?,
+                        ),
+                    )
+                };
+                return Some((
+                    interpolate(
+                        previous.cumulative_token_0_price_x32,
+                        current.cumulative_token_0_price_x32,
+                    )?,
+                    interpolate(
+                        previous.cumulative_token_1_price_x32,
+                        current.cumulative_token_1_price_x32,
+                    )?,
+                ));
+            }
+            index = previous_index;
+        }
+        None
+    }
+
+    /// Computes the time-weighted average prices between two timestamps covered by the ring buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `self` - The ObservationState account to read from
+    /// * `start_timestamp` - The start of the averaging window
+    /// * `end_timestamp` - The end of the averaging window, must be greater than `start_timestamp`
+    ///
+    /// # Return
+    /// * `(token_0_price_x32, token_1_price_x32)` - The average prices, Q32.32
+    ///
+    pub fn get_twap(&self, start_timestamp: u64, end_timestamp: u64) -> Option<(u128, u128)> {
+        if end_timestamp <= start_timestamp {
+            return None;
+        }
+        let (start_token_0_price_x32, start_token_1_price_x32) =
+            self.cumulative_price_at(start_timestamp)?;
+        let (end_token_0_price_x32, end_token_1_price_x32) =
+            self.cumulative_price_at(end_timestamp)?;
+        let duration = u128::from(end_timestamp - start_timestamp);
+        Some((
+            end_token_0_price_x32
+                .wrapping_sub(start_token_0_price_x32)
+                .checked_div(duration)?,
+            end_token_1_price_x32
+                .wrapping_sub(start_token_1_price_x32)
+                .checked_div(duration)?,
+        ))
+    }
 }
 
 /// Returns the block timestamp truncated to 32 bits, i.e. mod 2**32
@@ -124,3 +224,66 @@ pub fn block_timestamp_mock() -> u64 {
         .unwrap()
         .as_secs()
 }
+
+#[cfg(test)]
+pub mod oracle_test {
+    use super::*;
+    use crate::states::pool::Q32;
+
+    #[test]
+    fn twap_of_constant_price_is_the_price() {
+        let mut observation_state = ObservationState::default();
+        let start = block_timestamp_mock();
+        for i in 0..10 {
+            observation_state.update(
+                start + i * OBSERVATION_UPDATE_DURATION_DEFAULT,
+                2 * Q32,
+                Q32 / 2,
+            );
+        }
+        let end = start + 9 * OBSERVATION_UPDATE_DURATION_DEFAULT;
+        assert_eq!(
+            observation_state.get_twap(start, end),
+            Some((2 * Q32, Q32 / 2))
+        );
+        // interpolated between observations
+        assert_eq!(
+            observation_state.get_twap(start + 7, end - 3),
+            Some((2 * Q32, Q32 / 2))
+        );
+    }
+
+    #[test]
+    fn twap_weights_prices_by_duration() {
+        let mut observation_state = ObservationState::default();
+        let start = block_timestamp_mock();
+        observation_state.update(start, 0, 0);
+        // price 1 held for 30 seconds, then price 4 held for 90 seconds
+        observation_state.update(start + 30, Q32, Q32);
+        observation_state.update(start + 120, 4 * Q32, 4 * Q32);
+        let (token_0_price_x32, token_1_price_x32) =
+            observation_state.get_twap(start, start + 120).unwrap();
+        assert_eq!(token_0_price_x32, (30 * Q32 + 90 * 4 * Q32) / 120);
+        assert_eq!(token_1_price_x32, token_0_price_x32);
+        assert_eq!(
+            observation_state.get_twap(start + 30, start + 120),
+            Some((4 * Q32, 4 * Q32))
+        );
+    }
+
+    #[test]
+    fn twap_outside_ring_buffer_is_none() {
+        let mut observation_state = ObservationState::default();
+        assert_eq!(observation_state.get_twap(0, 1), None);
+        let start = block_timestamp_mock();
+        for i in 0..(OBSERVATION_NUM as u64 + 10) {
+            observation_state.update(start + i * OBSERVATION_UPDATE_DURATION_DEFAULT, Q32, Q32);
+        }
+        let newest = start + (OBSERVATION_NUM as u64 + 9) * OBSERVATION_UPDATE_DURATION_DEFAULT;
+        let oldest = newest - (OBSERVATION_NUM as u64 - 1) * OBSERVATION_UPDATE_DURATION_DEFAULT;
+        assert_eq!(observation_state.get_twap(oldest, newest), Some((Q32, Q32)));
+        assert_eq!(observation_state.get_twap(oldest - 1, newest), None);
+        assert_eq!(observation_state.get_twap(oldest, newest + 1), None);
+        assert_eq!(observation_state.get_twap(newest, oldest), None);
+    }
+}
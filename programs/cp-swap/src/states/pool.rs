@@ -1,13 +1,29 @@
+use crate::curve::{CurveCalculator, CurveType, FEE_RATE_DENOMINATOR_VALUE};
+use crate::utils::U128;
 use anchor_lang::prelude::*;
 use anchor_spl::token_interface::Mint;
 use std::ops::{BitAnd, BitOr, BitXor};
 /// Seed to derive account address and signature
 pub const POOL_SEED: &str = "pool";
 pub const POOL_LP_MINT_SEED: &str = "pool_lp_mint";
+pub const POOL_GRADUATED_LP_MINT_SEED: &str = "pool_graduated_lp_mint";
 pub const POOL_VAULT_SEED: &str = "pool_vault";
 
+/// Layout version of newly created pools, see `migrate_pool_state` for older accounts
+pub const POOL_STATE_VERSION: u8 = 1;
+/// Size of a version 0 pool state account, before the fields of version 1 were appended
+pub const POOL_STATE_V0_LEN: usize = 8 + 7 * 32 + 3 + 7 * 8 + 4 * 8;
+// Migrated pools only ever grow
+const _: () = assert!(PoolState::LEN > POOL_STATE_V0_LEN);
+
 pub const Q32: u128 = (u32::MAX as u128) + 1; // 2^32
 
+/// Cap of a pool's volatility, a 10x price move
+pub const MAX_VOLATILITY: u64 = 10 * FEE_RATE_DENOMINATOR_VALUE;
+
+/// Number of times an exponential launch fee halves towards its end rate over the launch window
+pub const LAUNCH_FEE_HALVINGS: u64 = 10;
+
 pub enum PoolStatusBitIndex {
     Deposit,
     Withdraw,
@@ -20,8 +36,48 @@ pub enum PoolStatusBitFlag {
     Disable,
 }
 
+/// How the launch fee moves from its start rate to its end rate
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchFeeDecay {
+    /// Decreases by the same amount every second
+    Linear,
+    /// Halves the distance to the end rate every `1 / LAUNCH_FEE_HALVINGS` of the window
+    Exponential,
+}
+
+/// The fee bucket receiving the launch fee charged above the config trade fee
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LaunchFeeRecipient {
+    Creator,
+    Protocol,
+}
+
+/// Launch fee schedule applied from the pool open time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LaunchFeeParam {
+    /// Fee rate charged at the open time
+    pub start_fee_rate: u64,
+    /// Fee rate reached at the end of the window
+    pub end_fee_rate: u64,
+    /// Length of the launch window in seconds
+    pub duration: u64,
+    pub decay: LaunchFeeDecay,
+    pub recipient: LaunchFeeRecipient,
+}
+
+/// Per-wallet purchase cap applied from the pool open time
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PurchaseCapParam {
+    /// Maximum quote tokens a wallet can pay in buys, 0 if unlimited
+    pub max_quote_amount_in: u64,
+    /// Maximum pool mint a wallet can buy, 0 if unlimited
+    pub max_mint_amount_out: u64,
+    /// Length of the capped window in seconds
+    pub duration: u64,
+}
+
 #[account(zero_copy(unsafe))]
-#[repr(packed)]
+#[repr(C, packed)]
 #[derive(Default, Debug)]
 pub struct PoolState {
     /// Which config the pool belongs
@@ -31,7 +87,7 @@ pub struct PoolState {
     /// Token A
     pub token_mint_vault: Pubkey,
     /// Token B
-    pub token_usdc_vault: Pubkey,
+    pub token_quote_vault: Pubkey,
 
     /// Mint information for token A
     pub mint: Pubkey,
@@ -53,17 +109,74 @@ pub struct PoolState {
     pub mint_decimals: u8,
 
     pub protocol_fees_token_mint: u64,
-    pub protocol_fees_token_usdc: u64,
+    pub protocol_fees_token_quote: u64,
     pub creator_fees_token_mint: u64,
-    pub creator_fees_token_usdc: u64,
+    pub creator_fees_token_quote: u64,
 
     /// The timestamp allowed for swap in the pool.
     pub open_time: u64,
     /// recent epoch
     pub recent_epoch: u64,
     pub off_set: u64,
+
+    /// Layout version of the account, the fields below only exist from `POOL_STATE_VERSION` 1
+    /// and start out zeroed over the version 0 padding
+    pub version: u8,
+    pub quote_mint_decimals: u8,
+    /// lp mint decimals
+    pub lp_mint_decimals: u8,
+    /// Whether the pool has left the launch curve for a plain constant product pool
+    pub graduated: bool,
+    /// `LaunchFeeDecay` of the launch fee schedule
+    pub launch_fee_decay: u8,
+    /// `LaunchFeeRecipient` of the launch fee schedule
+    pub launch_fee_recipient: u8,
+    /// `CurveType` discriminator of the invariant the pool trades on
+    pub curve_type: u8,
+    /// Whether the creator fees are only paid out through the pool fee split
+    pub creator_fee_split: bool,
+
+    /// Mint information for the quote token, recorded from the amm config at creation
+    pub quote_mint: Pubkey,
+    /// quote token program
+    pub quote_token_program: Pubkey,
+    /// Pool tokens are issued when liquidity is deposited
+    pub lp_mint: Pubkey,
+
+    /// True circulating supply without burns and lock ups
+    pub lp_supply: u64,
+    /// The queued virtual offset, applied once `pending_off_set_effective_time` is reached
+    pub pending_off_set: u64,
+    /// The timestamp the queued virtual offset takes effect, 0 if nothing is queued
+    pub pending_off_set_effective_time: u64,
+    /// Real quote token reserves at which the pool graduates, 0 if the pool never graduates
+    pub graduation_threshold: u64,
+    /// Fee rate charged at the open time, replacing the config trade fee during the launch window
+    pub launch_fee_start_rate: u64,
+    /// Fee rate reached at the end of the launch window
+    pub launch_fee_end_rate: u64,
+    /// Length of the launch window in seconds, 0 if the pool has no launch fee
+    pub launch_fee_duration: u64,
+    /// Maximum quote tokens a wallet can pay in buys during the purchase cap window, 0 if unlimited
+    pub purchase_cap_quote_amount_in: u64,
+    /// Maximum pool mint a wallet can buy during the purchase cap window, 0 if unlimited
+    pub purchase_cap_mint_amount_out: u64,
+    /// Length of the purchase cap window in seconds, 0 if buys are not capped
+    pub purchase_cap_duration: u64,
+    /// Parameters of the curve type, e.g. the amplification coefficient of a stable swap curve
+    pub curve_parameters: [u64; 2],
+    /// Accumulated relative price movement of recent swaps, denominated in hundredths of a bip
+    /// (10^-6), decaying to zero over the config volatility decay period
+    pub volatility_accumulator: u64,
+    /// The timestamp the volatility accumulator was last updated
+    pub volatility_update_time: u64,
+    /// Address proposed as the next pool creator, must accept to take over
+    pub pending_pool_creator: Pubkey,
+    /// Set while a flash swap calls back its program, every pool instruction rejects it
+    pub locked: bool,
+    pub padding1: [u8; 7],
     /// padding for future updates
-    pub padding: [u64; 4],
+    pub padding: [u64; 3],
 }
 
 impl PoolState {
@@ -72,32 +185,251 @@ impl PoolState {
     pub fn initialize(
         &mut self,
         off_set: u64,
+        graduation_threshold: u64,
+        launch_fee: Option<LaunchFeeParam>,
+        purchase_cap: Option<PurchaseCapParam>,
+        curve_type: CurveType,
         auth_bump: u8,
         open_time: u64,
         pool_creator: Pubkey,
         amm_config: Pubkey,
         token_mint_vault: Pubkey,
-        token_usdc_vault: Pubkey,
+        token_quote_vault: Pubkey,
         mint: &InterfaceAccount<Mint>,
+        quote_mint: &InterfaceAccount<Mint>,
+        lp_mint: &InterfaceAccount<Mint>,
         observation_key: Pubkey,
     ) {
         self.off_set = off_set;
         self.amm_config = amm_config.key();
         self.pool_creator = pool_creator.key();
         self.token_mint_vault = token_mint_vault;
-        self.token_usdc_vault = token_usdc_vault;
+        self.token_quote_vault = token_quote_vault;
         self.mint = mint.key();
+        self.quote_mint = quote_mint.key();
         self.mint_token_program = *mint.to_account_info().owner;
+        self.quote_token_program = *quote_mint.to_account_info().owner;
         self.observation_key = observation_key;
+        self.lp_mint = lp_mint.key();
         self.auth_bump = auth_bump;
         self.mint_decimals = mint.decimals;
+        self.quote_mint_decimals = quote_mint.decimals;
+        self.lp_mint_decimals = lp_mint.decimals;
+        self.lp_supply = 0;
         self.protocol_fees_token_mint = 0;
-        self.protocol_fees_token_usdc = 0;
+        self.protocol_fees_token_quote = 0;
         self.creator_fees_token_mint = 0;
-        self.creator_fees_token_usdc = 0;
+        self.creator_fees_token_quote = 0;
         self.open_time = open_time;
         self.recent_epoch = Clock::get().unwrap().epoch;
-        self.padding = [0u64; 4];
+        self.pending_off_set = 0;
+        self.pending_off_set_effective_time = 0;
+        self.graduation_threshold = graduation_threshold;
+        self.graduated = false;
+        self.creator_fee_split = false;
+        match launch_fee {
+            Some(launch_fee) => {
+                self.launch_fee_start_rate = launch_fee.start_fee_rate;
+                self.launch_fee_end_rate = launch_fee.end_fee_rate;
+                self.launch_fee_duration = launch_fee.duration;
+                self.launch_fee_decay = launch_fee.decay as u8;
+                self.launch_fee_recipient = launch_fee.recipient as u8;
+            }
+            None => {
+                self.launch_fee_start_rate = 0;
+                self.launch_fee_end_rate = 0;
+                self.launch_fee_duration = 0;
+                self.launch_fee_decay = 0;
+                self.launch_fee_recipient = 0;
+            }
+        }
+        match purchase_cap {
+            Some(purchase_cap) => {
+                self.purchase_cap_quote_amount_in = purchase_cap.max_quote_amount_in;
+                self.purchase_cap_mint_amount_out = purchase_cap.max_mint_amount_out;
+                self.purchase_cap_duration = purchase_cap.duration;
+            }
+            None => {
+                self.purchase_cap_quote_amount_in = 0;
+                self.purchase_cap_mint_amount_out = 0;
+                self.purchase_cap_duration = 0;
+            }
+        }
+        self.curve_type = curve_type.discriminator();
+        self.curve_parameters = curve_type.parameters();
+        self.volatility_accumulator = 0;
+        self.volatility_update_time = 0;
+        self.pending_pool_creator = Pubkey::default();
+        self.version = POOL_STATE_VERSION;
+        self.locked = false;
+        self.padding1 = [0u8; 7];
+        self.padding = [0u64; 3];
+    }
+
+    /// Records the fields of `POOL_STATE_VERSION` 1 a version 0 pool can't leave zeroed. The
+    /// pool keeps no lp mint, so deposits and withdrawals stay disabled
+    pub fn migrate_from_v0(
+        &mut self,
+        quote_mint: Pubkey,
+        quote_token_program: Pubkey,
+        quote_mint_decimals: u8,
+        observation_key: Pubkey,
+    ) {
+        self.version = POOL_STATE_VERSION;
+        self.quote_mint = quote_mint;
+        self.quote_token_program = quote_token_program;
+        self.quote_mint_decimals = quote_mint_decimals;
+        self.observation_key = observation_key;
+    }
+
+    /// Returns false for pools migrated from version 0, which have no lp mint to deposit into
+    pub fn has_lp_mint(&self) -> bool {
+        self.lp_mint != Pubkey::default()
+    }
+
+    /// Applies a queued virtual offset whose timelock has elapsed
+    pub fn apply_pending_off_set(&mut self, block_timestamp: u64) {
+        if self.pending_off_set_effective_time != 0
+            && block_timestamp >= self.pending_off_set_effective_time
+        {
+            self.off_set = self.pending_off_set;
+            self.pending_off_set = 0;
+            self.pending_off_set_effective_time = 0;
+        }
+    }
+
+    /// Returns true while the pool trades on its launch curve and has yet to graduate
+    pub fn in_launch_phase(&self) -> bool {
+        self.graduation_threshold != 0 && !self.graduated
+    }
+
+    /// Returns true once the real quote reserves crossed the graduation threshold
+    pub fn graduation_reached(&self, quote_vault: u64) -> bool {
+        self.in_launch_phase()
+            && quote_vault
+                .checked_sub(self.protocol_fees_token_quote + self.creator_fees_token_quote)
+                .unwrap()
+                >= self.graduation_threshold
+    }
+
+    /// Returns the pool mint amount kept by a graduating pool, the amount burned from the vault
+    /// and the liquidity of the graduated pool. Without the virtual offset, only the mint amount
+    /// backed by real quote tokens keeps the price
+    pub fn graduation_amounts(&self, mint_vault: u64, quote_vault: u64) -> (u64, u64, u64) {
+        let (total_mint_amount, total_quote_amount) = self.lp_vault_amount(mint_vault, quote_vault);
+        let graduated_mint_amount = if self.curve_type() == CurveType::ConstantProduct {
+            // price = (quote + off_set) / mint = quote / graduated_mint
+            u64::try_from(
+                u128::from(total_quote_amount)
+                    .checked_mul(u128::from(total_mint_amount))
+                    .unwrap()
+                    .checked_div(
+                        u128::from(total_quote_amount)
+                            .checked_add(u128::from(self.off_set))
+                            .unwrap(),
+                    )
+                    .unwrap(),
+            )
+            .unwrap()
+        } else {
+            // Other curves graduate at their spot price
+            let (_, token_1_price_x32, _) = self.token_price_x32(mint_vault, quote_vault);
+            u64::try_from(
+                u128::from(total_quote_amount)
+                    .checked_mul(token_1_price_x32)
+                    .unwrap()
+                    .checked_div(Q32)
+                    .unwrap()
+                    .min(u128::from(total_mint_amount)),
+            )
+            .unwrap()
+        };
+        let burn_amount = total_mint_amount
+            .checked_sub(graduated_mint_amount)
+            .unwrap();
+        let liquidity = U128::from(graduated_mint_amount)
+            .checked_mul(total_quote_amount.into())
+            .unwrap()
+            .integer_sqrt()
+            .as_u64();
+        (graduated_mint_amount, burn_amount, liquidity)
+    }
+
+    /// Returns the launch fee rate in effect, None once the launch window is over
+    pub fn launch_fee_rate_at(&self, block_timestamp: u64) -> Option<u64> {
+        let duration = self.launch_fee_duration;
+        let elapsed = block_timestamp.saturating_sub(self.open_time);
+        if duration == 0 || elapsed >= duration {
+            return None;
+        }
+        let range = u128::from(
+            self.launch_fee_start_rate
+                .saturating_sub(self.launch_fee_end_rate),
+        );
+        let excess = if self.launch_fee_decay == LaunchFeeDecay::Exponential as u8 {
+            // Interpolate linearly between two halvings
+            let scaled = u128::from(elapsed) * u128::from(LAUNCH_FEE_HALVINGS);
+            let shift = scaled / u128::from(duration);
+            let remainder = scaled % u128::from(duration);
+            let high = range >> shift;
+            let low = range >> (shift + 1);
+            high - (high - low) * remainder / u128::from(duration)
+        } else {
+            range * u128::from(duration - elapsed) / u128::from(duration)
+        };
+        Some(self.launch_fee_end_rate + u64::try_from(excess).unwrap())
+    }
+
+    /// Returns true if the launch fee above the config trade fee goes to the protocol bucket
+    pub fn launch_fee_to_protocol(&self) -> bool {
+        self.launch_fee_recipient == LaunchFeeRecipient::Protocol as u8
+    }
+
+    /// Returns true while buys are capped per wallet
+    pub fn purchase_cap_active(&self, block_timestamp: u64) -> bool {
+        self.purchase_cap_duration != 0
+            && block_timestamp < self.open_time.saturating_add(self.purchase_cap_duration)
+    }
+
+    /// Returns the volatility accumulator decayed linearly to `block_timestamp`
+    pub fn volatility_at(&self, block_timestamp: u64, decay_period: u64) -> u64 {
+        let elapsed = block_timestamp.saturating_sub(self.volatility_update_time);
+        if elapsed >= decay_period {
+            return 0;
+        }
+        u64::try_from(
+            u128::from(self.volatility_accumulator) * u128::from(decay_period - elapsed)
+                / u128::from(decay_period),
+        )
+        .unwrap()
+    }
+
+    /// Adds the relative price movement of a swap to the decayed volatility accumulator
+    pub fn update_volatility(
+        &mut self,
+        price_before_x32: u128,
+        price_after_x32: u128,
+        block_timestamp: u64,
+        decay_period: u64,
+    ) {
+        let price_movement = (price_after_x32.abs_diff(price_before_x32)
+            * u128::from(FEE_RATE_DENOMINATOR_VALUE))
+        .checked_div(price_before_x32)
+        .unwrap_or(u128::from(MAX_VOLATILITY));
+        let volatility = u128::from(self.volatility_at(block_timestamp, decay_period))
+            .saturating_add(price_movement)
+            .min(u128::from(MAX_VOLATILITY));
+        self.volatility_accumulator = volatility as u64;
+        self.volatility_update_time = block_timestamp;
+    }
+
+    /// The invariant the pool trades on
+    pub fn curve_type(&self) -> CurveType {
+        CurveType::from_discriminator(self.curve_type, self.curve_parameters).unwrap()
+    }
+
+    pub fn curve_calculator(&self) -> CurveCalculator {
+        CurveCalculator::new(self.curve_type())
     }
 
     pub fn set_status(&mut self, status: u8) {
@@ -120,24 +452,61 @@ impl PoolState {
         self.status.bitand(status) == 0
     }
 
-    pub fn vault_amount_without_fee(&self, mint_vault: u64, usdc_vault: u64) -> (u64, u64) {
+    pub fn vault_amount_without_fee(&self, mint_vault: u64, quote_vault: u64) -> (u64, u64) {
         (
             mint_vault
                 .checked_sub(self.protocol_fees_token_mint + self.creator_fees_token_mint)
                 .unwrap(),
-            usdc_vault
+            quote_vault
                 .checked_add(self.off_set)
                 .unwrap()
-                .checked_sub(self.protocol_fees_token_usdc + self.creator_fees_token_usdc)
+                .checked_sub(self.protocol_fees_token_quote + self.creator_fees_token_quote)
+                .unwrap(),
+        )
+    }
+
+    /// Vault amounts owned by liquidity providers, excluding accrued fees and the virtual offset
+    pub fn lp_vault_amount(&self, mint_vault: u64, quote_vault: u64) -> (u64, u64) {
+        (
+            mint_vault
+                .checked_sub(self.protocol_fees_token_mint + self.creator_fees_token_mint)
+                .unwrap(),
+            quote_vault
+                .checked_sub(self.protocol_fees_token_quote + self.creator_fees_token_quote)
+                .unwrap(),
+        )
+    }
+
+    /// Share of the virtual offset backing the given amount of lp tokens
+    pub fn lp_tokens_to_off_set(&self, lp_token_amount: u64) -> u64 {
+        self.lp_share(lp_token_amount, self.off_set)
+    }
+
+    /// Share of the queued virtual offset backing the given amount of lp tokens
+    pub fn lp_tokens_to_pending_off_set(&self, lp_token_amount: u64) -> u64 {
+        self.lp_share(lp_token_amount, self.pending_off_set)
+    }
+
+    fn lp_share(&self, lp_token_amount: u64, amount: u64) -> u64 {
+        u64::try_from(
+            u128::from(lp_token_amount)
+                .checked_mul(u128::from(amount))
+                .unwrap()
+                .checked_div(u128::from(self.lp_supply))
                 .unwrap(),
         )
+        .unwrap()
     }
 
     pub fn token_price_x32(&self, vault_0: u64, vault_1: u64) -> (u128, u128, u64) {
         let (token_0_amount, token_1_amount) = self.vault_amount_without_fee(vault_0, vault_1);
+        let (token_0_price_x32, token_1_price_x32) = self
+            .curve_calculator()
+            .spot_price_x32(u128::from(token_0_amount), u128::from(token_1_amount))
+            .unwrap();
         (
-            token_1_amount as u128 * Q32 as u128 / token_0_amount as u128,
-            token_0_amount as u128 * Q32 as u128 / token_1_amount as u128,
+            token_0_price_x32,
+            token_1_price_x32,
             token_1_amount.checked_sub(self.off_set).unwrap(),
         )
     }
@@ -228,4 +597,261 @@ pub mod pool_test {
             );
         }
     }
+
+    mod layout_test {
+        use super::*;
+        use crate::curve::{SwapParams, TradeDirection};
+
+        #[test]
+        fn version_0_fields_keep_their_offsets() {
+            assert_eq!(std::mem::offset_of!(PoolState, token_quote_vault), 3 * 32);
+            assert_eq!(std::mem::offset_of!(PoolState, off_set), 7 * 32 + 3 + 6 * 8);
+            // the fields of version 1 start where the version 0 padding was
+            assert_eq!(
+                8 + std::mem::offset_of!(PoolState, version),
+                POOL_STATE_V0_LEN - 4 * 8
+            );
+        }
+
+        /// Data of a version 0 pool account after its discriminator
+        fn version_0_data(mint_vault: Pubkey, quote_vault: Pubkey, off_set: u64) -> Vec<u8> {
+            let mut data = Vec::with_capacity(POOL_STATE_V0_LEN - 8);
+            // amm_config, pool_creator, vaults, mint and mint_token_program
+            for key in [
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                mint_vault,
+                quote_vault,
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+            ] {
+                data.extend_from_slice(key.as_ref());
+            }
+            // observation_key, unused by version 0
+            data.extend_from_slice(Pubkey::default().as_ref());
+            // auth_bump, status and mint_decimals
+            data.extend_from_slice(&[255, 0, 6]);
+            // fees, open_time and recent_epoch
+            data.extend_from_slice(&[0u8; 6 * 8]);
+            data.extend_from_slice(&off_set.to_le_bytes());
+            // padding
+            data.extend_from_slice(&[0u8; 4 * 8]);
+            assert_eq!(8 + data.len(), POOL_STATE_V0_LEN);
+            data
+        }
+
+        #[test]
+        fn migrated_version_0_pool_swaps() {
+            let mint_vault = Pubkey::new_unique();
+            let quote_vault = Pubkey::new_unique();
+            let observation_key = Pubkey::new_unique();
+            let mut data = version_0_data(mint_vault, quote_vault, 1_000_000);
+
+            // realloc zero fills the grown account
+            data.resize(PoolState::LEN - 8, 0);
+            let pool_state = bytemuck::from_bytes_mut::<PoolState>(&mut data);
+            pool_state.migrate_from_v0(
+                Pubkey::new_unique(),
+                Pubkey::new_unique(),
+                9,
+                observation_key,
+            );
+
+            assert_eq!({ pool_state.version }, POOL_STATE_VERSION);
+            assert_eq!({ pool_state.token_mint_vault }, mint_vault);
+            assert_eq!({ pool_state.token_quote_vault }, quote_vault);
+            // swaps require the observation account recorded by the migration
+            assert_eq!({ pool_state.observation_key }, observation_key);
+            assert!(!pool_state.has_lp_mint());
+            assert!(!pool_state.in_launch_phase());
+            assert_eq!(pool_state.curve_type(), CurveType::ConstantProduct);
+            assert_eq!(pool_state.launch_fee_rate_at(u64::MAX), None);
+
+            // buy with 1000 quote tokens from a pool holding 1_000_000 pool mint and no real quote
+            let (total_mint_amount, total_quote_amount) =
+                pool_state.vault_amount_without_fee(1_000_000, 0);
+            let result = pool_state
+                .curve_calculator()
+                .swap_base_input(
+                    1000,
+                    &SwapParams {
+                        swap_source_amount: u128::from(total_quote_amount),
+                        swap_destination_amount: u128::from(total_mint_amount),
+                        trade_fee_rate: 2500,
+                        protocol_fee_rate: 0,
+                        referral_fee_rate: 0,
+                        launch_fee: None,
+                        trade_direction: TradeDirection::OneForZero,
+                    },
+                )
+                .unwrap();
+            assert_eq!(result.source_amount_swapped, 1000);
+            assert_eq!(result.destination_amount_swapped, 996);
+        }
+    }
+
+    mod volatility_test {
+        use super::*;
+
+        #[test]
+        fn volatility_decays_linearly() {
+            let mut pool_state = PoolState::default();
+            pool_state.update_volatility(100 * Q32, 110 * Q32, 1000, 100);
+            assert_eq!({ pool_state.volatility_accumulator }, 100_000);
+            assert_eq!(pool_state.volatility_at(1000, 100), 100_000);
+            assert_eq!(pool_state.volatility_at(1050, 100), 50_000);
+            assert_eq!(pool_state.volatility_at(1100, 100), 0);
+
+            // a move back down adds up with what is left of the previous one
+            pool_state.update_volatility(110 * Q32, 99 * Q32, 1050, 100);
+            assert_eq!({ pool_state.volatility_accumulator }, 150_000);
+            assert_eq!({ pool_state.volatility_update_time }, 1050);
+        }
+
+        #[test]
+        fn volatility_is_capped() {
+            let mut pool_state = PoolState::default();
+            pool_state.update_volatility(Q32, 100 * Q32, 1000, 100);
+            assert_eq!({ pool_state.volatility_accumulator }, MAX_VOLATILITY);
+        }
+    }
+
+    mod launch_fee_test {
+        use super::*;
+
+        fn launch_pool(decay: LaunchFeeDecay) -> PoolState {
+            PoolState {
+                open_time: 1000,
+                launch_fee_start_rate: 500_000,
+                launch_fee_end_rate: 2_500,
+                launch_fee_duration: 100,
+                launch_fee_decay: decay as u8,
+                ..Default::default()
+            }
+        }
+
+        #[test]
+        fn no_launch_fee() {
+            let pool_state = PoolState::default();
+            assert_eq!(pool_state.launch_fee_rate_at(0), None);
+        }
+
+        #[test]
+        fn linear_launch_fee() {
+            let pool_state = launch_pool(LaunchFeeDecay::Linear);
+            assert_eq!(pool_state.launch_fee_rate_at(1000), Some(500_000));
+            assert_eq!(pool_state.launch_fee_rate_at(1050), Some(251_250));
+            assert_eq!(pool_state.launch_fee_rate_at(1099), Some(7_475));
+            assert_eq!(pool_state.launch_fee_rate_at(1100), None);
+        }
+
+        #[test]
+        fn launch_fee_never_below_end_rate() {
+            let mut pool_state = launch_pool(LaunchFeeDecay::Linear);
+            pool_state.launch_fee_start_rate = 1_000;
+            assert_eq!(pool_state.launch_fee_rate_at(1000), Some(2_500));
+            assert_eq!(pool_state.launch_fee_rate_at(1099), Some(2_500));
+        }
+
+        #[test]
+        fn exponential_launch_fee() {
+            let pool_state = launch_pool(LaunchFeeDecay::Exponential);
+            assert_eq!(pool_state.launch_fee_rate_at(1000), Some(500_000));
+            // one halving
+            assert_eq!(pool_state.launch_fee_rate_at(1010), Some(251_250));
+            // half way to the second halving
+            assert_eq!(pool_state.launch_fee_rate_at(1015), Some(189_063));
+            let mut previous = u64::MAX;
+            for block_timestamp in 1000..1100 {
+                let fee_rate = pool_state.launch_fee_rate_at(block_timestamp).unwrap();
+                assert!(fee_rate <= previous);
+                assert!(fee_rate >= 2_500);
+                previous = fee_rate;
+            }
+            assert_eq!(pool_state.launch_fee_rate_at(1100), None);
+        }
+    }
+
+    mod graduation_test {
+        use super::*;
+
+        #[test]
+        fn constant_product_graduation_drops_the_offset() {
+            let pool_state = PoolState {
+                off_set: 1_500_000,
+                creator_fees_token_mint: 100,
+                protocol_fees_token_quote: 50,
+                ..Default::default()
+            };
+            // price = (500_000 + 1_500_000) / 1_000_000 = 500_000 / 250_000
+            assert_eq!(
+                pool_state.graduation_amounts(1_000_100, 500_050),
+                (250_000, 750_000, 353_553)
+            );
+        }
+
+        #[test]
+        fn other_curves_graduate_at_their_spot_price() {
+            let curve_type = CurveType::Weighted {
+                weight_0: 80,
+                weight_1: 20,
+            };
+            let pool_state = PoolState {
+                curve_type: curve_type.discriminator(),
+                curve_parameters: curve_type.parameters(),
+                creator_fees_token_mint: 100,
+                protocol_fees_token_quote: 50,
+                ..Default::default()
+            };
+            // price = (500_000 / 20) / (1_000_000 / 80) = 500_000 / 250_000
+            assert_eq!(
+                pool_state.graduation_amounts(1_000_100, 500_050),
+                (250_000, 750_000, 353_553)
+            );
+
+            // the graduated mint amount never exceeds the reserves
+            let curve_type = CurveType::Weighted {
+                weight_0: 20,
+                weight_1: 80,
+            };
+            let pool_state = PoolState {
+                curve_type: curve_type.discriminator(),
+                curve_parameters: curve_type.parameters(),
+                ..Default::default()
+            };
+            assert_eq!(
+                pool_state.graduation_amounts(1_000_000, 500_000),
+                (1_000_000, 0, 707_106)
+            );
+        }
+    }
+
+    mod pending_off_set_test {
+        use super::*;
+
+        #[test]
+        fn apply_pending_off_set_after_timelock() {
+            let mut pool_state = PoolState {
+                off_set: 100,
+                ..Default::default()
+            };
+
+            // nothing queued
+            pool_state.apply_pending_off_set(u64::MAX);
+            assert_eq!({ pool_state.off_set }, 100);
+
+            pool_state.pending_off_set = 200;
+            pool_state.pending_off_set_effective_time = 1000;
+
+            // still locked
+            pool_state.apply_pending_off_set(999);
+            assert_eq!({ pool_state.off_set }, 100);
+            assert_eq!({ pool_state.pending_off_set_effective_time }, 1000);
+
+            pool_state.apply_pending_off_set(1000);
+            assert_eq!({ pool_state.off_set }, 200);
+            assert_eq!({ pool_state.pending_off_set }, 0);
+            assert_eq!({ pool_state.pending_off_set_effective_time }, 0);
+        }
+    }
 }
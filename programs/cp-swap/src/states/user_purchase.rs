@@ -0,0 +1,63 @@
+use crate::error::ErrorCode;
+use anchor_lang::prelude::*;
+
+pub const USER_PURCHASE_SEED: &str = "user_purchase";
+
+/// Cumulative buys of a wallet in a pool, tracked while the pool purchase cap applies
+#[account]
+#[derive(Default, Debug)]
+pub struct UserPurchaseState {
+    /// The pool the purchases were made in
+    pub pool_id: Pubkey,
+    /// The wallet making the purchases
+    pub owner: Pubkey,
+    /// Quote tokens paid for the pool mint
+    pub quote_amount_in: u64,
+    /// Pool mint bought
+    pub mint_amount_out: u64,
+    /// padding
+    pub padding: [u64; 4],
+}
+
+impl UserPurchaseState {
+    pub const LEN: usize = 8 + std::mem::size_of::<UserPurchaseState>();
+
+    /// Adds a buy to the cumulative purchases, a cap of 0 is unlimited
+    pub fn add_purchase(
+        &mut self,
+        quote_amount_in: u64,
+        mint_amount_out: u64,
+        max_quote_amount_in: u64,
+        max_mint_amount_out: u64,
+    ) -> Result<()> {
+        self.quote_amount_in = self.quote_amount_in.checked_add(quote_amount_in).unwrap();
+        self.mint_amount_out = self.mint_amount_out.checked_add(mint_amount_out).unwrap();
+        if (max_quote_amount_in != 0 && self.quote_amount_in > max_quote_amount_in)
+            || (max_mint_amount_out != 0 && self.mint_amount_out > max_mint_amount_out)
+        {
+            return err!(ErrorCode::PurchaseCapExceeded);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+pub mod user_purchase_test {
+    use super::*;
+
+    #[test]
+    fn add_purchase_up_to_cap() {
+        let mut user_purchase_state = UserPurchaseState::default();
+        user_purchase_state.add_purchase(60, 1000, 100, 0).unwrap();
+        user_purchase_state.add_purchase(40, 1000, 100, 0).unwrap();
+        assert_eq!(user_purchase_state.quote_amount_in, 100);
+        assert_eq!(user_purchase_state.mint_amount_out, 2000);
+        assert!(user_purchase_state.add_purchase(1, 0, 100, 0).is_err());
+
+        let mut user_purchase_state = UserPurchaseState::default();
+        user_purchase_state
+            .add_purchase(u64::MAX / 2, 500, 0, 500)
+            .unwrap();
+        assert!(user_purchase_state.add_purchase(0, 1, 0, 500).is_err());
+    }
+}
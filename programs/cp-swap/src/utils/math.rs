@@ -45,3 +45,119 @@ impl CheckedCeilDiv for u128 {
         Some((quotient, rhs))
     }
 }
+
+/// 1 as a Q64.64 fixed point number
+pub const ONE_X64: u128 = 1 << 64;
+/// ln(2) as a Q64.64 fixed point number
+pub const LN2_X64: i128 = 12_786_308_645_202_655_660;
+
+/// Natural logarithm of a Q64.64 fixed point number, as a signed Q64.64 fixed point number
+pub fn ln_x64(x: u128) -> Option<i128> {
+    if x == 0 {
+        return None;
+    }
+    // x = 2^k * m with m in [1, 2)
+    let k = (127 - x.leading_zeros()) as i128 - 64;
+    let m = if k >= 0 { x >> k } else { x << -k };
+
+    // ln(m) = 2 * atanh(z) = 2 * (z + z^3 / 3 + z^5 / 5 + ...) with z = (m - 1) / (m + 1) < 1 / 3
+    let z = (m - ONE_X64) * ONE_X64 / (m + ONE_X64);
+    let z_squared = (z * z) >> 64;
+    let mut sum = 0u128;
+    let mut term = z;
+    let mut i = 0u128;
+    while term > 0 {
+        sum += term / (2 * i + 1);
+        term = (term * z_squared) >> 64;
+        i += 1;
+    }
+    k.checked_mul(LN2_X64)?.checked_add(2 * sum as i128)
+}
+
+/// Exponential of a signed Q64.64 fixed point number, as a Q64.64 fixed point number
+pub fn exp_x64(y: i128) -> Option<U256> {
+    // y = k * ln(2) + r with r in [0, ln(2))
+    let k = y.div_euclid(LN2_X64);
+    let r = y.rem_euclid(LN2_X64) as u128;
+
+    // e^r = 1 + r + r^2 / 2! + ...
+    let mut sum = ONE_X64;
+    let mut term = ONE_X64;
+    let mut i = 1u128;
+    while term > 0 {
+        term = term * r / ONE_X64 / i;
+        sum += term;
+        i += 1;
+    }
+    if k >= 128 {
+        None
+    } else if k >= 0 {
+        Some(U256::from(sum) << k as usize)
+    } else if k > -128 {
+        Some(U256::from(sum >> -k))
+    } else {
+        Some(U256::zero())
+    }
+}
+
+/// `base ^ (numerator / denominator)` of a Q64.64 fixed point number, as a Q64.64 fixed point
+/// number. Rounded down, with a relative error below 2^-50.
+pub fn pow_x64(base: u128, numerator: u64, denominator: u64) -> Option<U256> {
+    exp_x64(
+        ln_x64(base)?
+            .checked_mul(i128::from(numerator))?
+            .div_euclid(i128::from(denominator)),
+    )
+}
+
+/// Narrows a `U256` to a `u128`, or `None` if it doesn't fit
+pub fn to_u128(value: U256) -> Option<u128> {
+    if value > U256::from(u128::MAX) {
+        None
+    } else {
+        Some(value.as_u128())
+    }
+}
+
+#[cfg(test)]
+mod fixed_point_test {
+    use super::*;
+
+    fn to_f64(x: i128) -> f64 {
+        x as f64 / ONE_X64 as f64
+    }
+
+    #[test]
+    fn ln_matches_float() {
+        for x in [1u128, 2, 3, 1_000_000, u64::MAX as u128] {
+            let ln = to_f64(ln_x64(x * ONE_X64).unwrap());
+            assert!((ln - (x as f64).ln()).abs() < 1e-12);
+        }
+        assert_eq!(ln_x64(ONE_X64), Some(0));
+        assert!((to_f64(ln_x64(ONE_X64 / 2).unwrap()) + 2f64.ln()).abs() < 1e-12);
+        assert_eq!(ln_x64(0), None);
+    }
+
+    #[test]
+    fn exp_matches_float() {
+        assert_eq!(exp_x64(0), Some(U256::from(ONE_X64)));
+        for y in [-5i128, -1, 1, 10, 40] {
+            let exp = exp_x64(y * ONE_X64 as i128).unwrap();
+            let expected = (y as f64).exp();
+            let exp = exp.as_u128() as f64 / ONE_X64 as f64;
+            assert!((exp - expected).abs() / expected < 1e-12);
+        }
+        assert_eq!(exp_x64(i128::MIN / 2), Some(U256::zero()));
+        assert_eq!(exp_x64(i128::MAX / 2), None);
+    }
+
+    #[test]
+    fn pow_of_fraction() {
+        // 4 ^ (1 / 2) = 2
+        let sqrt = pow_x64(4 * ONE_X64, 1, 2).unwrap().as_u128();
+        assert!(sqrt <= 2 * ONE_X64 && 2 * ONE_X64 - sqrt < 1 << 16);
+        // 0.5 ^ 4 = 0.0625
+        let pow = pow_x64(ONE_X64 / 2, 4, 1).unwrap().as_u128();
+        assert!(pow <= ONE_X64 / 16 && ONE_X64 / 16 - pow < 1 << 16);
+    }
+}
@@ -13,8 +13,8 @@ use anchor_spl::{
         },
     },
     token_interface::{
-        initialize_account3, spl_token_2022::extension::BaseStateWithExtensions,
-        InitializeAccount3, Mint,
+        close_account, initialize_account3, spl_token_2022::extension::BaseStateWithExtensions,
+        sync_native, CloseAccount, InitializeAccount3, Mint, SyncNative,
     },
 };
 use std::collections::HashSet;
@@ -221,17 +221,13 @@ pub fn create_token_account<'a>(
             TokenAccount::LEN
         }
     };
-    let lamports = Rent::get()?.minimum_balance(space);
-    let cpi_accounts = anchor_lang::system_program::CreateAccount {
-        from: payer.to_account_info(),
-        to: token_account.to_account_info(),
-    };
-    let cpi_context = CpiContext::new(system_program.to_account_info(), cpi_accounts);
-    anchor_lang::system_program::create_account(
-        cpi_context.with_signer(signer_seeds),
-        lamports,
-        space as u64,
+    create_or_allocate_account(
         token_program.key,
+        payer,
+        system_program,
+        token_account,
+        signer_seeds,
+        space,
     )?;
     initialize_account3(CpiContext::new(
         token_program.to_account_info(),
@@ -242,3 +238,102 @@ pub fn create_token_account<'a>(
         },
     ))
 }
+
+/// Creates a program address account owned by `program_id`. Anyone can send lamports to the
+/// address beforehand, which makes `create_account` fail, so a funded address is topped up to
+/// rent exemption, allocated and assigned instead.
+pub fn create_or_allocate_account<'a>(
+    program_id: &Pubkey,
+    payer: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    target_account: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+    space: usize,
+) -> Result<()> {
+    let rent = Rent::get()?;
+    let current_lamports = target_account.lamports();
+    if current_lamports == 0 {
+        let lamports = rent.minimum_balance(space);
+        let cpi_accounts = anchor_lang::system_program::CreateAccount {
+            from: payer.to_account_info(),
+            to: target_account.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::create_account(
+            cpi_context.with_signer(signer_seeds),
+            lamports,
+            space as u64,
+            program_id,
+        )?;
+    } else {
+        let required_lamports = rent
+            .minimum_balance(space)
+            .max(1)
+            .saturating_sub(current_lamports);
+        if required_lamports > 0 {
+            let cpi_accounts = anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: target_account.to_account_info(),
+            };
+            let cpi_context = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+            anchor_lang::system_program::transfer(cpi_context, required_lamports)?;
+        }
+        let cpi_accounts = anchor_lang::system_program::Allocate {
+            account_to_allocate: target_account.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::allocate(cpi_context.with_signer(signer_seeds), space as u64)?;
+
+        let cpi_accounts = anchor_lang::system_program::Assign {
+            account_to_assign: target_account.to_account_info(),
+        };
+        let cpi_context = CpiContext::new(system_program.to_account_info(), cpi_accounts);
+        anchor_lang::system_program::assign(cpi_context.with_signer(signer_seeds), program_id)?;
+    }
+    Ok(())
+}
+
+/// Wraps `amount` lamports of the payer into a token account of the native mint
+pub fn wrap_native_sol<'a>(
+    payer: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    system_program: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    amount: u64,
+) -> Result<()> {
+    anchor_lang::system_program::transfer(
+        CpiContext::new(
+            system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: payer.to_account_info(),
+                to: token_account.to_account_info(),
+            },
+        ),
+        amount,
+    )?;
+    sync_native(CpiContext::new(
+        token_program.to_account_info(),
+        SyncNative {
+            account: token_account.to_account_info(),
+        },
+    ))
+}
+
+/// Closes a token account, a native mint account sends its wrapped SOL along with the rent
+pub fn close_token_account<'a>(
+    authority: &AccountInfo<'a>,
+    token_account: &AccountInfo<'a>,
+    destination: &AccountInfo<'a>,
+    token_program: &AccountInfo<'a>,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    close_account(CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        CloseAccount {
+            account: token_account.to_account_info(),
+            destination: destination.to_account_info(),
+            authority: authority.to_account_info(),
+        },
+        signer_seeds,
+    ))
+}
@@ -0,0 +1,48 @@
+//! Flash swap callback used by the tests, it forwards its data as an instruction of the
+//! program passed as first remaining account, with the other remaining accounts
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::{
+    instruction::{AccountMeta, Instruction},
+    program::invoke,
+};
+
+declare_id!("GPaiQj7YwP135Jb5CUUPi7x95voptHBNrJN683nyFdxq");
+
+#[program]
+pub mod flash_swap_callback {
+    use super::*;
+
+    /// Invokes `data` on the first remaining account, e.g. to re-enter the pool
+    ///
+    /// # Arguments
+    ///
+    /// * `ctx`- The context of accounts
+    /// * `data` - instruction data of the forwarded instruction
+    ///
+    pub fn forward<'a, 'b, 'c: 'info, 'info>(
+        ctx: Context<'a, 'b, 'c, 'info, Forward>,
+        data: Vec<u8>,
+    ) -> Result<()> {
+        let (program, accounts) = ctx
+            .remaining_accounts
+            .split_first()
+            .ok_or(ErrorCode::AccountNotEnoughKeys)?;
+        let instruction = Instruction {
+            program_id: program.key(),
+            accounts: accounts
+                .iter()
+                .map(|account_info| AccountMeta {
+                    pubkey: account_info.key(),
+                    is_signer: account_info.is_signer,
+                    is_writable: account_info.is_writable,
+                })
+                .collect(),
+            data,
+        };
+        invoke(&instruction, ctx.remaining_accounts)?;
+        Ok(())
+    }
+}
+
+#[derive(Accounts)]
+pub struct Forward {}
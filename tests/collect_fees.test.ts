@@ -0,0 +1,80 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { RaydiumCpSwap } from "../target/types/raydium_cp_swap";
+import {
+  collect_creator_fees,
+  collect_protocol_fees,
+  setupSwapTest,
+  swap_base_input,
+} from "./utils";
+import { assert } from "chai";
+
+describe("collect fees test", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const owner = anchor.Wallet.local().payer;
+
+  const program = anchor.workspace.RaydiumCpSwap as Program<RaydiumCpSwap>;
+
+  const confirmOptions = {
+    skipPreflight: true,
+  };
+
+  it("collect creator and protocol fees", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 12,
+        tradeFeeRate: new BN(2500),
+        protocolFeeRate: new BN(120000),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    await swap_base_input(
+      program,
+      owner,
+      poolAddress,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.mint,
+      poolState.mintTokenProgram,
+      new BN(100000000),
+      new BN(0),
+      confirmOptions
+    );
+    const swappedPoolState = await program.account.poolState.fetch(
+      poolAddress
+    );
+    assert(swappedPoolState.protocolFeesTokenQuote.gtn(0));
+    assert(swappedPoolState.creatorFeesTokenQuote.gtn(0));
+
+    // The creator fees are collected alone, the protocol fees stay
+    await collect_creator_fees(
+      program,
+      owner,
+      poolAddress,
+      null,
+      null,
+      confirmOptions
+    );
+    let newPoolState = await program.account.poolState.fetch(poolAddress);
+    assert(newPoolState.creatorFeesTokenQuote.eqn(0));
+    assert(
+      newPoolState.protocolFeesTokenQuote.eq(
+        swappedPoolState.protocolFeesTokenQuote
+      )
+    );
+
+    await collect_protocol_fees(
+      program,
+      owner,
+      poolAddress,
+      null,
+      null,
+      confirmOptions
+    );
+    newPoolState = await program.account.poolState.fetch(poolAddress);
+    assert(newPoolState.protocolFeesTokenQuote.eqn(0));
+  });
+});
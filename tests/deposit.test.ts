@@ -28,39 +28,33 @@ describe("deposit test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 3,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       { transferFeeBasisPoints: 0, MaxFee: 0 }
     );
 
     const {
-      onwerToken0Account: ownerToken0AccountBefore,
-      onwerToken1Account: ownerToken1AccountBefore,
-      poolVault0TokenAccount: poolVault0TokenAccountBefore,
-      poolVault1TokenAccount: poolVault1TokenAccountBefore,
+      onwerToken0Account: ownerTokenMintAccountBefore,
+      onwerToken1Account: ownerTokenQuoteAccountBefore,
+      poolVault0TokenAccount: poolMintVaultBefore,
+      poolVault1TokenAccount: poolQuoteVaultBefore,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
 
     const liquidity = new BN(10000000000);
     await deposit(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity,
       new BN(10000000000),
       new BN(20000000000),
@@ -68,32 +62,34 @@ describe("deposit test", () => {
     );
     const newPoolState = await program.account.poolState.fetch(poolAddress);
     assert(newPoolState.lpSupply.eq(liquidity.add(poolState.lpSupply)));
+    // The virtual offset grows with the liquidity so the price is unchanged
+    assert(newPoolState.offSet.gt(poolState.offSet));
 
     const {
-      onwerToken0Account: ownerToken0AccountAfter,
-      onwerToken1Account: ownerToken1AccountAfter,
-      poolVault0TokenAccount: poolVault0TokenAccountAfter,
-      poolVault1TokenAccount: poolVault1TokenAccountAfter,
+      onwerToken0Account: ownerTokenMintAccountAfter,
+      onwerToken1Account: ownerTokenQuoteAccountAfter,
+      poolVault0TokenAccount: poolMintVaultAfter,
+      poolVault1TokenAccount: poolQuoteVaultAfter,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
-    const input_token0_amount =
-      ownerToken0AccountBefore.amount - ownerToken0AccountAfter.amount;
-    const input_token1_amount =
-      ownerToken1AccountBefore.amount - ownerToken1AccountAfter.amount;
+    const input_mint_amount =
+      ownerTokenMintAccountBefore.amount - ownerTokenMintAccountAfter.amount;
+    const input_quote_amount =
+      ownerTokenQuoteAccountBefore.amount - ownerTokenQuoteAccountAfter.amount;
     assert.equal(
-      poolVault0TokenAccountAfter.amount - poolVault0TokenAccountBefore.amount,
-      input_token0_amount
+      poolMintVaultAfter.amount - poolMintVaultBefore.amount,
+      input_mint_amount
     );
     assert.equal(
-      poolVault1TokenAccountAfter.amount - poolVault1TokenAccountBefore.amount,
-      input_token1_amount
+      poolQuoteVaultAfter.amount - poolQuoteVaultBefore.amount,
+      input_quote_amount
     );
 
     /// deposit with fee
@@ -109,67 +105,49 @@ describe("deposit test", () => {
         anchor.getProvider().connection,
         owner,
         {
-          config_index: 0,
+          config_index: 4,
           tradeFeeRate: new BN(10),
           protocolFeeRate: new BN(1000),
-          fundFeeRate: new BN(25000),
-          create_fee: new BN(0),
         },
         transferFeeConfig,
         confirmOptions,
         {
-          initAmount0: new BN(
+          mintAmount: new BN(
             calculatePreFeeAmount(
               transferFeeConfig,
-              poolVault0TokenAccountBefore.amount,
-              poolState.token0Program
-            ).toString()
-          ),
-          initAmount1: new BN(
-            calculatePreFeeAmount(
-              transferFeeConfig,
-              poolVault1TokenAccountBefore.amount,
-              poolState.token1Program
+              poolMintVaultBefore.amount,
+              poolState.mintTokenProgram
             ).toString()
           ),
+          offset: poolState.offSet,
         },
         {
-          token0Program: poolState.token0Program,
-          token1Program: poolState.token1Program,
+          mintProgram: poolState.mintTokenProgram,
+          quoteProgram: poolState.quoteTokenProgram,
         }
       );
     const {
-      onwerToken0Account: onwerToken0AccountBefore2,
-      onwerToken1Account: onwerToken1AccountBefore2,
-      poolVault0TokenAccount: poolVault0TokenAccountBefore2,
-      poolVault1TokenAccount: poolVault1TokenAccountBefore2,
+      onwerToken0Account: ownerTokenMintAccountBefore2,
+      onwerToken1Account: ownerTokenQuoteAccountBefore2,
+      poolVault0TokenAccount: poolMintVaultBefore2,
+      poolVault1TokenAccount: poolQuoteVaultBefore2,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState2.token0Mint,
-      poolState2.token0Program,
-      poolState2.token1Mint,
-      poolState2.token1Program,
-      poolState2.token0Vault,
-      poolState2.token1Vault
+      poolState2.mint,
+      poolState2.mintTokenProgram,
+      poolState2.quoteMint,
+      poolState2.quoteTokenProgram,
+      poolState2.tokenMintVault,
+      poolState2.tokenQuoteVault
     );
     // check vault init state
-    assert.equal(
-      poolVault0TokenAccountBefore2.amount,
-      poolVault0TokenAccountBefore.amount
-    );
-    assert.equal(
-      poolVault1TokenAccountBefore2.amount,
-      poolVault1TokenAccountBefore.amount
-    );
+    assert.equal(poolMintVaultBefore2.amount, poolMintVaultBefore.amount);
+    assert.equal(poolQuoteVaultBefore2.amount, poolQuoteVaultBefore.amount);
 
     await deposit(
       program,
       owner,
-      poolState2.ammConfig,
-      poolState2.token0Mint,
-      poolState2.token0Program,
-      poolState2.token1Mint,
-      poolState2.token1Program,
+      poolAddress2,
       liquidity,
       new BN(100000000000),
       new BN(200000000000),
@@ -179,64 +157,57 @@ describe("deposit test", () => {
     assert(newPoolState2.lpSupply.eq(liquidity.add(poolState2.lpSupply)));
 
     const {
-      onwerToken0Account: onwerToken0AccountAfter2,
-      onwerToken1Account: onwerToken1AccountAfter2,
-      poolVault0TokenAccount: poolVault0TokenAccountAfter2,
-      poolVault1TokenAccount: poolVault1TokenAccountAfter2,
+      onwerToken0Account: ownerTokenMintAccountAfter2,
+      onwerToken1Account: ownerTokenQuoteAccountAfter2,
+      poolVault0TokenAccount: poolMintVaultAfter2,
+      poolVault1TokenAccount: poolQuoteVaultAfter2,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState2.token0Mint,
-      poolState2.token0Program,
-      poolState2.token1Mint,
-      poolState2.token1Program,
-      poolState2.token0Vault,
-      poolState2.token1Vault
+      poolState2.mint,
+      poolState2.mintTokenProgram,
+      poolState2.quoteMint,
+      poolState2.quoteTokenProgram,
+      poolState2.tokenMintVault,
+      poolState2.tokenQuoteVault
     );
 
-    const input_token0_amount_with_fee =
-      onwerToken0AccountBefore2.amount - onwerToken0AccountAfter2.amount;
-    const input_token1_amount_with_fee =
-      onwerToken1AccountBefore2.amount - onwerToken1AccountAfter2.amount;
-    assert(input_token0_amount_with_fee >= input_token0_amount);
-    assert(input_token1_amount_with_fee >= input_token1_amount);
+    const input_mint_amount_with_fee =
+      ownerTokenMintAccountBefore2.amount - ownerTokenMintAccountAfter2.amount;
+    const input_quote_amount_with_fee =
+      ownerTokenQuoteAccountBefore2.amount -
+      ownerTokenQuoteAccountAfter2.amount;
+    assert(input_mint_amount_with_fee >= input_mint_amount);
+    assert(input_quote_amount_with_fee >= input_quote_amount);
 
     assert.equal(
-      input_token0_amount_with_fee,
+      input_mint_amount_with_fee,
       calculateFee(
         transferFeeConfig,
-        input_token0_amount_with_fee,
-        poolState2.token0Program
-      ) + input_token0_amount
+        input_mint_amount_with_fee,
+        poolState2.mintTokenProgram
+      ) + input_mint_amount
     );
     assert.equal(
-      input_token1_amount_with_fee,
+      input_quote_amount_with_fee,
       calculateFee(
         transferFeeConfig,
-        input_token1_amount_with_fee,
-        poolState2.token1Program
-      ) + input_token1_amount
+        input_quote_amount_with_fee,
+        poolState2.quoteTokenProgram
+      ) + input_quote_amount
     );
 
     // Add the same liquidity, the amount increment of the pool vault will be the same as without fees.
     assert.equal(
-      poolVault0TokenAccountAfter2.amount -
-        poolVault0TokenAccountBefore2.amount,
-      input_token0_amount
+      poolMintVaultAfter2.amount - poolMintVaultBefore2.amount,
+      input_mint_amount
     );
     assert.equal(
-      poolVault1TokenAccountAfter2.amount -
-        poolVault1TokenAccountBefore2.amount,
-      input_token1_amount
+      poolQuoteVaultAfter2.amount - poolQuoteVaultBefore2.amount,
+      input_quote_amount
     );
 
-    assert.equal(
-      poolVault0TokenAccountAfter.amount,
-      poolVault0TokenAccountAfter2.amount
-    );
-    assert.equal(
-      poolVault1TokenAccountAfter.amount,
-      poolVault1TokenAccountAfter2.amount
-    );
+    assert.equal(poolMintVaultAfter.amount, poolMintVaultAfter2.amount);
+    assert.equal(poolQuoteVaultAfter.amount, poolQuoteVaultAfter2.amount);
   });
 
   it("deposit test with 100% transferFeeConfig, reache maximum fee limit", async () => {
@@ -250,41 +221,33 @@ describe("deposit test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 5,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       transferFeeConfig
     );
 
     const {
-      onwerToken0Account: ownerToken0AccountBefore,
-      onwerToken1Account: ownerToken1AccountBefore,
-      poolVault0TokenAccount: poolVault0TokenAccountBefore,
-      poolVault1TokenAccount: poolVault1TokenAccountBefore,
+      onwerToken0Account: ownerTokenMintAccountBefore,
+      poolVault0TokenAccount: poolMintVaultBefore,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
 
     const liquidity = new BN(10000000000);
     await deposit(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity,
-      new BN(10000000000),
+      new BN(20000000000),
       new BN(20000000000),
       confirmOptions
     );
@@ -292,45 +255,30 @@ describe("deposit test", () => {
     assert(newPoolState.lpSupply.eq(liquidity.add(poolState.lpSupply)));
 
     const {
-      onwerToken0Account: ownerToken0AccountAfter,
-      onwerToken1Account: ownerToken1AccountAfter,
-      poolVault0TokenAccount: poolVault0TokenAccountAfter,
-      poolVault1TokenAccount: poolVault1TokenAccountAfter,
+      onwerToken0Account: ownerTokenMintAccountAfter,
+      poolVault0TokenAccount: poolMintVaultAfter,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
-    const input_token0_amount =
-      ownerToken0AccountBefore.amount - ownerToken0AccountAfter.amount;
-    const input_token1_amount =
-      ownerToken1AccountBefore.amount - ownerToken1AccountAfter.amount;
+    const input_mint_amount =
+      ownerTokenMintAccountBefore.amount - ownerTokenMintAccountAfter.amount;
 
-    if (poolState.token0Program.equals(TOKEN_PROGRAM_ID)) {
+    // The quote vault holds no real tokens before the first buy, only the pool mint is paid
+    if (poolState.mintTokenProgram.equals(TOKEN_PROGRAM_ID)) {
       assert.equal(
-        poolVault0TokenAccountAfter.amount -
-          poolVault0TokenAccountBefore.amount,
-        input_token0_amount
-      );
-      assert.equal(
-        poolVault1TokenAccountAfter.amount -
-          poolVault1TokenAccountBefore.amount,
-        input_token1_amount - BigInt(transferFeeConfig.MaxFee)
+        poolMintVaultAfter.amount - poolMintVaultBefore.amount,
+        input_mint_amount
       );
     } else {
       assert.equal(
-        poolVault0TokenAccountAfter.amount -
-          poolVault0TokenAccountBefore.amount,
-        input_token0_amount - BigInt(transferFeeConfig.MaxFee)
-      );
-      assert.equal(
-        poolVault1TokenAccountAfter.amount -
-          poolVault1TokenAccountBefore.amount,
-        input_token1_amount
+        poolMintVaultAfter.amount - poolMintVaultBefore.amount,
+        input_mint_amount - BigInt(transferFeeConfig.MaxFee)
       );
     }
   });
@@ -0,0 +1,291 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { RaydiumCpSwap } from "../target/types/raydium_cp_swap";
+import { FlashSwapCallback } from "../target/types/flash_swap_callback";
+import {
+  flash_swap,
+  getAuthAddress,
+  quote_base_output,
+  setupSwapTest,
+} from "./utils";
+import { assert } from "chai";
+import {
+  createTransferCheckedInstruction,
+  getAccount,
+  getAssociatedTokenAddressSync,
+  getMint,
+} from "@solana/spl-token";
+import { PublicKey } from "@solana/web3.js";
+
+describe("flash swap test", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const owner = anchor.Wallet.local().payer;
+
+  const program = anchor.workspace.RaydiumCpSwap as Program<RaydiumCpSwap>;
+  const callbackProgram = anchor.workspace
+    .FlashSwapCallback as Program<FlashSwapCallback>;
+
+  const confirmOptions = {
+    skipPreflight: false,
+  };
+
+  // Flash swaps the quote token for the pool mint, the callback pays
+  // `repay_amount` of the quote token into the quote vault
+  async function flashSwapRepaying(
+    poolAddress: PublicKey,
+    amount_out_less_fee: BN,
+    repay_amount: BN
+  ) {
+    const poolState = await program.account.poolState.fetch(poolAddress);
+    const quoteMint = await getMint(
+      anchor.getProvider().connection,
+      poolState.quoteMint,
+      "processed",
+      poolState.quoteTokenProgram
+    );
+    const repayInstruction = createTransferCheckedInstruction(
+      getAssociatedTokenAddressSync(
+        poolState.quoteMint,
+        owner.publicKey,
+        false,
+        poolState.quoteTokenProgram
+      ),
+      poolState.quoteMint,
+      poolState.tokenQuoteVault,
+      owner.publicKey,
+      BigInt(repay_amount.toString()),
+      quoteMint.decimals,
+      [],
+      poolState.quoteTokenProgram
+    );
+    const forwardInstruction = await callbackProgram.methods
+      .forward(repayInstruction.data)
+      .instruction();
+
+    return await flash_swap(
+      program,
+      owner,
+      poolAddress,
+      poolState.quoteMint,
+      poolState.mint,
+      poolState.mintTokenProgram,
+      amount_out_less_fee,
+      callbackProgram.programId,
+      forwardInstruction.data,
+      [
+        {
+          pubkey: poolState.quoteTokenProgram,
+          isSigner: false,
+          isWritable: false,
+        },
+        ...repayInstruction.keys,
+      ],
+      confirmOptions
+    );
+  }
+
+  it("flash swap repaid by the callback books the pool fees", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 13,
+        tradeFeeRate: new BN(10000),
+        protocolFeeRate: new BN(100000),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const amount_out = new BN(100000000);
+    const quote = await quote_base_output(
+      program,
+      poolAddress,
+      poolState.quoteMint,
+      poolState.mint,
+      amount_out
+    );
+    assert(quote.protocolFee.gtn(0));
+
+    const outputTokenAccountAddr = getAssociatedTokenAddressSync(
+      poolState.mint,
+      owner.publicKey,
+      false,
+      poolState.mintTokenProgram
+    );
+    const outputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      poolState.mintTokenProgram
+    );
+    const quoteVaultBefore = await getAccount(
+      anchor.getProvider().connection,
+      poolState.tokenQuoteVault,
+      "processed",
+      poolState.quoteTokenProgram
+    );
+
+    await flashSwapRepaying(poolAddress, amount_out, quote.amountIn);
+
+    const outputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      poolState.mintTokenProgram
+    );
+    const quoteVaultAfter = await getAccount(
+      anchor.getProvider().connection,
+      poolState.tokenQuoteVault,
+      "processed",
+      poolState.quoteTokenProgram
+    );
+    assert.equal(
+      outputTokenAccountAfter.amount - outputTokenAccountBefore.amount,
+      BigInt(amount_out.toString())
+    );
+    assert.equal(
+      quoteVaultAfter.amount - quoteVaultBefore.amount,
+      BigInt(quote.amountIn.toString())
+    );
+
+    const newPoolState = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(newPoolState.locked);
+    assert(
+      newPoolState.protocolFeesTokenQuote
+        .sub(poolState.protocolFeesTokenQuote)
+        .eq(quote.protocolFee)
+    );
+    assert(
+      newPoolState.creatorFeesTokenQuote
+        .sub(poolState.creatorFeesTokenQuote)
+        .eq(quote.creatorFee)
+    );
+  });
+
+  it("flash swap underpaid by the callback fails", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 14,
+        tradeFeeRate: new BN(10000),
+        protocolFeeRate: new BN(100000),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const amount_out = new BN(100000000);
+    const quote = await quote_base_output(
+      program,
+      poolAddress,
+      poolState.quoteMint,
+      poolState.mint,
+      amount_out
+    );
+
+    let repaid = true;
+    try {
+      await flashSwapRepaying(
+        poolAddress,
+        amount_out,
+        quote.amountIn.subn(1)
+      );
+    } catch (error) {
+      repaid = false;
+      assert.isTrue(
+        error.logs.some((log: string) => log.includes("FlashSwapNotRepaid")),
+        error.toString()
+      );
+    }
+    assert.isFalse(repaid);
+
+    const newPoolState = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(newPoolState.locked);
+    assert(
+      newPoolState.protocolFeesTokenQuote.eq(poolState.protocolFeesTokenQuote)
+    );
+  });
+
+  it("flash swap callback can not re-enter the pool", async () => {
+    const { poolAddress, poolState } = await setupSwapTest(
+      program,
+      anchor.getProvider().connection,
+      owner,
+      {
+        config_index: 11,
+        tradeFeeRate: new BN(10),
+        protocolFeeRate: new BN(1000),
+      },
+      { transferFeeBasisPoints: 0, MaxFee: 0 }
+    );
+    const [auth] = await getAuthAddress(program.programId);
+
+    // The callback swaps in the same pool instead of paying the flash swap back
+    const reenterInstruction = await program.methods
+      .swapBaseInput(new BN(100000000), new BN(0), null, null)
+      .accountsPartial({
+        payer: owner.publicKey,
+        authority: auth,
+        ammConfig: poolState.ammConfig,
+        poolState: poolAddress,
+        inputTokenAccount: getAssociatedTokenAddressSync(
+          poolState.quoteMint,
+          owner.publicKey,
+          false,
+          poolState.quoteTokenProgram
+        ),
+        outputTokenAccount: getAssociatedTokenAddressSync(
+          poolState.mint,
+          owner.publicKey,
+          false,
+          poolState.mintTokenProgram
+        ),
+        inputVault: poolState.tokenQuoteVault,
+        outputVault: poolState.tokenMintVault,
+        inputTokenProgram: poolState.quoteTokenProgram,
+        outputTokenProgram: poolState.mintTokenProgram,
+        inputTokenMint: poolState.quoteMint,
+        outputTokenMint: poolState.mint,
+        observationState: poolState.observationKey,
+      })
+      .instruction();
+    const forwardInstruction = await callbackProgram.methods
+      .forward(reenterInstruction.data)
+      .instruction();
+
+    let reentered = true;
+    try {
+      await flash_swap(
+        program,
+        owner,
+        poolAddress,
+        poolState.quoteMint,
+        poolState.mint,
+        poolState.mintTokenProgram,
+        new BN(1000),
+        callbackProgram.programId,
+        forwardInstruction.data,
+        [
+          {
+            pubkey: program.programId,
+            isSigner: false,
+            isWritable: false,
+          },
+          ...reenterInstruction.keys,
+        ],
+        confirmOptions
+      );
+    } catch (error) {
+      reentered = false;
+      assert.isTrue(
+        error.logs.some((log: string) => log.includes("PoolLocked")),
+        error.toString()
+      );
+    }
+    assert.isFalse(reentered);
+
+    const newPoolState = await program.account.poolState.fetch(poolAddress);
+    assert.isFalse(newPoolState.locked);
+    assert(newPoolState.lpSupply.eq(poolState.lpSupply));
+  });
+});
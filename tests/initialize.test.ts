@@ -2,7 +2,7 @@ import * as anchor from "@coral-xyz/anchor";
 import { Program, BN } from "@coral-xyz/anchor";
 import { RaydiumCpSwap } from "../target/types/raydium_cp_swap";
 
-import { getAccount, TOKEN_PROGRAM_ID } from "@solana/spl-token";
+import { getAccount, getMint, TOKEN_PROGRAM_ID } from "@solana/spl-token";
 import { setupInitializeTest, initialize, calculateFee } from "./utils";
 import { assert } from "chai";
 
@@ -18,7 +18,7 @@ describe("initialize test", () => {
   };
 
   it("create pool without fee", async () => {
-    const { configAddress, token0, token0Program, token1, token1Program } =
+    const { configAddress, mint, mintProgram, quoteMint, quoteProgram } =
       await setupInitializeTest(
         program,
         anchor.getProvider().connection,
@@ -27,45 +27,45 @@ describe("initialize test", () => {
           config_index: 0,
           tradeFeeRate: new BN(10),
           protocolFeeRate: new BN(1000),
-          fundFeeRate: new BN(25000),
-          create_fee: new BN(0),
         },
         { transferFeeBasisPoints: 0, MaxFee: 0 },
         confirmOptions
       );
 
-    const initAmount0 = new BN(10000000000);
-    const initAmount1 = new BN(10000000000);
+    const mintAmount = new BN(10000000000);
+    const offset = new BN(10000000000);
     const { poolAddress, poolState } = await initialize(
       program,
       owner,
       configAddress,
-      token0,
-      token0Program,
-      token1,
-      token1Program,
+      mint,
+      mintProgram,
+      quoteMint,
+      quoteProgram,
       confirmOptions,
-      { initAmount0, initAmount1 }
+      { mintAmount, offset }
     );
-    let vault0 = await getAccount(
+    let mintVault = await getAccount(
       anchor.getProvider().connection,
-      poolState.token0Vault,
+      poolState.tokenMintVault,
       "processed",
-      poolState.token0Program
+      poolState.mintTokenProgram
     );
-    assert.equal(vault0.amount.toString(), initAmount0.toString());
+    assert.equal(mintVault.amount.toString(), mintAmount.toString());
 
-    let vault1 = await getAccount(
+    // The quote side is only the virtual offset
+    let quoteVault = await getAccount(
       anchor.getProvider().connection,
-      poolState.token1Vault,
+      poolState.tokenQuoteVault,
       "processed",
-      poolState.token1Program
+      poolState.quoteTokenProgram
     );
-    assert.equal(vault1.amount.toString(), initAmount1.toString());
+    assert.equal(quoteVault.amount.toString(), "0");
+    assert(poolState.offSet.eq(offset));
   });
 
-  it("create pool with fee", async () => {
-    const { configAddress, token0, token0Program, token1, token1Program } =
+  it("create pool locks the initial liquidity", async () => {
+    const { configAddress, mint, mintProgram, quoteMint, quoteProgram } =
       await setupInitializeTest(
         program,
         anchor.getProvider().connection,
@@ -74,110 +74,81 @@ describe("initialize test", () => {
           config_index: 1,
           tradeFeeRate: new BN(10),
           protocolFeeRate: new BN(1000),
-          fundFeeRate: new BN(25000),
-          create_fee: new BN(100000000),
         },
         { transferFeeBasisPoints: 0, MaxFee: 0 },
         confirmOptions
       );
 
-    const initAmount0 = new BN(10000000000);
-    const initAmount1 = new BN(10000000000);
+    const mintAmount = new BN(10000000000);
+    const offset = new BN(40000000000);
     const { poolAddress, poolState } = await initialize(
       program,
       owner,
       configAddress,
-      token0,
-      token0Program,
-      token1,
-      token1Program,
+      mint,
+      mintProgram,
+      quoteMint,
+      quoteProgram,
       confirmOptions,
-      { initAmount0, initAmount1 }
+      { mintAmount, offset }
     );
-    let vault0 = await getAccount(
+    // sqrt(mint_amount * offset), none of it minted to the creator
+    assert(poolState.lpSupply.eq(new BN(20000000000)));
+    const lpMint = await getMint(
       anchor.getProvider().connection,
-      poolState.token0Vault,
+      poolState.lpMint,
       "processed",
-      poolState.token0Program
+      TOKEN_PROGRAM_ID
     );
-    assert.equal(vault0.amount.toString(), initAmount0.toString());
-
-    let vault1 = await getAccount(
-      anchor.getProvider().connection,
-      poolState.token1Vault,
-      "processed",
-      poolState.token1Program
-    );
-    assert.equal(vault1.amount.toString(), initAmount1.toString());
+    assert.equal(lpMint.supply.toString(), "0");
   });
 
   it("create pool with token2022 mint has transfer fee", async () => {
     const transferFeeConfig = { transferFeeBasisPoints: 100, MaxFee: 50000000 }; // %10
-    const { configAddress, token0, token0Program, token1, token1Program } =
+    const { configAddress, mint, mintProgram, quoteMint, quoteProgram } =
       await setupInitializeTest(
         program,
         anchor.getProvider().connection,
         owner,
         {
-          config_index: 1,
+          config_index: 2,
           tradeFeeRate: new BN(10),
           protocolFeeRate: new BN(1000),
-          fundFeeRate: new BN(25000),
-          create_fee: new BN(100000000),
         },
         transferFeeConfig,
         confirmOptions
       );
 
-    const initAmount0 = new BN(10000000000);
-    const initAmount1 = new BN(10000000000);
+    const mintAmount = new BN(10000000000);
+    const offset = new BN(10000000000);
     const { poolAddress, poolState } = await initialize(
       program,
       owner,
       configAddress,
-      token0,
-      token0Program,
-      token1,
-      token1Program,
+      mint,
+      mintProgram,
+      quoteMint,
+      quoteProgram,
       confirmOptions,
-      { initAmount0, initAmount1 }
-    );
-    let vault0 = await getAccount(
-      anchor.getProvider().connection,
-      poolState.token0Vault,
-      "processed",
-      poolState.token0Program
+      { mintAmount, offset }
     );
-    if (token0Program == TOKEN_PROGRAM_ID) {
-      assert.equal(vault0.amount.toString(), initAmount0.toString());
-    } else {
-      const total =
-        vault0.amount +
-        calculateFee(
-          transferFeeConfig,
-          BigInt(initAmount0.toString()),
-          poolState.token0Program
-        );
-      assert(new BN(total.toString()).gte(initAmount0));
-    }
-
-    let vault1 = await getAccount(
+    let mintVault = await getAccount(
       anchor.getProvider().connection,
-      poolState.token1Vault,
+      poolState.tokenMintVault,
       "processed",
-      poolState.token1Program
+      poolState.mintTokenProgram
     );
-    if (token1Program == TOKEN_PROGRAM_ID) {
-      assert.equal(vault1.amount.toString(), initAmount1.toString());
+    if (mintProgram.equals(TOKEN_PROGRAM_ID)) {
+      assert.equal(mintVault.amount.toString(), mintAmount.toString());
     } else {
       const total =
-        vault1.amount +
+        mintVault.amount +
         calculateFee(
           transferFeeConfig,
-          BigInt(initAmount1.toString()),
-          poolState.token1Program
+          BigInt(mintAmount.toString()),
+          poolState.mintTokenProgram
         );
-      assert(new BN(total.toString()).gte(initAmount1));
+      assert(new BN(total.toString()).gte(mintAmount));
     }
   });
 });
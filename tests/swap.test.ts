@@ -21,16 +21,14 @@ describe("swap test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 8,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       { transferFeeBasisPoints: 0, MaxFee: 0 }
     );
-    const inputToken = poolState.token0Mint;
-    const inputTokenProgram = poolState.token0Program;
+    const inputToken = poolState.quoteMint;
+    const inputTokenProgram = poolState.quoteTokenProgram;
     const inputTokenAccountAddr = getAssociatedTokenAddressSync(
       inputToken,
       owner.publicKey,
@@ -47,11 +45,11 @@ describe("swap test", () => {
     await swap_base_input(
       program,
       owner,
-      configAddress,
+      poolAddress,
       inputToken,
       inputTokenProgram,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolState.mint,
+      poolState.mintTokenProgram,
       amount_in,
       new BN(0)
     );
@@ -73,24 +71,22 @@ describe("swap test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 9,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       { transferFeeBasisPoints: 0, MaxFee: 0 }
     );
-    const inputToken = poolState.token0Mint;
-    const inputTokenProgram = poolState.token0Program;
+    const inputToken = poolState.quoteMint;
+    const inputTokenProgram = poolState.quoteTokenProgram;
     const inputTokenAccountAddr = getAssociatedTokenAddressSync(
       inputToken,
       owner.publicKey,
       false,
       inputTokenProgram
     );
-    const outputToken = poolState.token1Mint;
-    const outputTokenProgram = poolState.token1Program;
+    const outputToken = poolState.mint;
+    const outputTokenProgram = poolState.mintTokenProgram;
     const outputTokenAccountAddr = getAssociatedTokenAddressSync(
       outputToken,
       owner.publicKey,
@@ -107,11 +103,11 @@ describe("swap test", () => {
     await swap_base_output(
       program,
       owner,
-      configAddress,
+      poolAddress,
       inputToken,
       inputTokenProgram,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolState.mint,
+      poolState.mintTokenProgram,
       amount_out,
       new BN(10000000000000),
       confirmOptions
@@ -135,25 +131,23 @@ describe("swap test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 10,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       transferFeeConfig
     );
 
-    const inputToken = poolState.token0Mint;
-    const inputTokenProgram = poolState.token0Program;
+    const inputToken = poolState.quoteMint;
+    const inputTokenProgram = poolState.quoteTokenProgram;
     const inputTokenAccountAddr = getAssociatedTokenAddressSync(
       inputToken,
       owner.publicKey,
       false,
       inputTokenProgram
     );
-    const outputToken = poolState.token1Mint;
-    const outputTokenProgram = poolState.token1Program;
+    const outputToken = poolState.mint;
+    const outputTokenProgram = poolState.mintTokenProgram;
     const outputTokenAccountAddr = getAssociatedTokenAddressSync(
       outputToken,
       owner.publicKey,
@@ -170,11 +164,11 @@ describe("swap test", () => {
     await swap_base_output(
       program,
       owner,
-      configAddress,
+      poolAddress,
       inputToken,
       inputTokenProgram,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolState.mint,
+      poolState.mintTokenProgram,
       amount_out,
       new BN(10000000000000),
       confirmOptions
@@ -0,0 +1,245 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { RaydiumCpSwap } from "../target/types/raydium_cp_swap";
+import {
+  createTokenMintAndAssociatedTokenAccount,
+  initialize,
+  quote_base_input,
+  route_hop_accounts,
+  setupInitializeTest,
+  swap_base_input,
+  swap_route,
+} from "./utils";
+import { assert } from "chai";
+import { getAccount, getAssociatedTokenAddressSync } from "@solana/spl-token";
+import { AccountMeta, Keypair, PublicKey } from "@solana/web3.js";
+
+describe("swap route test", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+  const owner = anchor.Wallet.local().payer;
+
+  const program = anchor.workspace.RaydiumCpSwap as Program<RaydiumCpSwap>;
+
+  const confirmOptions = {
+    skipPreflight: false,
+  };
+
+  // Two pools quoted in the same quote token, the first one holding real quote
+  // tokens to sell into
+  async function setupRouteTest(config_index: number) {
+    const connection = anchor.getProvider().connection;
+    const { configAddress, mint, mintProgram, quoteMint, quoteProgram } =
+      await setupInitializeTest(
+        program,
+        connection,
+        owner,
+        {
+          config_index,
+          tradeFeeRate: new BN(10000),
+          protocolFeeRate: new BN(100000),
+        },
+        { transferFeeBasisPoints: 0, MaxFee: 0 },
+        confirmOptions
+      );
+    const [{ token0: otherMint, token0Program: otherMintProgram }] =
+      await createTokenMintAndAssociatedTokenAccount(
+        connection,
+        owner,
+        new Keypair(),
+        { transferFeeBasisPoints: 0, MaxFee: 0 }
+      );
+
+    const { poolAddress } = await initialize(
+      program,
+      owner,
+      configAddress,
+      mint,
+      mintProgram,
+      quoteMint,
+      quoteProgram,
+      confirmOptions
+    );
+    const { poolAddress: otherPoolAddress } = await initialize(
+      program,
+      owner,
+      configAddress,
+      otherMint,
+      otherMintProgram,
+      quoteMint,
+      quoteProgram,
+      confirmOptions
+    );
+    await swap_base_input(
+      program,
+      owner,
+      poolAddress,
+      quoteMint,
+      quoteProgram,
+      mint,
+      mintProgram,
+      new BN(100000000),
+      new BN(0),
+      confirmOptions
+    );
+    return {
+      pool: { poolAddress, mint, mintProgram },
+      otherPool: {
+        poolAddress: otherPoolAddress,
+        mint: otherMint,
+        mintProgram: otherMintProgram,
+      },
+      quote: { mint: quoteMint, mintProgram: quoteProgram },
+    };
+  }
+
+  async function assertRouteFails(
+    inputToken: { mint: PublicKey; mintProgram: PublicKey },
+    outputToken: { mint: PublicKey; mintProgram: PublicKey },
+    minimum_amount_out: BN,
+    hopAccounts: AccountMeta[],
+    errorCode: string
+  ) {
+    let swapped = true;
+    try {
+      await swap_route(
+        program,
+        owner,
+        inputToken.mint,
+        inputToken.mintProgram,
+        outputToken.mint,
+        outputToken.mintProgram,
+        new BN(1000000),
+        minimum_amount_out,
+        hopAccounts,
+        confirmOptions
+      );
+    } catch (error) {
+      swapped = false;
+      assert.isTrue(
+        error.logs.some((log: string) => log.includes(errorCode)),
+        error.toString()
+      );
+    }
+    assert.isFalse(swapped);
+  }
+
+  it("swap route through the quote token", async () => {
+    const { pool, otherPool, quote } = await setupRouteTest(15);
+    const quoteMint = quote.mint;
+    const amount_in = new BN(1000000);
+    const firstHop = await quote_base_input(
+      program,
+      pool.poolAddress,
+      pool.mint,
+      quoteMint,
+      amount_in
+    );
+    const secondHop = await quote_base_input(
+      program,
+      otherPool.poolAddress,
+      quoteMint,
+      otherPool.mint,
+      firstHop.amountOut
+    );
+    const hopAccounts = [
+      ...(await route_hop_accounts(program, pool.poolAddress, pool.mint)),
+      ...(await route_hop_accounts(program, otherPool.poolAddress, quoteMint)),
+    ];
+
+    // The minimum amount out applies to the output of the last pool
+    await assertRouteFails(
+      pool,
+      otherPool,
+      secondHop.amountOut.addn(1),
+      hopAccounts,
+      "ExceededSlippage"
+    );
+
+    const outputTokenAccountAddr = getAssociatedTokenAddressSync(
+      otherPool.mint,
+      owner.publicKey,
+      false,
+      otherPool.mintProgram
+    );
+    const outputTokenAccountBefore = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      otherPool.mintProgram
+    );
+    await swap_route(
+      program,
+      owner,
+      pool.mint,
+      pool.mintProgram,
+      otherPool.mint,
+      otherPool.mintProgram,
+      amount_in,
+      secondHop.amountOut,
+      hopAccounts,
+      confirmOptions
+    );
+    const outputTokenAccountAfter = await getAccount(
+      anchor.getProvider().connection,
+      outputTokenAccountAddr,
+      "processed",
+      otherPool.mintProgram
+    );
+    assert.equal(
+      outputTokenAccountAfter.amount - outputTokenAccountBefore.amount,
+      BigInt(secondHop.amountOut.toString())
+    );
+  });
+
+  it("swap route rejects invalid routes", async () => {
+    const { pool, otherPool, quote } = await setupRouteTest(16);
+    const quoteMint = quote.mint;
+    const firstHopAccounts = await route_hop_accounts(
+      program,
+      pool.poolAddress,
+      pool.mint
+    );
+
+    // The same pool twice
+    await assertRouteFails(
+      pool,
+      pool,
+      new BN(0),
+      [
+        ...firstHopAccounts,
+        ...(await route_hop_accounts(program, pool.poolAddress, quoteMint)),
+      ],
+      "InvalidInput"
+    );
+
+    // The second pool doesn't take the quote token paid out by the first one
+    await assertRouteFails(
+      pool,
+      quote,
+      new BN(0),
+      [
+        ...firstHopAccounts,
+        ...(await route_hop_accounts(
+          program,
+          otherPool.poolAddress,
+          otherPool.mint
+        )),
+      ],
+      "InvalidInput"
+    );
+
+    // A hop short of an account
+    await assertRouteFails(
+      pool,
+      otherPool,
+      new BN(0),
+      [
+        ...firstHopAccounts,
+        ...(
+          await route_hop_accounts(program, otherPool.poolAddress, quoteMint)
+        ).slice(0, -1),
+      ],
+      "InvalidInput"
+    );
+  });
+});
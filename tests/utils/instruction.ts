@@ -1,6 +1,7 @@
 import { Program, BN } from "@coral-xyz/anchor";
 import { RaydiumCpSwap } from "../../target/types/raydium_cp_swap";
 import {
+  AccountMeta,
   Connection,
   ConfirmOptions,
   PublicKey,
@@ -13,10 +14,12 @@ import {
   TOKEN_PROGRAM_ID,
   TOKEN_2022_PROGRAM_ID,
   getAssociatedTokenAddressSync,
+  createAssociatedTokenAccountIdempotentInstruction,
 } from "@solana/spl-token";
 import {
   accountExist,
   sendTransaction,
+  getAdminAddress,
   getAmmConfigAddress,
   getAuthAddress,
   getPoolAddress,
@@ -24,10 +27,10 @@ import {
   getPoolVaultAddress,
   createTokenMintAndAssociatedTokenAccount,
   getOrcleAccountAddress,
+  getUserPurchaseAddress,
+  getNativeSolAddress,
 } from "./index";
 
-import { ASSOCIATED_PROGRAM_ID } from "@coral-xyz/anchor/dist/cjs/utils/token";
-
 export async function setupInitializeTest(
   program: Program<RaydiumCpSwap>,
   connection: Connection,
@@ -36,8 +39,6 @@ export async function setupInitializeTest(
     config_index: number;
     tradeFeeRate: BN;
     protocolFeeRate: BN;
-    fundFeeRate: BN;
-    create_fee: BN;
   },
   transferFeeConfig: { transferFeeBasisPoints: number; MaxFee: number } = {
     transferFeeBasisPoints: 0,
@@ -52,6 +53,7 @@ export async function setupInitializeTest(
       new Keypair(),
       transferFeeConfig
     );
+  // Every config quotes its pools in one mint, token1 is the quote of this one
   const configAddress = await createAmmConfig(
     program,
     connection,
@@ -59,16 +61,15 @@ export async function setupInitializeTest(
     config.config_index,
     config.tradeFeeRate,
     config.protocolFeeRate,
-    config.fundFeeRate,
-    config.create_fee,
+    token1,
     confirmOptions
   );
   return {
     configAddress,
-    token0,
-    token0Program,
-    token1,
-    token1Program,
+    mint: token0,
+    mintProgram: token0Program,
+    quoteMint: token1,
+    quoteProgram: token1Program,
   };
 }
 
@@ -80,35 +81,21 @@ export async function setupDepositTest(
     config_index: number;
     tradeFeeRate: BN;
     protocolFeeRate: BN;
-    fundFeeRate: BN;
-    create_fee: BN;
   },
   transferFeeConfig: { transferFeeBasisPoints: number; MaxFee: number } = {
     transferFeeBasisPoints: 0,
     MaxFee: 0,
   },
   confirmOptions?: ConfirmOptions,
-  initAmount: { initAmount0: BN; initAmount1: BN } = {
-    initAmount0: new BN(10000000000),
-    initAmount1: new BN(20000000000),
+  initAmount: { mintAmount: BN; offset: BN } = {
+    mintAmount: new BN(10000000000),
+    offset: new BN(20000000000),
   },
   tokenProgramRequired?: {
-    token0Program: PublicKey;
-    token1Program: PublicKey;
+    mintProgram: PublicKey;
+    quoteProgram: PublicKey;
   }
 ) {
-  const configAddress = await createAmmConfig(
-    program,
-    connection,
-    owner,
-    config.config_index,
-    config.tradeFeeRate,
-    config.protocolFeeRate,
-    config.fundFeeRate,
-    config.create_fee,
-    confirmOptions
-  );
-
   while (1) {
     const [{ token0, token0Program }, { token1, token1Program }] =
       await createTokenMintAndAssociatedTokenAccount(
@@ -118,24 +105,21 @@ export async function setupDepositTest(
         transferFeeConfig
       );
 
-    if (tokenProgramRequired != undefined) {
-      if (
-        token0Program.equals(tokenProgramRequired.token0Program) &&
-        token1Program.equals(tokenProgramRequired.token1Program)
-      ) {
-        return await initialize(
-          program,
-          owner,
-          configAddress,
-          token0,
-          token0Program,
-          token1,
-          token1Program,
-          confirmOptions,
-          initAmount
-        );
-      }
-    } else {
+    if (
+      tokenProgramRequired == undefined ||
+      (token0Program.equals(tokenProgramRequired.mintProgram) &&
+        token1Program.equals(tokenProgramRequired.quoteProgram))
+    ) {
+      const configAddress = await createAmmConfig(
+        program,
+        connection,
+        owner,
+        config.config_index,
+        config.tradeFeeRate,
+        config.protocolFeeRate,
+        token1,
+        confirmOptions
+      );
       return await initialize(
         program,
         owner,
@@ -159,8 +143,6 @@ export async function setupSwapTest(
     config_index: number;
     tradeFeeRate: BN;
     protocolFeeRate: BN;
-    fundFeeRate: BN;
-    create_fee: BN;
   },
   transferFeeConfig: { transferFeeBasisPoints: number; MaxFee: number } = {
     transferFeeBasisPoints: 0,
@@ -168,45 +150,31 @@ export async function setupSwapTest(
   },
   confirmOptions?: ConfirmOptions
 ) {
-  const configAddress = await createAmmConfig(
-    program,
-    connection,
-    owner,
-    config.config_index,
-    config.tradeFeeRate,
-    config.protocolFeeRate,
-    config.fundFeeRate,
-    config.create_fee,
-    confirmOptions
-  );
-
-  const [{ token0, token0Program }, { token1, token1Program }] =
-    await createTokenMintAndAssociatedTokenAccount(
+  const { configAddress, mint, mintProgram, quoteMint, quoteProgram } =
+    await setupInitializeTest(
+      program,
       connection,
       owner,
-      new Keypair(),
-      transferFeeConfig
+      config,
+      transferFeeConfig,
+      confirmOptions
     );
 
   const { poolAddress, poolState } = await initialize(
     program,
     owner,
     configAddress,
-    token0,
-    token0Program,
-    token1,
-    token1Program,
+    mint,
+    mintProgram,
+    quoteMint,
+    quoteProgram,
     confirmOptions
   );
 
   await deposit(
     program,
     owner,
-    poolState.ammConfig,
-    poolState.token0Mint,
-    poolState.token0Program,
-    poolState.token1Mint,
-    poolState.token1Program,
+    poolAddress,
     new BN(10000000000),
     new BN(100000000000),
     new BN(100000000000),
@@ -215,6 +183,31 @@ export async function setupSwapTest(
   return { configAddress, poolAddress, poolState };
 }
 
+export async function createAdmin(
+  program: Program<RaydiumCpSwap>,
+  connection: Connection,
+  owner: Signer,
+  confirmOptions?: ConfirmOptions
+): Promise<PublicKey> {
+  const [address] = await getAdminAddress(program.programId);
+  if (await accountExist(connection, address)) {
+    return address;
+  }
+
+  const ix = await program.methods
+    .createAdmin(owner.publicKey)
+    .accountsPartial({
+      payer: owner.publicKey,
+      adminState: address,
+      systemProgram: SystemProgram.programId,
+    })
+    .instruction();
+
+  const tx = await sendTransaction(connection, [ix], [owner], confirmOptions);
+  console.log("init admin tx: ", tx);
+  return address;
+}
+
 export async function createAmmConfig(
   program: Program<RaydiumCpSwap>,
   connection: Connection,
@@ -222,8 +215,7 @@ export async function createAmmConfig(
   config_index: number,
   tradeFeeRate: BN,
   protocolFeeRate: BN,
-  fundFeeRate: BN,
-  create_fee: BN,
+  quoteMint: PublicKey,
   confirmOptions?: ConfirmOptions
 ): Promise<PublicKey> {
   const [address, _] = await getAmmConfigAddress(
@@ -231,20 +223,32 @@ export async function createAmmConfig(
     program.programId
   );
   if (await accountExist(connection, address)) {
+    const ammConfig = await program.account.ammConfig.fetch(address);
+    if (!ammConfig.quoteMint.equals(quoteMint)) {
+      throw new Error(`amm config ${config_index} quotes another mint`);
+    }
     return address;
   }
+  const adminState = await createAdmin(
+    program,
+    connection,
+    owner,
+    confirmOptions
+  );
 
   const ix = await program.methods
     .createAmmConfig(
       config_index,
       tradeFeeRate,
       protocolFeeRate,
-      fundFeeRate,
-      create_fee
+      owner.publicKey,
+      { constantProduct: {} }
     )
-    .accounts({
+    .accountsPartial({
       owner: owner.publicKey,
+      adminState,
       ammConfig: address,
+      quoteMint,
       systemProgram: SystemProgram.programId,
     })
     .instruction();
@@ -258,84 +262,68 @@ export async function initialize(
   program: Program<RaydiumCpSwap>,
   creator: Signer,
   configAddress: PublicKey,
-  token0: PublicKey,
-  token0Program: PublicKey,
-  token1: PublicKey,
-  token1Program: PublicKey,
+  mint: PublicKey,
+  mintProgram: PublicKey,
+  quoteMint: PublicKey,
+  quoteProgram: PublicKey,
   confirmOptions?: ConfirmOptions,
-  initAmount: { initAmount0: BN; initAmount1: BN } = {
-    initAmount0: new BN(10000000000),
-    initAmount1: new BN(20000000000),
-  },
-  createPoolFee = new PublicKey("DNXgeM9EiiaAbaWvwjHj9fQQLAX5ZsfHyvmYUNRAdNC8")
+  initAmount: { mintAmount: BN; offset: BN } = {
+    mintAmount: new BN(10000000000),
+    offset: new BN(20000000000),
+  }
 ) {
   const [auth] = await getAuthAddress(program.programId);
-  const [poolAddress] = await getPoolAddress(
-    configAddress,
-    token0,
-    token1,
-    program.programId
-  );
+  const [poolAddress] = await getPoolAddress(mint, program.programId);
   const [lpMintAddress] = await getPoolLpMintAddress(
     poolAddress,
     program.programId
   );
-  const [vault0] = await getPoolVaultAddress(
+  const [tokenMintVault] = await getPoolVaultAddress(
     poolAddress,
-    token0,
+    mint,
     program.programId
   );
-  const [vault1] = await getPoolVaultAddress(
+  const [tokenQuoteVault] = await getPoolVaultAddress(
     poolAddress,
-    token1,
+    quoteMint,
     program.programId
   );
-  const [creatorLpTokenAddress] = await PublicKey.findProgramAddress(
-    [
-      creator.publicKey.toBuffer(),
-      TOKEN_PROGRAM_ID.toBuffer(),
-      lpMintAddress.toBuffer(),
-    ],
-    ASSOCIATED_PROGRAM_ID
-  );
-
   const [observationAddress] = await getOrcleAccountAddress(
     poolAddress,
     program.programId
   );
 
-  const creatorToken0 = getAssociatedTokenAddressSync(
-    token0,
+  const creatorTokenMint = getAssociatedTokenAddressSync(
+    mint,
     creator.publicKey,
     false,
-    token0Program
+    mintProgram
   );
-  const creatorToken1 = getAssociatedTokenAddressSync(
-    token1,
+  const creatorTokenQuote = getAssociatedTokenAddressSync(
+    quoteMint,
     creator.publicKey,
     false,
-    token1Program
+    quoteProgram
   );
+  // The initial liquidity is locked in the pool, the creator receives no lp tokens
   await program.methods
-    .initialize(initAmount.initAmount0, initAmount.initAmount1, new BN(0))
-    .accounts({
+    .initialize(initAmount.mintAmount, initAmount.offset, new BN(0), null, null)
+    .accountsPartial({
       creator: creator.publicKey,
       ammConfig: configAddress,
       authority: auth,
       poolState: poolAddress,
-      token0Mint: token0,
-      token1Mint: token1,
+      mint,
+      quoteMint,
       lpMint: lpMintAddress,
-      creatorToken0,
-      creatorToken1,
-      creatorLpToken: creatorLpTokenAddress,
-      token0Vault: vault0,
-      token1Vault: vault1,
-      createPoolFee,
+      creatorTokenMint,
+      creatorTokenQuote,
+      tokenMintVault,
+      tokenQuoteVault,
       observationState: observationAddress,
       tokenProgram: TOKEN_PROGRAM_ID,
-      token0Program: token0Program,
-      token1Program: token1Program,
+      mintTokenProgram: mintProgram,
+      quoteTokenProgram: quoteProgram,
       systemProgram: SystemProgram.programId,
       rent: SYSVAR_RENT_PUBKEY,
     })
@@ -347,77 +335,60 @@ export async function initialize(
 export async function deposit(
   program: Program<RaydiumCpSwap>,
   owner: Signer,
-  configAddress: PublicKey,
-  token0: PublicKey,
-  token0Program: PublicKey,
-  token1: PublicKey,
-  token1Program: PublicKey,
+  poolAddress: PublicKey,
   lp_token_amount: BN,
-  maximum_token_0_amount: BN,
-  maximum_token_1_amount: BN,
+  maximum_mint_amount: BN,
+  maximum_quote_amount: BN,
   confirmOptions?: ConfirmOptions
 ) {
   const [auth] = await getAuthAddress(program.programId);
-  const [poolAddress] = await getPoolAddress(
-    configAddress,
-    token0,
-    token1,
-    program.programId
-  );
+  const poolState = await program.account.poolState.fetch(poolAddress);
 
-  const [lpMintAddress] = await getPoolLpMintAddress(
-    poolAddress,
-    program.programId
-  );
-  const [vault0] = await getPoolVaultAddress(
-    poolAddress,
-    token0,
-    program.programId
-  );
-  const [vault1] = await getPoolVaultAddress(
-    poolAddress,
-    token1,
-    program.programId
-  );
-  const [ownerLpToken] = await PublicKey.findProgramAddress(
-    [
-      owner.publicKey.toBuffer(),
-      TOKEN_PROGRAM_ID.toBuffer(),
-      lpMintAddress.toBuffer(),
-    ],
-    ASSOCIATED_PROGRAM_ID
+  const ownerLpToken = getAssociatedTokenAddressSync(
+    poolState.lpMint,
+    owner.publicKey,
+    false,
+    TOKEN_PROGRAM_ID
   );
-
-  const onwerToken0 = getAssociatedTokenAddressSync(
-    token0,
+  const ownerTokenMint = getAssociatedTokenAddressSync(
+    poolState.mint,
     owner.publicKey,
     false,
-    token0Program
+    poolState.mintTokenProgram
   );
-  const onwerToken1 = getAssociatedTokenAddressSync(
-    token1,
+  const ownerTokenQuote = getAssociatedTokenAddressSync(
+    poolState.quoteMint,
     owner.publicKey,
     false,
-    token1Program
+    poolState.quoteTokenProgram
   );
 
   const tx = await program.methods
-    .deposit(lp_token_amount, maximum_token_0_amount, maximum_token_1_amount)
-    .accounts({
+    .deposit(lp_token_amount, maximum_mint_amount, maximum_quote_amount)
+    .accountsPartial({
       owner: owner.publicKey,
       authority: auth,
       poolState: poolAddress,
       ownerLpToken,
-      token0Account: onwerToken0,
-      token1Account: onwerToken1,
-      token0Vault: vault0,
-      token1Vault: vault1,
+      tokenMintAccount: ownerTokenMint,
+      tokenQuoteAccount: ownerTokenQuote,
+      tokenMintVault: poolState.tokenMintVault,
+      tokenQuoteVault: poolState.tokenQuoteVault,
       tokenProgram: TOKEN_PROGRAM_ID,
       tokenProgram2022: TOKEN_2022_PROGRAM_ID,
-      vault0Mint: token0,
-      vault1Mint: token1,
-      lpMint: lpMintAddress,
+      vaultMint: poolState.mint,
+      vaultQuoteMint: poolState.quoteMint,
+      lpMint: poolState.lpMint,
     })
+    .preInstructions([
+      createAssociatedTokenAccountIdempotentInstruction(
+        owner.publicKey,
+        ownerLpToken,
+        owner.publicKey,
+        poolState.lpMint,
+        TOKEN_PROGRAM_ID
+      ),
+    ])
     .rpc(confirmOptions);
   return tx;
 }
@@ -425,80 +396,52 @@ export async function deposit(
 export async function withdraw(
   program: Program<RaydiumCpSwap>,
   owner: Signer,
-  configAddress: PublicKey,
-  token0: PublicKey,
-  token0Program: PublicKey,
-  token1: PublicKey,
-  token1Program: PublicKey,
+  poolAddress: PublicKey,
   lp_token_amount: BN,
-  minimum_token_0_amount: BN,
-  minimum_token_1_amount: BN,
+  minimum_mint_amount: BN,
+  minimum_quote_amount: BN,
   confirmOptions?: ConfirmOptions
 ) {
   const [auth] = await getAuthAddress(program.programId);
-  const [poolAddress] = await getPoolAddress(
-    configAddress,
-    token0,
-    token1,
-    program.programId
-  );
+  const poolState = await program.account.poolState.fetch(poolAddress);
 
-  const [lpMintAddress] = await getPoolLpMintAddress(
-    poolAddress,
-    program.programId
-  );
-  const [vault0] = await getPoolVaultAddress(
-    poolAddress,
-    token0,
-    program.programId
-  );
-  const [vault1] = await getPoolVaultAddress(
-    poolAddress,
-    token1,
-    program.programId
-  );
-  const [ownerLpToken] = await PublicKey.findProgramAddress(
-    [
-      owner.publicKey.toBuffer(),
-      TOKEN_PROGRAM_ID.toBuffer(),
-      lpMintAddress.toBuffer(),
-    ],
-    ASSOCIATED_PROGRAM_ID
+  const ownerLpToken = getAssociatedTokenAddressSync(
+    poolState.lpMint,
+    owner.publicKey,
+    false,
+    TOKEN_PROGRAM_ID
   );
-
-  const onwerToken0 = getAssociatedTokenAddressSync(
-    token0,
+  const ownerTokenMint = getAssociatedTokenAddressSync(
+    poolState.mint,
     owner.publicKey,
     false,
-    token0Program
+    poolState.mintTokenProgram
   );
-  const onwerToken1 = getAssociatedTokenAddressSync(
-    token1,
+  const ownerTokenQuote = getAssociatedTokenAddressSync(
+    poolState.quoteMint,
     owner.publicKey,
     false,
-    token1Program
+    poolState.quoteTokenProgram
   );
 
   const tx = await program.methods
-    .withdraw(lp_token_amount, minimum_token_0_amount, minimum_token_1_amount)
-    .accounts({
+    .withdraw(lp_token_amount, minimum_mint_amount, minimum_quote_amount)
+    .accountsPartial({
       owner: owner.publicKey,
       authority: auth,
       poolState: poolAddress,
       ownerLpToken,
-      token0Account: onwerToken0,
-      token1Account: onwerToken1,
-      token0Vault: vault0,
-      token1Vault: vault1,
+      tokenMintAccount: ownerTokenMint,
+      tokenQuoteAccount: ownerTokenQuote,
+      tokenMintVault: poolState.tokenMintVault,
+      tokenQuoteVault: poolState.tokenQuoteVault,
       tokenProgram: TOKEN_PROGRAM_ID,
       tokenProgram2022: TOKEN_2022_PROGRAM_ID,
-      vault0Mint: token0,
-      vault1Mint: token1,
-      lpMint: lpMintAddress,
-      memoProgram: new PublicKey("MemoSq4gqABAXKb96qnH8TysNcWxMyWCqXgDLGmfcHr"),
+      vaultMint: poolState.mint,
+      vaultQuoteMint: poolState.quoteMint,
+      lpMint: poolState.lpMint,
     })
-    .rpc(confirmOptions)
-    .catch();
+    .rpc(confirmOptions);
 
   return tx;
 }
@@ -506,7 +449,7 @@ export async function withdraw(
 export async function swap_base_input(
   program: Program<RaydiumCpSwap>,
   owner: Signer,
-  configAddress: PublicKey,
+  poolAddress: PublicKey,
   inputToken: PublicKey,
   inputTokenProgram: PublicKey,
   outputToken: PublicKey,
@@ -516,23 +459,10 @@ export async function swap_base_input(
   confirmOptions?: ConfirmOptions
 ) {
   const [auth] = await getAuthAddress(program.programId);
-  const [poolAddress] = await getPoolAddress(
-    configAddress,
-    inputToken,
-    outputToken,
-    program.programId
-  );
-
-  const [inputVault] = await getPoolVaultAddress(
-    poolAddress,
-    inputToken,
-    program.programId
-  );
-  const [outputVault] = await getPoolVaultAddress(
-    poolAddress,
-    outputToken,
-    program.programId
-  );
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault] = inputToken.equals(poolState.mint)
+    ? [poolState.tokenMintVault, poolState.tokenQuoteVault]
+    : [poolState.tokenQuoteVault, poolState.tokenMintVault];
 
   const inputTokenAccount = getAssociatedTokenAddressSync(
     inputToken,
@@ -546,17 +476,24 @@ export async function swap_base_input(
     false,
     outputTokenProgram
   );
-  const [observationAddress] = await getOrcleAccountAddress(
+  // Tracks the buys of the owner while the pool caps buys per wallet
+  const [userPurchaseState] = await getUserPurchaseAddress(
     poolAddress,
+    owner.publicKey,
+    program.programId
+  );
+  // Only used to wrap native SOL when a token account is left out
+  const [nativeSolAccount] = await getNativeSolAddress(
+    owner.publicKey,
     program.programId
   );
 
   const tx = await program.methods
-    .swapBaseInput(amount_in, minimum_amount_out)
-    .accounts({
+    .swapBaseInput(amount_in, minimum_amount_out, null, null)
+    .accountsPartial({
       payer: owner.publicKey,
       authority: auth,
-      ammConfig: configAddress,
+      ammConfig: poolState.ammConfig,
       poolState: poolAddress,
       inputTokenAccount,
       outputTokenAccount,
@@ -566,7 +503,10 @@ export async function swap_base_input(
       outputTokenProgram: outputTokenProgram,
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
-      observationState: observationAddress,
+      observationState: poolState.observationKey,
+      userPurchaseState,
+      systemProgram: SystemProgram.programId,
+      nativeSolAccount,
     })
     .rpc(confirmOptions);
 
@@ -576,33 +516,20 @@ export async function swap_base_input(
 export async function swap_base_output(
   program: Program<RaydiumCpSwap>,
   owner: Signer,
-  configAddress: PublicKey,
+  poolAddress: PublicKey,
   inputToken: PublicKey,
   inputTokenProgram: PublicKey,
   outputToken: PublicKey,
   outputTokenProgram: PublicKey,
-  amount_out_less_fee: BN,
+  amount_out: BN,
   max_amount_in: BN,
   confirmOptions?: ConfirmOptions
 ) {
   const [auth] = await getAuthAddress(program.programId);
-  const [poolAddress] = await getPoolAddress(
-    configAddress,
-    inputToken,
-    outputToken,
-    program.programId
-  );
-
-  const [inputVault] = await getPoolVaultAddress(
-    poolAddress,
-    inputToken,
-    program.programId
-  );
-  const [outputVault] = await getPoolVaultAddress(
-    poolAddress,
-    outputToken,
-    program.programId
-  );
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault] = inputToken.equals(poolState.mint)
+    ? [poolState.tokenMintVault, poolState.tokenQuoteVault]
+    : [poolState.tokenQuoteVault, poolState.tokenMintVault];
 
   const inputTokenAccount = getAssociatedTokenAddressSync(
     inputToken,
@@ -616,17 +543,24 @@ export async function swap_base_output(
     false,
     outputTokenProgram
   );
-  const [observationAddress] = await getOrcleAccountAddress(
+  // Tracks the buys of the owner while the pool caps buys per wallet
+  const [userPurchaseState] = await getUserPurchaseAddress(
     poolAddress,
+    owner.publicKey,
+    program.programId
+  );
+  // Only used to wrap native SOL when a token account is left out
+  const [nativeSolAccount] = await getNativeSolAddress(
+    owner.publicKey,
     program.programId
   );
 
   const tx = await program.methods
-    .swapBaseOutput(max_amount_in, amount_out_less_fee)
-    .accounts({
+    .swapBaseOutput(max_amount_in, amount_out, null, null)
+    .accountsPartial({
       payer: owner.publicKey,
       authority: auth,
-      ammConfig: configAddress,
+      ammConfig: poolState.ammConfig,
       poolState: poolAddress,
       inputTokenAccount,
       outputTokenAccount,
@@ -636,8 +570,264 @@ export async function swap_base_output(
       outputTokenProgram: outputTokenProgram,
       inputTokenMint: inputToken,
       outputTokenMint: outputToken,
-      observationState: observationAddress,
+      observationState: poolState.observationKey,
+      userPurchaseState,
+      systemProgram: SystemProgram.programId,
+      nativeSolAccount,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function collect_creator_fees(
+  program: Program<RaydiumCpSwap>,
+  owner: Signer,
+  poolAddress: PublicKey,
+  amount_mint_requested: BN | null,
+  amount_quote_requested: BN | null,
+  confirmOptions?: ConfirmOptions
+) {
+  const [auth] = await getAuthAddress(program.programId);
+  const poolState = await program.account.poolState.fetch(poolAddress);
+
+  const tx = await program.methods
+    .collectCreatorFees(amount_mint_requested, amount_quote_requested)
+    .accountsPartial({
+      poolCreator: owner.publicKey,
+      authority: auth,
+      poolState: poolAddress,
+      tokenMintVault: poolState.tokenMintVault,
+      tokenQuoteVault: poolState.tokenQuoteVault,
+      vaultMint: poolState.mint,
+      vaultQuoteMint: poolState.quoteMint,
+      recipientTokenMintAccount: getAssociatedTokenAddressSync(
+        poolState.mint,
+        owner.publicKey,
+        false,
+        poolState.mintTokenProgram
+      ),
+      recipientTokenQuoteAccount: getAssociatedTokenAddressSync(
+        poolState.quoteMint,
+        owner.publicKey,
+        false,
+        poolState.quoteTokenProgram
+      ),
+      mintTokenProgram: poolState.mintTokenProgram,
+      quoteTokenProgram: poolState.quoteTokenProgram,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function collect_protocol_fees(
+  program: Program<RaydiumCpSwap>,
+  owner: Signer,
+  poolAddress: PublicKey,
+  amount_mint_requested: BN | null,
+  amount_quote_requested: BN | null,
+  confirmOptions?: ConfirmOptions
+) {
+  const [auth] = await getAuthAddress(program.programId);
+  const poolState = await program.account.poolState.fetch(poolAddress);
+
+  const tx = await program.methods
+    .collectProtocolFees(amount_mint_requested, amount_quote_requested)
+    .accountsPartial({
+      owner: owner.publicKey,
+      authority: auth,
+      poolState: poolAddress,
+      ammConfig: poolState.ammConfig,
+      tokenMintVault: poolState.tokenMintVault,
+      tokenQuoteVault: poolState.tokenQuoteVault,
+      vaultMint: poolState.mint,
+      vaultQuoteMint: poolState.quoteMint,
+      recipientTokenMintAccount: getAssociatedTokenAddressSync(
+        poolState.mint,
+        owner.publicKey,
+        false,
+        poolState.mintTokenProgram
+      ),
+      recipientTokenQuoteAccount: getAssociatedTokenAddressSync(
+        poolState.quoteMint,
+        owner.publicKey,
+        false,
+        poolState.quoteTokenProgram
+      ),
+      mintTokenProgram: poolState.mintTokenProgram,
+      quoteTokenProgram: poolState.quoteTokenProgram,
+    })
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+export async function quote_base_input(
+  program: Program<RaydiumCpSwap>,
+  poolAddress: PublicKey,
+  inputToken: PublicKey,
+  outputToken: PublicKey,
+  amount_in: BN
+) {
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault] = inputToken.equals(poolState.mint)
+    ? [poolState.tokenMintVault, poolState.tokenQuoteVault]
+    : [poolState.tokenQuoteVault, poolState.tokenMintVault];
+
+  return await program.methods
+    .quoteBaseInput(amount_in, false, null)
+    .accountsPartial({
+      ammConfig: poolState.ammConfig,
+      poolState: poolAddress,
+      inputVault,
+      outputVault,
+      inputTokenMint: inputToken,
+      outputTokenMint: outputToken,
+    })
+    .view();
+}
+
+export async function quote_base_output(
+  program: Program<RaydiumCpSwap>,
+  poolAddress: PublicKey,
+  inputToken: PublicKey,
+  outputToken: PublicKey,
+  amount_out: BN
+) {
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault] = inputToken.equals(poolState.mint)
+    ? [poolState.tokenMintVault, poolState.tokenQuoteVault]
+    : [poolState.tokenQuoteVault, poolState.tokenMintVault];
+
+  return await program.methods
+    .quoteBaseOutput(amount_out, false, null)
+    .accountsPartial({
+      ammConfig: poolState.ammConfig,
+      poolState: poolAddress,
+      inputVault,
+      outputVault,
+      inputTokenMint: inputToken,
+      outputTokenMint: outputToken,
+    })
+    .view();
+}
+
+export async function flash_swap(
+  program: Program<RaydiumCpSwap>,
+  owner: Signer,
+  poolAddress: PublicKey,
+  inputToken: PublicKey,
+  outputToken: PublicKey,
+  outputTokenProgram: PublicKey,
+  amount_out_less_fee: BN,
+  callbackProgram: PublicKey,
+  data: Buffer,
+  remainingAccounts: AccountMeta[],
+  confirmOptions?: ConfirmOptions
+) {
+  const [auth] = await getAuthAddress(program.programId);
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault] = inputToken.equals(poolState.mint)
+    ? [poolState.tokenMintVault, poolState.tokenQuoteVault]
+    : [poolState.tokenQuoteVault, poolState.tokenMintVault];
+
+  const outputTokenAccount = getAssociatedTokenAddressSync(
+    outputToken,
+    owner.publicKey,
+    false,
+    outputTokenProgram
+  );
+
+  const tx = await program.methods
+    .flashSwap(amount_out_less_fee, data)
+    .accountsPartial({
+      payer: owner.publicKey,
+      authority: auth,
+      ammConfig: poolState.ammConfig,
+      poolState: poolAddress,
+      outputTokenAccount,
+      inputVault,
+      outputVault,
+      outputTokenProgram,
+      inputTokenMint: inputToken,
+      outputTokenMint: outputToken,
+      observationState: poolState.observationKey,
+      callbackProgram,
+    })
+    .remainingAccounts(remainingAccounts)
+    .rpc(confirmOptions);
+
+  return tx;
+}
+
+// The accounts of one pool of a routed swap, taking `inputToken`
+export async function route_hop_accounts(
+  program: Program<RaydiumCpSwap>,
+  poolAddress: PublicKey,
+  inputToken: PublicKey
+): Promise<AccountMeta[]> {
+  const poolState = await program.account.poolState.fetch(poolAddress);
+  const [inputVault, outputVault, outputMint, outputTokenProgram] =
+    inputToken.equals(poolState.mint)
+      ? [
+          poolState.tokenMintVault,
+          poolState.tokenQuoteVault,
+          poolState.quoteMint,
+          poolState.quoteTokenProgram,
+        ]
+      : [
+          poolState.tokenQuoteVault,
+          poolState.tokenMintVault,
+          poolState.mint,
+          poolState.mintTokenProgram,
+        ];
+  return [
+    { pubkey: poolState.ammConfig, isSigner: false, isWritable: false },
+    { pubkey: poolAddress, isSigner: false, isWritable: true },
+    { pubkey: inputVault, isSigner: false, isWritable: true },
+    { pubkey: outputVault, isSigner: false, isWritable: true },
+    { pubkey: outputMint, isSigner: false, isWritable: false },
+    { pubkey: outputTokenProgram, isSigner: false, isWritable: false },
+    { pubkey: poolState.observationKey, isSigner: false, isWritable: true },
+  ];
+}
+
+export async function swap_route(
+  program: Program<RaydiumCpSwap>,
+  owner: Signer,
+  inputToken: PublicKey,
+  inputTokenProgram: PublicKey,
+  outputToken: PublicKey,
+  outputTokenProgram: PublicKey,
+  amount_in: BN,
+  minimum_amount_out: BN,
+  hopAccounts: AccountMeta[],
+  confirmOptions?: ConfirmOptions
+) {
+  const [auth] = await getAuthAddress(program.programId);
+
+  const tx = await program.methods
+    .swapRoute(amount_in, minimum_amount_out)
+    .accountsPartial({
+      payer: owner.publicKey,
+      authority: auth,
+      inputTokenAccount: getAssociatedTokenAddressSync(
+        inputToken,
+        owner.publicKey,
+        false,
+        inputTokenProgram
+      ),
+      outputTokenAccount: getAssociatedTokenAddressSync(
+        outputToken,
+        owner.publicKey,
+        false,
+        outputTokenProgram
+      ),
+      inputTokenProgram,
+      inputTokenMint: inputToken,
     })
+    .remainingAccounts(hopAccounts)
     .rpc(confirmOptions);
 
   return tx;
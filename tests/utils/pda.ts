@@ -1,5 +1,6 @@
 import * as anchor from "@coral-xyz/anchor";
 import { PublicKey } from "@solana/web3.js";
+export const ADMIN_SEED = Buffer.from(anchor.utils.bytes.utf8.encode("admin"));
 export const AMM_CONFIG_SEED = Buffer.from(
   anchor.utils.bytes.utf8.encode("amm_config")
 );
@@ -25,6 +26,14 @@ export const ORACLE_SEED = Buffer.from(
   anchor.utils.bytes.utf8.encode("observation")
 );
 
+export const USER_PURCHASE_SEED = Buffer.from(
+  anchor.utils.bytes.utf8.encode("user_purchase")
+);
+
+export const NATIVE_SOL_SEED = Buffer.from(
+  anchor.utils.bytes.utf8.encode("native_sol")
+);
+
 export function u16ToBytes(num: number) {
   const arr = new ArrayBuffer(2);
   const view = new DataView(arr);
@@ -53,6 +62,16 @@ export function i32ToBytes(num: number) {
   return new Uint8Array(arr);
 }
 
+export async function getAdminAddress(
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [ADMIN_SEED],
+    programId
+  );
+  return [address, bump];
+}
+
 export async function getAmmConfigAddress(
   index: number,
   programId: PublicKey
@@ -75,18 +94,11 @@ export async function getAuthAddress(
 }
 
 export async function getPoolAddress(
-  ammConfig: PublicKey,
-  tokenMint0: PublicKey,
-  tokenMint1: PublicKey,
+  mint: PublicKey,
   programId: PublicKey
 ): Promise<[PublicKey, number]> {
   const [address, bump] = await PublicKey.findProgramAddress(
-    [
-      POOL_SEED,
-      ammConfig.toBuffer(),
-      tokenMint0.toBuffer(),
-      tokenMint1.toBuffer(),
-    ],
+    [POOL_SEED, mint.toBuffer()],
     programId
   );
   return [address, bump];
@@ -125,3 +137,26 @@ export async function getOrcleAccountAddress(
   );
   return [address, bump];
 }
+
+export async function getUserPurchaseAddress(
+  pool: PublicKey,
+  user: PublicKey,
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [USER_PURCHASE_SEED, pool.toBuffer(), user.toBuffer()],
+    programId
+  );
+  return [address, bump];
+}
+
+export async function getNativeSolAddress(
+  payer: PublicKey,
+  programId: PublicKey
+): Promise<[PublicKey, number]> {
+  const [address, bump] = await PublicKey.findProgramAddress(
+    [NATIVE_SOL_SEED, payer.toBuffer()],
+    programId
+  );
+  return [address, bump];
+}
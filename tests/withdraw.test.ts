@@ -25,11 +25,9 @@ describe("withdraw test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 6,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       { transferFeeBasisPoints: 0, MaxFee: 0 }
     );
@@ -37,11 +35,7 @@ describe("withdraw test", () => {
     await deposit(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity,
       new BN(10000000000),
       new BN(20000000000)
@@ -50,14 +44,10 @@ describe("withdraw test", () => {
     await withdraw(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity.divn(2),
       new BN(10000000),
-      new BN(1000000),
+      new BN(0),
       confirmOptions
     );
     const newPoolState = await program.account.poolState.fetch(poolAddress);
@@ -70,11 +60,9 @@ describe("withdraw test", () => {
       anchor.getProvider().connection,
       owner,
       {
-        config_index: 0,
+        config_index: 7,
         tradeFeeRate: new BN(10),
         protocolFeeRate: new BN(1000),
-        fundFeeRate: new BN(25000),
-        create_fee: new BN(0),
       },
       { transferFeeBasisPoints: 0, MaxFee: 0 }
     );
@@ -86,22 +74,18 @@ describe("withdraw test", () => {
       poolVault1TokenAccount: poolVault1TokenAccountBefore,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
 
     await deposit(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity,
       new BN(10000000000),
       new BN(20000000000)
@@ -110,14 +94,10 @@ describe("withdraw test", () => {
     await withdraw(
       program,
       owner,
-      poolState.ammConfig,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
+      poolAddress,
       liquidity,
       new BN(10000000),
-      new BN(1000000),
+      new BN(0),
       confirmOptions
     );
 
@@ -131,12 +111,12 @@ describe("withdraw test", () => {
       poolVault1TokenAccount: poolVault1TokenAccountAfter,
     } = await getUserAndPoolVaultAmount(
       owner.publicKey,
-      poolState.token0Mint,
-      poolState.token0Program,
-      poolState.token1Mint,
-      poolState.token1Program,
-      poolState.token0Vault,
-      poolState.token1Vault
+      poolState.mint,
+      poolState.mintTokenProgram,
+      poolState.quoteMint,
+      poolState.quoteTokenProgram,
+      poolState.tokenMintVault,
+      poolState.tokenQuoteVault
     );
 
     assert(